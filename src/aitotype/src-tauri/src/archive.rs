@@ -0,0 +1,283 @@
+//! 录音归档模块（默认关闭）
+//!
+//! 开启后每次录音会转为单声道并以 gzip 压缩保存在 app data 目录下，
+//! 由转写日志的 `audio_file` 字段关联，便于换模型重跑。
+//! 按最长保留天数与总大小上限清理旧文件。
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hound::{WavReader, WavSpec, WavWriter};
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+pub const ARCHIVE_DIRNAME: &str = "audio";
+pub const ARCHIVE_EXTENSION: &str = "wav.gz";
+
+/// 清理结果
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct PruneResult {
+    pub removed_files: usize,
+    pub removed_bytes: u64,
+}
+
+/// 获取归档目录
+pub fn archive_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(ARCHIVE_DIRNAME))
+        .map_err(|e| format!("获取数据目录失败: {:?}", e))
+}
+
+fn validate_file_name(file_name: &str) -> Result<(), String> {
+    let valid = file_name.ends_with(ARCHIVE_EXTENSION)
+        && file_name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || ch == '.')
+        && !file_name.contains("..");
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("无效的归档文件名: {}", file_name))
+    }
+}
+
+/// 把 WAV 转为单声道 16-bit 并 gzip 压缩
+fn compress_wav(source: &Path) -> Result<Vec<u8>, String> {
+    let mut reader = WavReader::open(source).map_err(|e| format!("读取录音失败: {:?}", e))?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+
+    let samples: Vec<i16> = reader
+        .samples::<i16>()
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("解析录音失败: {:?}", e))?;
+
+    let mut wav_bytes = Cursor::new(Vec::new());
+    {
+        let mono_spec = WavSpec {
+            channels: 1,
+            sample_rate: spec.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = WavWriter::new(&mut wav_bytes, mono_spec)
+            .map_err(|e| format!("写入录音失败: {:?}", e))?;
+        for frame in samples.chunks(channels) {
+            let sum: i32 = frame.iter().map(|&s| s as i32).sum();
+            writer
+                .write_sample((sum / frame.len() as i32) as i16)
+                .map_err(|e| format!("写入录音失败: {:?}", e))?;
+        }
+        writer
+            .finalize()
+            .map_err(|e| format!("写入录音失败: {:?}", e))?;
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(wav_bytes.get_ref())
+        .map_err(|e| format!("压缩录音失败: {:?}", e))?;
+    encoder
+        .finish()
+        .map_err(|e| format!("压缩录音失败: {:?}", e))
+}
+
+/// 归档一段录音，返回归档文件名（相对归档目录）
+pub fn archive_recording(app: &tauri::AppHandle, wav_path: &Path) -> Result<String, String> {
+    let dir = archive_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建归档目录失败: {:?}", e))?;
+
+    let file_name = format!(
+        "{}.{}",
+        chrono::Local::now().format("%Y%m%d%H%M%S%3f"),
+        ARCHIVE_EXTENSION
+    );
+    let compressed = compress_wav(wav_path)?;
+    std::fs::write(dir.join(&file_name), compressed)
+        .map_err(|e| format!("保存归档录音失败: {:?}", e))?;
+    Ok(file_name)
+}
+
+/// 把归档录音解压为临时 WAV 文件，返回其路径（调用方用完后必须删除）
+pub fn restore_to_temp_wav(app: &tauri::AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    validate_file_name(file_name)?;
+    let source = archive_dir(app)?.join(file_name);
+    let compressed = std::fs::read(&source).map_err(|e| format!("读取归档录音失败: {:?}", e))?;
+
+    let mut wav_bytes = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut wav_bytes)
+        .map_err(|e| format!("解压归档录音失败: {:?}", e))?;
+
+    let target = std::env::temp_dir().join(format!(
+        "aitotype_archive_{}_{}.wav",
+        std::process::id(),
+        file_name.trim_end_matches(&format!(".{}", ARCHIVE_EXTENSION))
+    ));
+    std::fs::write(&target, wav_bytes).map_err(|e| format!("写入临时录音失败: {:?}", e))?;
+    Ok(target)
+}
+
+/// 删除一个归档录音（文件不存在视为成功）
+pub fn remove_archived(app: &tauri::AppHandle, file_name: &str) -> Result<(), String> {
+    validate_file_name(file_name)?;
+    let path = archive_dir(app)?.join(file_name);
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("删除归档录音失败: {:?}", e))?;
+    }
+    Ok(())
+}
+
+/// 归档目录中的一个文件
+#[derive(Debug, Clone)]
+struct ArchivedFile {
+    path: PathBuf,
+    modified: SystemTime,
+    size: u64,
+}
+
+/// 计算需要删除的文件：先按天数淘汰，再从最旧开始删除直到总大小不超过上限
+///
+/// `max_age_days` / `max_total_mb` 为 0 表示不限制。
+fn select_files_to_prune(
+    mut files: Vec<ArchivedFile>,
+    now: SystemTime,
+    max_age_days: u32,
+    max_total_mb: u32,
+) -> Vec<ArchivedFile> {
+    files.sort_by_key(|file| file.modified);
+
+    let mut removed = Vec::new();
+    let mut kept = Vec::new();
+    let max_age = Duration::from_secs(u64::from(max_age_days) * 24 * 60 * 60);
+    for file in files {
+        let age = now.duration_since(file.modified).unwrap_or_default();
+        if max_age_days > 0 && age > max_age {
+            removed.push(file);
+        } else {
+            kept.push(file);
+        }
+    }
+
+    if max_total_mb > 0 {
+        let max_bytes = u64::from(max_total_mb) * 1024 * 1024;
+        let mut total: u64 = kept.iter().map(|file| file.size).sum();
+        let mut kept_iter = kept.into_iter();
+        while total > max_bytes {
+            match kept_iter.next() {
+                Some(file) => {
+                    total = total.saturating_sub(file.size);
+                    removed.push(file);
+                }
+                None => break,
+            }
+        }
+    }
+
+    removed
+}
+
+/// 按保留策略清理归档录音
+pub fn prune(
+    app: &tauri::AppHandle,
+    max_age_days: u32,
+    max_total_mb: u32,
+) -> Result<PruneResult, String> {
+    let dir = archive_dir(app)?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(PruneResult::default()),
+    };
+
+    let files: Vec<ArchivedFile> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .ends_with(ARCHIVE_EXTENSION)
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some(ArchivedFile {
+                path: entry.path(),
+                modified: metadata.modified().ok()?,
+                size: metadata.len(),
+            })
+        })
+        .collect();
+
+    let mut result = PruneResult::default();
+    for file in select_files_to_prune(files, SystemTime::now(), max_age_days, max_total_mb) {
+        match std::fs::remove_file(&file.path) {
+            Ok(_) => {
+                result.removed_files += 1;
+                result.removed_bytes += file.size;
+            }
+            Err(e) => warn!("archive prune: remove failed {:?}: {:?}", file.path, e),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+    const MB: u64 = 1024 * 1024;
+
+    fn file(name: &str, age_days: u64, size_mb: u64, now: SystemTime) -> ArchivedFile {
+        ArchivedFile {
+            path: PathBuf::from(name),
+            modified: now - Duration::from_secs(age_days * DAY),
+            size: size_mb * MB,
+        }
+    }
+
+    fn names(files: &[ArchivedFile]) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| file.path.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn prunes_by_age() {
+        let now = SystemTime::now();
+        let files = vec![file("old", 40, 1, now), file("new", 1, 1, now)];
+        let removed = select_files_to_prune(files, now, 30, 0);
+        assert_eq!(names(&removed), vec!["old"]);
+    }
+
+    #[test]
+    fn prunes_oldest_until_under_size_limit() {
+        let now = SystemTime::now();
+        let files = vec![
+            file("c", 1, 4, now),
+            file("a", 3, 4, now),
+            file("b", 2, 4, now),
+        ];
+        let removed = select_files_to_prune(files, now, 0, 5);
+        assert_eq!(names(&removed), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn zero_limits_keep_everything() {
+        let now = SystemTime::now();
+        let files = vec![file("a", 400, 900, now)];
+        assert!(select_files_to_prune(files, now, 0, 0).is_empty());
+    }
+
+    #[test]
+    fn rejects_path_like_file_names() {
+        assert!(validate_file_name("20260101120000000.wav.gz").is_ok());
+        assert!(validate_file_name("../config.wav.gz").is_err());
+        assert!(validate_file_name("config.json").is_err());
+    }
+}
@@ -7,11 +7,12 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use hound::{WavSpec, WavWriter};
 use std::sync::{
-    atomic::{AtomicBool, AtomicU32, Ordering},
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
     Arc, Mutex,
 };
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{error, info};
 
 // 全局录音状态
 lazy_static::lazy_static! {
@@ -20,6 +21,8 @@ lazy_static::lazy_static! {
     static ref RECORDING_DONE: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
     static ref AUDIO_LEVEL: Arc<AtomicU32> = Arc::new(AtomicU32::new(0.0_f32.to_bits()));
     static ref CURRENT_DEVICE_NAME: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
+    /// 最近一次 WAV finalize 耗时（毫秒）
+    static ref LAST_FINALIZE_MS: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
 }
 
 const AUDIO_LEVEL_GAIN: f32 = 3.0;
@@ -79,10 +82,13 @@ pub fn start_recording() -> Result<(), String> {
     AUDIO_LEVEL.store(0.0_f32.to_bits(), Ordering::Relaxed);
 
     // 在新线程中进行录音
+    // 录音线程沿用当前录音会话的诊断日志 span
+    let span = tracing::Span::current();
     thread::spawn(move || {
+        let _entered = span.enter();
         let result = do_recording(&output_path);
         if let Err(e) = result {
-            eprintln!("录音错误: {}", e);
+            error!("录音错误: {}", e);
         }
         RECORDING_DONE.store(true, Ordering::SeqCst);
     });
@@ -107,8 +113,8 @@ fn do_recording(output_path: &str) -> Result<(), String> {
     let sample_rate = config.sample_rate().0;
     let channels = config.channels();
 
-    println!("📱 录音设备: {}", device_name);
-    println!("⚙️  采样率: {} Hz, 通道: {}", sample_rate, channels);
+    info!("录音设备: {}", device_name);
+    info!("采样率: {} Hz, 通道: {}", sample_rate, channels);
 
     // WAV 文件配置 - 使用设备的实际参数
     let spec = WavSpec {
@@ -124,7 +130,7 @@ fn do_recording(output_path: &str) -> Result<(), String> {
     let writer = Arc::new(Mutex::new(Some(writer)));
     let writer_clone = writer.clone();
 
-    let err_fn = |err| eprintln!("录音错误: {:?}", err);
+    let err_fn = |err| error!("录音错误: {:?}", err);
 
     let stream = match config.sample_format() {
         cpal::SampleFormat::F32 => device.build_input_stream(
@@ -200,11 +206,16 @@ fn do_recording(output_path: &str) -> Result<(), String> {
     // 完成写入
     if let Ok(mut guard) = writer.lock() {
         if let Some(w) = guard.take() {
+            let finalize_started = Instant::now();
             w.finalize().map_err(|e| format!("保存失败: {:?}", e))?;
+            LAST_FINALIZE_MS.store(
+                finalize_started.elapsed().as_millis() as u64,
+                Ordering::SeqCst,
+            );
         }
     }
 
-    println!("✅ 录音完成: {}", output_path);
+    info!("录音完成: {}", output_path);
     AUDIO_LEVEL.store(0.0_f32.to_bits(), Ordering::Relaxed);
     Ok(())
 }
@@ -223,20 +234,37 @@ fn update_audio_level(rms: f32) {
 
 /// 停止录音并返回音频数据路径
 pub fn stop_recording() -> Result<String, String> {
+    stop_recording_timed().map(|stopped| stopped.path)
+}
+
+/// 停止录音的结果与耗时
+#[derive(Debug, Clone)]
+pub struct StoppedRecording {
+    pub path: String,
+    /// 发出停止信号到录音线程结束（含 finalize）
+    pub stop_ms: u64,
+    /// WAV finalize 耗时
+    pub finalize_ms: u64,
+}
+
+/// 停止录音并返回各阶段耗时
+pub fn stop_recording_timed() -> Result<StoppedRecording, String> {
     if !IS_RECORDING.load(Ordering::SeqCst) {
         return Err("当前没有在录音".to_string());
     }
 
     // 停止录音
+    let stop_started = Instant::now();
+    LAST_FINALIZE_MS.store(0, Ordering::SeqCst);
     IS_RECORDING.store(false, Ordering::SeqCst);
     AUDIO_LEVEL.store(0.0_f32.to_bits(), Ordering::Relaxed);
 
     // 等待录音线程完成
     let mut wait_count = 0;
     while !RECORDING_DONE.load(Ordering::SeqCst) {
-        thread::sleep(Duration::from_millis(50));
+        thread::sleep(Duration::from_millis(10));
         wait_count += 1;
-        if wait_count > 40 {
+        if wait_count > 200 {
             // 最多等待 2 秒
             return Err("等待录音完成超时".to_string());
         }
@@ -244,7 +272,22 @@ pub fn stop_recording() -> Result<String, String> {
 
     // 返回文件路径
     let path = CURRENT_PATH.lock().unwrap();
-    path.clone().ok_or_else(|| "没有录音文件".to_string())
+    let path = path.clone().ok_or_else(|| "没有录音文件".to_string())?;
+    Ok(StoppedRecording {
+        path,
+        stop_ms: stop_started.elapsed().as_millis() as u64,
+        finalize_ms: LAST_FINALIZE_MS.load(Ordering::SeqCst),
+    })
+}
+
+/// 读取 WAV 文件时长（毫秒）
+pub fn wav_duration_ms(path: &str) -> Option<u64> {
+    let reader = hound::WavReader::open(path).ok()?;
+    let sample_rate = u64::from(reader.spec().sample_rate);
+    if sample_rate == 0 {
+        return None;
+    }
+    Some(u64::from(reader.duration()) * 1000 / sample_rate)
 }
 
 /// 获取当前音频电平 (0.0 - 1.0)
@@ -262,3 +305,21 @@ pub fn get_input_device_name() -> String {
         .map(|name| name.clone())
         .unwrap_or_default()
 }
+
+/// 列出所有输入设备名称（用于诊断）
+pub fn list_input_devices() -> Result<Vec<String>, String> {
+    let host = cpal::default_host();
+    let devices = host
+        .input_devices()
+        .map_err(|e| format!("枚举输入设备失败: {:?}", e))?;
+    Ok(devices
+        .map(|device| device.name().unwrap_or_else(|_| "Unknown".to_string()))
+        .collect())
+}
+
+/// 默认输入设备名称
+pub fn default_input_device_name() -> Option<String> {
+    cpal::default_host()
+        .default_input_device()
+        .and_then(|device| device.name().ok())
+}
@@ -0,0 +1,3 @@
+fn main() -> std::process::ExitCode {
+    aitotype_lib::run_cli()
+}
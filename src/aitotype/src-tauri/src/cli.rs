@@ -0,0 +1,410 @@
+//! 命令行工具模块（`aitotype-cli`）
+//!
+//! 不启动 GUI，直接复用转写、易错词、历史记录等逻辑，
+//! 通过 `HeadlessPaths` 读写与 GUI 相同的配置目录。加 `--json` 时输出 JSON，便于脚本处理。
+
+use crate::config::{self, LoadOutcome};
+use crate::corrections::{self, CorrectionHit};
+use crate::db;
+use crate::diagnostics;
+use crate::history::{self, HistoryQuery};
+use crate::paths::HeadlessPaths;
+use crate::pipeline::{self, Pipeline, PipelineContext};
+use crate::profiles;
+use crate::redaction;
+use crate::secrets;
+use crate::stt::SttConfig;
+use crate::timing::PipelineTimer;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// 判定为“有声音”的电平阈值（0.0 - 1.0）
+const SPEECH_LEVEL_THRESHOLD: f32 = 0.05;
+const LEVEL_POLL_INTERVAL_MS: u64 = 50;
+
+#[derive(Parser)]
+#[command(name = "aitotype-cli", version, about = "AItoType 命令行工具")]
+struct Cli {
+    /// 以 JSON 输出结果
+    #[arg(long, global = true)]
+    json: bool,
+    /// 按配置的诊断日志级别输出日志到 stderr（默认只输出警告）
+    #[arg(long, short, global = true)]
+    verbose: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 转写音频文件（含易错词替换与润色）
+    Transcribe {
+        file: PathBuf,
+        /// 跳过 LLM 润色
+        #[arg(long)]
+        no_enhance: bool,
+    },
+    /// 录音并转写；默认按回车结束录音
+    Record {
+        /// 说话后静音一段时间自动结束
+        #[arg(long)]
+        until_silence: bool,
+        /// 自动结束所需的静音时长（毫秒）
+        #[arg(long, default_value_t = 1500)]
+        silence_ms: u64,
+        /// 最长录音时长（秒）
+        #[arg(long, default_value_t = 120)]
+        max_seconds: u64,
+        /// 跳过 LLM 润色
+        #[arg(long)]
+        no_enhance: bool,
+    },
+    /// 管理易错词
+    Corrections {
+        #[command(subcommand)]
+        action: CorrectionsCommand,
+    },
+    /// 查询转写记录
+    Logs {
+        #[command(subcommand)]
+        action: LogsCommand,
+    },
+    /// 测试当前配置的 API 连接
+    TestConnection,
+}
+
+#[derive(Subcommand)]
+enum CorrectionsCommand {
+    /// 列出易错词
+    List,
+    /// 添加错误词到正确词的映射
+    Add { wrong: String, correct: String },
+    /// 删除正确词（或其中一个错误变体）
+    Remove {
+        correct: String,
+        #[arg(long)]
+        variant: Option<String>,
+    },
+    /// 对文本应用易错词替换（不计入命中次数）
+    Apply { text: String },
+}
+
+#[derive(Subcommand)]
+enum LogsCommand {
+    /// 搜索转写记录
+    Search {
+        query: Option<String>,
+        /// 起始日期 YYYY-MM-DD（含）
+        #[arg(long)]
+        from: Option<String>,
+        /// 结束日期 YYYY-MM-DD（含）
+        #[arg(long)]
+        to: Option<String>,
+        /// 只看置顶记录
+        #[arg(long)]
+        pinned: bool,
+        #[arg(long, default_value_t = history::DEFAULT_PAGE_SIZE)]
+        limit: usize,
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+    },
+}
+
+/// 转写结果
+#[derive(Debug, Clone, Serialize)]
+struct TranscribeOutput {
+    text: String,
+    stt_text: String,
+    correction_hits: Vec<CorrectionHit>,
+    enhancement_status: Option<String>,
+    enhancement_error: Option<String>,
+    duration_ms: u64,
+    log_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ErrorOutput {
+    error: String,
+}
+
+/// 读取当前配置方案的配置与 API Key（不写回磁盘，迁移由 GUI 负责）
+fn load_config(paths: &HeadlessPaths) -> (String, SttConfig) {
+    let store = profiles::load_store(paths);
+    let profile = if store.active.is_empty() {
+        profiles::DEFAULT_PROFILE.to_string()
+    } else {
+        store.active
+    };
+
+    let mut config = match config::load(paths) {
+        LoadOutcome::Loaded { config, .. } => *config,
+        LoadOutcome::Reset(payload) => {
+            warn!("配置文件无法解析，使用默认配置: {}", payload.error);
+            crate::initial_stt_config()
+        }
+        LoadOutcome::Missing => crate::initial_stt_config(),
+    };
+    secrets::load_config_keys(paths, &profile, &mut config);
+    (profile, crate::normalize_stt_config(config))
+}
+
+/// 命令行中的流水线输出：保存易错词命中次数并写入转写记录
+struct CliProviders<'a> {
+    paths: &'a HeadlessPaths,
+    profile: &'a str,
+}
+
+impl pipeline::Providers for CliProviders<'_> {
+    fn output(&self, ctx: &mut PipelineContext) {
+        if ctx.corrections_dirty {
+            let _ = corrections::save_corrections(self.paths, &ctx.corrections);
+        }
+        if !ctx.config.log_enabled {
+            return;
+        }
+        let mut entry = ctx.entry.clone();
+        entry.profile = Some(self.profile.to_string());
+        entry.timings = Some(ctx.timer.finish());
+        redaction::mask_log_entry(&mut entry, &ctx.config.redaction);
+        if let Err(e) = db::Store::open(self.paths).and_then(|store| store.insert_entry(&entry)) {
+            warn!("写入转写记录失败: {}", e);
+        }
+    }
+}
+
+/// 按当前配置方案的流水线转写文件并写入转写记录
+async fn transcribe_file(
+    paths: &HeadlessPaths,
+    profile: &str,
+    config: &SttConfig,
+    file: &Path,
+) -> Result<TranscribeOutput, String> {
+    let file_path = file.to_string_lossy().to_string();
+    let mut ctx = PipelineContext::new(
+        config.clone(),
+        file_path.clone(),
+        corrections::load_corrections(paths),
+        PipelineTimer::start(),
+        CancellationToken::new(),
+    );
+    ctx.entry.audio_duration_ms = crate::audio::wav_duration_ms(&file_path);
+    let text = Pipeline::for_config(config)
+        .run(&CliProviders { paths, profile }, &mut ctx)
+        .await?;
+
+    Ok(TranscribeOutput {
+        text,
+        stt_text: ctx.entry.stt_text,
+        correction_hits: ctx.correction_hits,
+        enhancement_status: ctx.entry.enhancement_status,
+        enhancement_error: ctx.entry.enhancement_error,
+        duration_ms: ctx.timer.finish().total_ms.unwrap_or_default(),
+        log_id: ctx.entry.id,
+    })
+}
+
+/// 录音直到按下回车、静音超时或达到最长时长，返回录音文件路径
+fn record(until_silence: bool, silence_ms: u64, max_seconds: u64) -> Result<String, String> {
+    let stop_requested = Arc::new(AtomicBool::new(false));
+    if !until_silence {
+        let stop_requested = stop_requested.clone();
+        std::thread::spawn(move || {
+            let mut line = String::new();
+            let _ = std::io::stdin().read_line(&mut line);
+            stop_requested.store(true, Ordering::SeqCst);
+        });
+        eprintln!("录音中，按回车结束…");
+    } else {
+        eprintln!("录音中，停止说话后自动结束…");
+    }
+
+    crate::audio::start_recording()?;
+    let started = Instant::now();
+    let max_duration = Duration::from_secs(max_seconds.max(1));
+    let silence_limit = Duration::from_millis(silence_ms);
+    let mut heard_speech = false;
+    let mut last_speech = Instant::now();
+
+    while !stop_requested.load(Ordering::SeqCst) && started.elapsed() < max_duration {
+        std::thread::sleep(Duration::from_millis(LEVEL_POLL_INTERVAL_MS));
+        if !until_silence {
+            continue;
+        }
+        if crate::audio::get_audio_level() >= SPEECH_LEVEL_THRESHOLD {
+            heard_speech = true;
+            last_speech = Instant::now();
+        } else if heard_speech && last_speech.elapsed() >= silence_limit {
+            break;
+        }
+    }
+
+    crate::audio::stop_recording()
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("序列化输出失败: {:?}", e),
+    }
+}
+
+/// 按 `--json` 输出 JSON，否则输出文本
+fn print_output<T: Serialize>(json: bool, value: &T, text: impl FnOnce(&T) -> String) {
+    if json {
+        print_json(value);
+    } else {
+        println!("{}", text(value));
+    }
+}
+
+fn print_transcription(json: bool, output: &TranscribeOutput) {
+    print_output(json, output, |output| {
+        if let Some(error) = &output.enhancement_error {
+            eprintln!("润色未生效: {}", error);
+        }
+        output.text.clone()
+    });
+}
+
+async fn execute(cli: Cli, paths: &HeadlessPaths) -> Result<ExitCode, String> {
+    let json = cli.json;
+    match cli.command {
+        Command::Transcribe { file, no_enhance } => {
+            let (profile, mut config) = load_config(paths);
+            config.enhancement_enabled &= !no_enhance;
+            let output = transcribe_file(paths, &profile, &config, &file).await?;
+            print_transcription(json, &output);
+        }
+        Command::Record {
+            until_silence,
+            silence_ms,
+            max_seconds,
+            no_enhance,
+        } => {
+            let (profile, mut config) = load_config(paths);
+            config.enhancement_enabled &= !no_enhance;
+            let file_path = record(until_silence, silence_ms, max_seconds)?;
+            let result = transcribe_file(paths, &profile, &config, Path::new(&file_path)).await;
+            crate::remove_temp_recording(&file_path);
+            print_transcription(json, &result?);
+        }
+        Command::Corrections { action } => {
+            let mut store = corrections::load_corrections(paths);
+            match action {
+                CorrectionsCommand::List => {}
+                CorrectionsCommand::Add { wrong, correct } => {
+                    corrections::add_correction(&mut store, &wrong, &correct)?;
+                    corrections::save_corrections(paths, &store)?;
+                }
+                CorrectionsCommand::Remove { correct, variant } => {
+                    let removed = match variant {
+                        Some(variant) => {
+                            corrections::remove_correction_variant(&mut store, &correct, &variant)
+                        }
+                        None => corrections::remove_correction(&mut store, &correct),
+                    };
+                    if !removed {
+                        return Err(format!("未找到易错词: {}", correct));
+                    }
+                    corrections::save_corrections(paths, &store)?;
+                }
+                CorrectionsCommand::Apply { text } => {
+                    let result = corrections::apply_corrections(&text, &store);
+                    print_output(json, &result, |result| result.text.clone());
+                    return Ok(ExitCode::SUCCESS);
+                }
+            }
+            print_output(json, &store, |store| {
+                store
+                    .corrections
+                    .iter()
+                    .map(|entry| format!("{} <- {}", entry.correct, entry.variants.join(", ")))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        }
+        Command::Logs {
+            action:
+                LogsCommand::Search {
+                    query,
+                    from,
+                    to,
+                    pinned,
+                    limit,
+                    offset,
+                },
+        } => {
+            let query = HistoryQuery {
+                query,
+                start_date: from,
+                end_date: to,
+                pinned_only: pinned,
+                offset,
+                limit: Some(limit),
+            };
+            let page = history::search_store(&db::Store::open(paths)?, &query)?;
+            print_output(json, &page, |page| {
+                page.items
+                    .iter()
+                    .map(|entry| format!("{}\t{}\t{}", entry.timestamp, entry.id, entry.final_text))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        }
+        Command::TestConnection => {
+            let (_, config) = load_config(paths);
+            let result = crate::run_connection_test(&config).await;
+            print_output(json, &result, |result| {
+                let status = if result.success { "OK" } else { "FAILED" };
+                format!(
+                    "{} {} / {} / {}ms: {}",
+                    status, result.provider, result.model, result.latency_ms, result.message
+                )
+            });
+            if !result.success {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+pub fn run() -> ExitCode {
+    crate::load_env_files();
+    let cli = Cli::parse();
+    let paths = HeadlessPaths;
+    let json = cli.json;
+
+    let level = if cli.verbose {
+        load_config(&paths).1.diagnostic_log_level
+    } else {
+        "warn".to_string()
+    };
+    diagnostics::init(&paths, &level);
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("启动异步运行时失败: {:?}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match runtime.block_on(execute(cli, &paths)) {
+        Ok(code) => code,
+        Err(error) => {
+            if json {
+                print_json(&ErrorOutput { error });
+            } else {
+                eprintln!("错误: {}", error);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! 配置文件模块
+//!
+//! `config.json` 带 `version` 字段，加载时按顺序执行迁移函数升级到当前版本；
+//! 写入采用临时文件 + rename，避免写到一半崩溃留下损坏的配置。
+//! 无法解析的配置会备份后重置为默认值，并通知前端。
+
+use crate::paths::AppPaths;
+use crate::policy;
+use crate::stt::{self, SttConfig};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+const CONFIG_FILENAME: &str = "config.json";
+const LEGACY_ENHANCEMENT_OPENROUTER_MODEL: &str = "google/gemini-2.0-flash-001";
+const LEGACY_ENHANCEMENT_PROGRAMMER_PROMPT: &str = "你是程序员语音转文字的润色助手。请按规则处理文本：\n1) 去除口头禅、重复词和无意义停顿词；\n2) 修正技术术语、产品名、代码相关拼写错误；\n3) 保留原意，不扩写、不总结、不补充新信息；\n4) 仅做必要标点与断句优化；\n5) 只输出润色后的最终文本，不要任何解释。\n\n原文：\n{text}";
+
+/// 迁移函数：就地修改 JSON 对象
+type Migration = fn(&mut Map<String, Value>);
+
+/// 按顺序执行，第 i 个函数把版本 i 升级到 i + 1
+const MIGRATIONS: &[Migration] = &[migrate_legacy_enhancement_defaults];
+
+/// 当前配置版本
+pub const CONFIG_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// 配置被重置时发给前端的事件
+pub const CONFIG_RESET_EVENT: &str = "config-reset-event";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigResetPayload {
+    /// 原配置备份路径（备份失败时为空）
+    pub backup_path: Option<String>,
+    pub error: String,
+}
+
+/// 加载结果
+pub enum LoadOutcome {
+    /// 配置文件不存在
+    Missing,
+    /// 已加载；`migrated` 为 true 表示做过迁移，应写回磁盘
+    Loaded {
+        config: Box<SttConfig>,
+        migrated: bool,
+    },
+    /// 配置无法解析，已备份并重置为默认值
+    Reset(ConfigResetPayload),
+}
+
+/// v0 → v1：旧版默认润色模型与“程序员”润色提示词替换为当前默认值
+fn migrate_legacy_enhancement_defaults(config: &mut Map<String, Value>) {
+    let provider = stt::normalize_provider(
+        config
+            .get("enhancement_provider")
+            .and_then(Value::as_str)
+            .unwrap_or_default(),
+    );
+    let is_legacy_model = config
+        .get("enhancement_model")
+        .and_then(Value::as_str)
+        .is_some_and(|model| {
+            model
+                .trim()
+                .eq_ignore_ascii_case(LEGACY_ENHANCEMENT_OPENROUTER_MODEL)
+        });
+    if provider == stt::PROVIDER_OPENROUTER && is_legacy_model {
+        config.insert(
+            "enhancement_model".to_string(),
+            Value::from(stt::default_enhancement_model_for_provider(&provider)),
+        );
+    }
+
+    let is_legacy_prompt = config
+        .get("enhancement_prompt")
+        .and_then(Value::as_str)
+        .is_some_and(|prompt| prompt.trim() == LEGACY_ENHANCEMENT_PROGRAMMER_PROMPT);
+    if is_legacy_prompt {
+        config.insert(
+            "enhancement_prompt".to_string(),
+            Value::from(stt::default_enhancement_prompt()),
+        );
+    }
+}
+
+/// 解析配置内容并升级到当前版本
+///
+/// 返回 (配置, 是否做过迁移)。
+fn parse_and_migrate(content: &str) -> Result<(SttConfig, bool), String> {
+    let value: Value =
+        serde_json::from_str(content).map_err(|e| format!("配置文件不是有效的 JSON: {}", e))?;
+    migrate_value(value)
+}
+
+/// 把任意版本的配置 JSON 升级并解析为当前版本（也用于导入设置）
+pub fn migrate_value(value: Value) -> Result<(SttConfig, bool), String> {
+    let Value::Object(mut object) = value else {
+        return Err("配置文件格式错误：顶层应为对象".to_string());
+    };
+
+    let version = object.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > CONFIG_VERSION {
+        warn!(
+            "配置版本 {} 高于当前支持的版本 {}，按当前版本读取",
+            version, CONFIG_VERSION
+        );
+    }
+
+    let migrated = version < CONFIG_VERSION;
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(&mut object);
+    }
+    object.insert("version".to_string(), Value::from(CONFIG_VERSION));
+    // 用户未设置的字段使用管理员策略的默认值
+    policy::current().merge_defaults(&mut object);
+
+    let config = serde_json::from_value::<SttConfig>(Value::Object(object))
+        .map_err(|e| format!("配置字段无效: {}", e))?;
+    Ok((config, migrated))
+}
+
+pub fn config_path(app: &impl AppPaths) -> Result<PathBuf, String> {
+    Ok(app.app_config_dir()?.join(CONFIG_FILENAME))
+}
+
+/// 备份无法解析的配置，返回备份路径
+fn backup_invalid_config(path: &Path, original_content: &str) -> Option<PathBuf> {
+    let timestamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+    let backup_path = path.with_file_name(format!("config.bak.{}.json", timestamp));
+
+    if std::fs::rename(path, &backup_path).is_ok()
+        || std::fs::write(&backup_path, original_content).is_ok()
+    {
+        return Some(backup_path);
+    }
+    None
+}
+
+/// 读取配置文件
+pub fn load(app: &impl AppPaths) -> LoadOutcome {
+    let path = match config_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            warn!("{}", e);
+            return LoadOutcome::Missing;
+        }
+    };
+    if !path.exists() {
+        return LoadOutcome::Missing;
+    }
+
+    let content = match std::fs::read(&path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) => {
+            warn!("读取配置文件失败 {:?}: {:?}", path, e);
+            return LoadOutcome::Missing;
+        }
+    };
+
+    match parse_and_migrate(&content) {
+        Ok((config, migrated)) => {
+            info!("已加载配置文件: {:?}", path);
+            LoadOutcome::Loaded {
+                config: Box::new(config),
+                migrated,
+            }
+        }
+        Err(error) => {
+            warn!("配置文件无法解析，已重置为默认配置 {:?}: {}", path, error);
+            let backup_path = backup_invalid_config(&path, &content);
+            LoadOutcome::Reset(ConfigResetPayload {
+                backup_path: backup_path.map(|path| path.to_string_lossy().to_string()),
+                error,
+            })
+        }
+    }
+}
+
+/// 原子写入：先写同目录临时文件并落盘，再 rename 覆盖目标文件
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {:?}", e))?;
+    }
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("无效的文件路径: {:?}", path))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+    let result = (|| {
+        let mut file =
+            std::fs::File::create(&temp_path).map_err(|e| format!("创建临时文件失败: {:?}", e))?;
+        file.write_all(content)
+            .map_err(|e| format!("写入临时文件失败: {:?}", e))?;
+        file.sync_all()
+            .map_err(|e| format!("写入临时文件失败: {:?}", e))?;
+        std::fs::rename(&temp_path, path).map_err(|e| format!("替换文件失败: {:?}", e))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// 保存配置（写入当前版本号）
+pub fn save(app: &impl AppPaths, config: &SttConfig) -> Result<(), String> {
+    let mut config = config.clone();
+    config.version = CONFIG_VERSION;
+    let json =
+        serde_json::to_string_pretty(&config).map_err(|e| format!("序列化配置失败: {:?}", e))?;
+    write_atomic(&config_path(app)?, json.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_unversioned_config() {
+        let content = serde_json::json!({
+            "provider": "openrouter",
+            "base_url": "https://openrouter.ai/api/v1",
+            "api_key": "",
+            "model": "m",
+            "enhancement_model": "google/gemini-2.0-flash-001",
+            "enhancement_prompt": LEGACY_ENHANCEMENT_PROGRAMMER_PROMPT,
+        })
+        .to_string();
+
+        let (config, migrated) = parse_and_migrate(&content).unwrap();
+        assert!(migrated);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(
+            config.enhancement_model,
+            stt::default_enhancement_model_for_provider(stt::PROVIDER_OPENROUTER)
+        );
+        assert_eq!(config.enhancement_prompt, stt::default_enhancement_prompt());
+
+        let current = serde_json::to_string(&config).unwrap();
+        let (reloaded, migrated) = parse_and_migrate(&current).unwrap();
+        assert!(!migrated);
+        assert_eq!(reloaded.model, "m");
+    }
+
+    #[test]
+    fn rejects_broken_config() {
+        assert!(parse_and_migrate("{\"provider\": ").is_err());
+        assert!(parse_and_migrate("[]").is_err());
+        assert!(parse_and_migrate("{\"auto_write\": \"yes\"}").is_err());
+    }
+
+    #[test]
+    fn atomic_write_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("aitotype-config-test-{}", std::process::id()));
+        let path = dir.join("config.json");
+        write_atomic(&path, b"{\"a\":1}").unwrap();
+        write_atomic(&path, b"{\"a\":2}").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"a\":2}");
+        assert!(!dir.join(".config.json.tmp").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
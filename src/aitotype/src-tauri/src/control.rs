@@ -0,0 +1,569 @@
+//! 本地控制接口模块
+//!
+//! 供脚型踏板脚本、Stream Deck、i3/sway 快捷键、编辑器插件等外部程序触发听写。
+//! 只监听 127.0.0.1（以及 Unix 上 app data 目录下的 `control.sock`），
+//! 每个请求都必须带 `Authorization: Bearer <token>`，令牌保存在 app config 目录的 `control_token`。
+//!
+//! 接口（HTTP/1.1，返回 JSON）：
+//! - `POST /v1/start` / `POST /v1/stop` / `POST /v1/toggle`：与全局快捷键相同，发出 `toggle-recording-event`；
+//!   停止时可带 `?wait=true` 等待并返回最终文本，`&paste=false` 表示不粘贴到光标处
+//! - `POST /v1/cancel`：取消录音或转写
+//! - `GET /v1/status`、`GET /v1/last_result`
+
+use crate::paths::AppPaths;
+use crate::stt::SttConfig;
+use crate::{audio, AppState};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::Serialize;
+#[cfg(unix)]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+pub const DEFAULT_PORT: u16 = 47631;
+const TOKEN_FILENAME: &str = "control_token";
+#[cfg(unix)]
+const SOCKET_FILENAME: &str = "control.sock";
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+const READ_TIMEOUT_SECS: u64 = 5;
+/// `wait=true` 时等待转写结果的最长时间
+const WAIT_TIMEOUT_SECS: u64 = 180;
+
+/// 正在运行的服务（端口变化或关闭时停止）
+struct RunningServer {
+    port: u16,
+    cancel: CancellationToken,
+}
+
+static SERVER: Mutex<Option<RunningServer>> = Mutex::new(None);
+
+/// 最近一次听写结果
+#[derive(Debug, Clone, Serialize)]
+pub struct LastResult {
+    pub text: String,
+    pub finished_at: String,
+}
+
+/// 返回给设置界面的接口信息
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlInfo {
+    pub enabled: bool,
+    pub running: bool,
+    pub url: String,
+    pub socket_path: Option<String>,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    token: Option<String>,
+}
+
+impl Request {
+    fn flag(&self, name: &str, default: bool) -> bool {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| matches!(value.as_str(), "" | "1" | "true" | "yes"))
+            .unwrap_or(default)
+    }
+}
+
+struct Response {
+    status: u16,
+    body: serde_json::Value,
+}
+
+impl Response {
+    fn ok(body: serde_json::Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message.into() }),
+        }
+    }
+}
+
+fn token_path(app: &impl AppPaths) -> Result<PathBuf, String> {
+    Ok(app.app_config_dir()?.join(TOKEN_FILENAME))
+}
+
+fn write_token(app: &impl AppPaths) -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+    let path = token_path(app)?;
+    crate::config::write_atomic(&path, token.as_bytes())?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+    Ok(token)
+}
+
+/// 读取访问令牌，不存在时生成
+pub fn load_token(app: &impl AppPaths) -> Result<String, String> {
+    match std::fs::read_to_string(token_path(app)?) {
+        Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_string()),
+        _ => write_token(app),
+    }
+}
+
+/// 重新生成访问令牌（旧令牌立即失效）
+pub fn regenerate_token(app: &impl AppPaths) -> Result<String, String> {
+    write_token(app)
+}
+
+#[cfg(unix)]
+fn socket_path(app: &impl AppPaths) -> Result<PathBuf, String> {
+    Ok(app.app_data_dir()?.join(SOCKET_FILENAME))
+}
+
+pub fn info(app: &tauri::AppHandle, config: &SttConfig) -> Result<ControlInfo, String> {
+    #[cfg(unix)]
+    let socket_path = Some(socket_path(app)?.to_string_lossy().to_string());
+    #[cfg(not(unix))]
+    let socket_path = None;
+
+    let running = SERVER
+        .lock()
+        .map(|server| server.is_some())
+        .unwrap_or(false);
+    Ok(ControlInfo {
+        enabled: config.control_api_enabled,
+        running,
+        url: format!("http://127.0.0.1:{}/v1", config.control_api_port),
+        socket_path,
+        token: load_token(app)?,
+    })
+}
+
+/// 按配置启动、重启或停止服务
+pub fn apply(app: &tauri::AppHandle, config: &SttConfig) {
+    let Ok(mut server) = SERVER.lock() else {
+        return;
+    };
+    let wanted = config
+        .control_api_enabled
+        .then_some(config.control_api_port);
+    if server.as_ref().map(|running| running.port) == wanted {
+        return;
+    }
+    if let Some(running) = server.take() {
+        running.cancel.cancel();
+        info!("本地控制接口已停止");
+    }
+    let Some(port) = wanted else {
+        return;
+    };
+
+    let cancel = CancellationToken::new();
+    *server = Some(RunningServer {
+        port,
+        cancel: cancel.clone(),
+    });
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(app, port, cancel).await {
+            warn!("本地控制接口启动失败: {}", e);
+            if let Ok(mut server) = SERVER.lock() {
+                if server.as_ref().is_some_and(|running| running.port == port) {
+                    *server = None;
+                }
+            }
+        }
+    });
+}
+
+async fn serve(app: tauri::AppHandle, port: u16, cancel: CancellationToken) -> Result<(), String> {
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("监听 127.0.0.1:{} 失败: {:?}", port, e))?;
+    info!("本地控制接口已启动: http://127.0.0.1:{}/v1", port);
+
+    #[cfg(unix)]
+    let unix_socket = match bind_unix_socket(&app) {
+        Ok(socket) => Some(socket),
+        Err(e) => {
+            warn!("{}", e);
+            None
+        }
+    };
+
+    loop {
+        #[cfg(unix)]
+        let accept_unix = async {
+            match &unix_socket {
+                Some(socket) => socket.listener.accept().await.map(|(stream, _)| stream),
+                None => std::future::pending().await,
+            }
+        };
+        #[cfg(not(unix))]
+        let accept_unix = std::future::pending::<std::io::Result<tokio::net::TcpStream>>();
+
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(handle_connection(app.clone(), stream));
+                }
+                Err(e) => warn!("本地控制接口接受连接失败: {:?}", e),
+            },
+            accepted = accept_unix => match accepted {
+                Ok(stream) => {
+                    tauri::async_runtime::spawn(handle_connection(app.clone(), stream));
+                }
+                Err(e) => warn!("本地控制接口接受连接失败: {:?}", e),
+            },
+        }
+    }
+
+    #[cfg(unix)]
+    if let Some(socket) = unix_socket {
+        drop(socket.listener);
+        remove_if_same_file(&socket.path, socket.file_id);
+    }
+    Ok(())
+}
+
+/// 本任务绑定的 Unix socket
+#[cfg(unix)]
+struct BoundSocket {
+    listener: tokio::net::UnixListener,
+    path: PathBuf,
+    /// 绑定时 socket 文件的 (设备号, inode)
+    file_id: Option<(u64, u64)>,
+}
+
+#[cfg(unix)]
+fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::symlink_metadata(path)
+        .ok()
+        .map(|meta| (meta.dev(), meta.ino()))
+}
+
+/// 只在路径仍指向本任务绑定的文件时删除：端口变更后新任务已在同一路径绑定了
+/// 新的 socket，旧任务退出时不能把它删掉
+#[cfg(unix)]
+fn remove_if_same_file(path: &Path, bound: Option<(u64, u64)>) {
+    if bound.is_some() && file_id(path) == bound {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(unix)]
+fn bind_unix_socket(app: &tauri::AppHandle) -> Result<BoundSocket, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = socket_path(app)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {:?}", e))?;
+    }
+    // 上次异常退出残留的 socket 文件
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)
+        .map_err(|e| format!("监听 {:?} 失败: {:?}", path, e))?;
+    let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    Ok(BoundSocket {
+        listener,
+        file_id: file_id(&path),
+        path,
+    })
+}
+
+async fn handle_connection<S>(app: tauri::AppHandle, mut stream: S)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let response = match tokio::time::timeout(
+        Duration::from_secs(READ_TIMEOUT_SECS),
+        read_request_head(&mut stream),
+    )
+    .await
+    {
+        Ok(Ok(head)) => match parse_request(&head) {
+            Ok(request) => authorize_and_route(&app, request).await,
+            Err(e) => Response::error(400, e),
+        },
+        Ok(Err(e)) => Response::error(400, e),
+        Err(_) => Response::error(408, "读取请求超时"),
+    };
+    let _ = stream.write_all(&encode_response(&response)).await;
+    let _ = stream.shutdown().await;
+}
+
+/// 读取请求行与请求头（忽略请求体）
+async fn read_request_head<S: AsyncRead + Unpin>(stream: &mut S) -> Result<String, String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    loop {
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| format!("读取请求失败: {:?}", e))?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+            break;
+        }
+        if buffer.len() > MAX_REQUEST_BYTES {
+            return Err("请求头过大".to_string());
+        }
+    }
+    String::from_utf8(buffer).map_err(|_| "请求不是有效的 UTF-8".to_string())
+}
+
+fn parse_request(head: &str) -> Result<Request, String> {
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err("无效的请求行".to_string());
+    };
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key.to_ascii_lowercase(), value.to_ascii_lowercase())
+        })
+        .collect();
+
+    let token = lines
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .and_then(|(_, value)| {
+            let value = value.trim();
+            let (scheme, token) = value.split_once(' ')?;
+            scheme
+                .eq_ignore_ascii_case("bearer")
+                .then(|| token.trim().to_string())
+        });
+
+    Ok(Request {
+        method: method.to_ascii_uppercase(),
+        path: path.trim_end_matches('/').to_string(),
+        query,
+        token,
+    })
+}
+
+/// 定长比较，避免按耗时猜测令牌
+fn token_matches(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn authorize_and_route(app: &tauri::AppHandle, request: Request) -> Response {
+    let expected = match load_token(app) {
+        Ok(token) => token,
+        Err(e) => return Response::error(500, e),
+    };
+    if !request
+        .token
+        .as_deref()
+        .is_some_and(|token| token_matches(token, &expected))
+    {
+        return Response::error(401, "缺少或无效的访问令牌");
+    }
+    route(app, &request).await
+}
+
+async fn route(app: &tauri::AppHandle, request: &Request) -> Response {
+    let action = request.path.strip_prefix("/v1/").unwrap_or_default();
+    let expected_method = match action {
+        "status" | "last_result" => "GET",
+        "start" | "stop" | "toggle" | "cancel" => "POST",
+        _ => return Response::error(404, format!("未知接口: {}", request.path)),
+    };
+    if request.method != expected_method {
+        return Response::error(405, format!("{} 需要使用 {}", action, expected_method));
+    }
+
+    let state = app.state::<AppState>();
+    match action {
+        "status" => Response::ok(serde_json::json!({
+            "recording": audio::is_recording(),
+            "transcribing": is_transcribing(&state),
+            "last_result": last_result(&state),
+        })),
+        "last_result" => match last_result(&state) {
+            Some(result) => Response::ok(serde_json::json!(result)),
+            None => Response::error(404, "还没有听写结果"),
+        },
+        "cancel" => Response::ok(serde_json::json!({
+            "cancelled": crate::cancel_active_transcription(app),
+        })),
+        "start" => start(app, &state),
+        "stop" => stop(app, &state, request).await,
+        _ => {
+            if audio::is_recording() {
+                stop(app, &state, request).await
+            } else {
+                start(app, &state)
+            }
+        }
+    }
+}
+
+fn is_transcribing(state: &AppState) -> bool {
+    state
+        .transcription_cancel
+        .lock()
+        .map(|token| token.is_some())
+        .unwrap_or(false)
+}
+
+fn last_result(state: &AppState) -> Option<LastResult> {
+    state
+        .last_result
+        .lock()
+        .ok()
+        .and_then(|result| result.clone())
+}
+
+fn start(app: &tauri::AppHandle, state: &AppState) -> Response {
+    if audio::is_recording() || is_transcribing(state) {
+        return Response::error(409, "正在录音或转写中");
+    }
+    crate::emit_recording_action(app, "start", true);
+    Response::ok(serde_json::json!({ "action": "start" }))
+}
+
+async fn stop(app: &tauri::AppHandle, state: &AppState, request: &Request) -> Response {
+    if !audio::is_recording() {
+        return Response::error(409, "当前没有在录音");
+    }
+    let paste = request.flag("paste", true);
+    let wait = request.flag("wait", false);
+    // 先订阅再发事件，避免错过很快结束的转写
+    let mut done = state.transcription_done.subscribe();
+    crate::emit_recording_action(app, "stop", paste);
+    if !wait {
+        return Response::ok(serde_json::json!({ "action": "stop", "paste": paste }));
+    }
+
+    match tokio::time::timeout(Duration::from_secs(WAIT_TIMEOUT_SECS), done.recv()).await {
+        Ok(Ok(Ok(text))) => Response::ok(serde_json::json!({
+            "action": "stop",
+            "paste": paste,
+            "text": text,
+        })),
+        Ok(Ok(Err(e))) => {
+            let (error_type, message) = crate::parse_typed_error(&e);
+            let status = if error_type == "cancelled" { 409 } else { 502 };
+            Response::error(status, message)
+        }
+        Ok(Err(_)) => Response::error(500, "等待转写结果失败"),
+        Err(_) => Response::error(504, "等待转写结果超时"),
+    }
+}
+
+fn encode_response(response: &Response) -> Vec<u8> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        502 => "Bad Gateway",
+        504 => "Gateway Timeout",
+        _ => "Internal Server Error",
+    };
+    let body = response.body.to_string();
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason,
+        body.len()
+    );
+    if response.status == 401 {
+        head.push_str("WWW-Authenticate: Bearer\r\n");
+    }
+    head.push_str("\r\n");
+    let mut bytes = head.into_bytes();
+    bytes.extend_from_slice(body.as_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_request_with_bearer_token() {
+        let request = parse_request(
+            "POST /v1/stop/?wait=true&Paste=False HTTP/1.1\r\nHost: 127.0.0.1\r\nauthorization: bearer abc123\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1/stop");
+        assert_eq!(request.token.as_deref(), Some("abc123"));
+        assert!(request.flag("wait", false));
+        assert!(!request.flag("paste", true));
+        assert!(request.flag("missing", true));
+
+        let anonymous =
+            parse_request("GET /v1/status HTTP/1.1\r\nAuthorization: Basic eDp5\r\n\r\n").unwrap();
+        assert_eq!(anonymous.token, None);
+        assert!(parse_request("\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn compares_tokens() {
+        assert!(token_matches("secret-token", "secret-token"));
+        assert!(!token_matches("secret-tokeN", "secret-token"));
+        assert!(!token_matches("secret", "secret-token"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_socket_bound_by_newer_task() {
+        let dir =
+            std::env::temp_dir().join(format!("aitotype-control-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(SOCKET_FILENAME);
+        let stale = dir.join("stale.sock");
+
+        std::fs::write(&path, "old").unwrap();
+        let old_id = file_id(&path);
+        // 旧文件仍然存在（只是换了路径），保证新文件拿到不同的 inode
+        std::fs::rename(&path, &stale).unwrap();
+        std::fs::write(&path, "new").unwrap();
+
+        remove_if_same_file(&path, old_id);
+        assert!(path.exists());
+        remove_if_same_file(&path, file_id(&path));
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,9 +1,11 @@
 //! 用户易错词记忆与替换模块
 
+use crate::paths::AppPaths;
 use aho_corasick::{AhoCorasickBuilder, MatchKind};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use tracing::warn;
 
 pub const CORRECTIONS_FILENAME: &str = "corrections.json";
 pub const CORRECTIONS_VERSION: u32 = 1;
@@ -135,16 +137,11 @@ fn backup_invalid_store(path: &Path, original_content: &str) {
     let _ = std::fs::write(&backup_path, original_content);
 }
 
-pub fn corrections_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    use tauri::Manager;
-    let dir = app
-        .path()
-        .app_config_dir()
-        .map_err(|e| format!("获取配置目录失败: {:?}", e))?;
-    Ok(dir.join(CORRECTIONS_FILENAME))
+pub fn corrections_path(app: &impl AppPaths) -> Result<PathBuf, String> {
+    Ok(app.app_config_dir()?.join(CORRECTIONS_FILENAME))
 }
 
-pub fn load_corrections(app: &tauri::AppHandle) -> CorrectionStore {
+pub fn load_corrections(app: &impl AppPaths) -> CorrectionStore {
     let path = match corrections_path(app) {
         Ok(path) => path,
         Err(_) => return CorrectionStore::default(),
@@ -162,14 +159,14 @@ pub fn load_corrections(app: &tauri::AppHandle) -> CorrectionStore {
     match serde_json::from_str::<CorrectionStore>(&content) {
         Ok(store) => sanitize_store(store),
         Err(err) => {
-            eprintln!("load_corrections: parse failed {:?}: {:?}", path, err);
+            warn!("load_corrections: parse failed {:?}: {:?}", path, err);
             backup_invalid_store(&path, &content);
             CorrectionStore::default()
         }
     }
 }
 
-pub fn save_corrections(app: &tauri::AppHandle, store: &CorrectionStore) -> Result<(), String> {
+pub fn save_corrections(app: &impl AppPaths, store: &CorrectionStore) -> Result<(), String> {
     let path = corrections_path(app)?;
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {:?}", e))?;
@@ -229,6 +226,44 @@ pub fn add_correction(store: &mut CorrectionStore, wrong: &str, correct: &str) -
     Ok(())
 }
 
+/// 合并易错词的结果统计
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct CorrectionMergeSummary {
+    /// 新增的错误变体
+    pub added: usize,
+    /// 原先绑定到其他正确词、合并后改绑的变体
+    pub moved: usize,
+    /// 已存在且绑定相同的变体
+    pub unchanged: usize,
+}
+
+/// 把另一份易错词库合并进来（同一变体以导入的绑定为准）
+pub fn merge_store(
+    store: &mut CorrectionStore,
+    incoming: &CorrectionStore,
+) -> Result<CorrectionMergeSummary, String> {
+    let mut summary = CorrectionMergeSummary::default();
+    for entry in &incoming.corrections {
+        for variant in &entry.variants {
+            let normalized = normalize_variant_input(variant)?;
+            let existing = store
+                .corrections
+                .iter()
+                .find(|existing| existing.variants.contains(&normalized));
+            match existing {
+                Some(existing) if existing.correct.eq_ignore_ascii_case(entry.correct.trim()) => {
+                    summary.unchanged += 1;
+                    continue;
+                }
+                Some(_) => summary.moved += 1,
+                None => summary.added += 1,
+            }
+            add_correction(store, &normalized, &entry.correct)?;
+        }
+    }
+    Ok(summary)
+}
+
 pub fn remove_correction(store: &mut CorrectionStore, correct: &str) -> bool {
     let correct_trimmed = correct.trim();
     if correct_trimmed.is_empty() {
@@ -398,7 +433,7 @@ pub fn apply_corrections(text: &str, store: &CorrectionStore) -> ApplyCorrection
     {
         Ok(ac) => ac,
         Err(err) => {
-            eprintln!("apply_corrections: build matcher failed: {:?}", err);
+            warn!("apply_corrections: build matcher failed: {:?}", err);
             return ApplyCorrectionsResult {
                 text: text.to_string(),
                 hits: Vec::new(),
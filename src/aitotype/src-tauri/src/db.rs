@@ -0,0 +1,806 @@
+//! SQLite 存储模块
+//!
+//! 转写记录、易错词命中与润色尝试保存在 app data 目录下的 `history.db`，
+//! 由 FTS5（trigram 分词，支持中英文子串）提供全文搜索。
+//! 旧版按天日志文件在启动时导入，导入成功后安全删除，避免已删除的记录仍留在磁盘上；
+//! 数据库不可用时日志回退写入 JSONL 文件。
+
+use crate::corrections::CorrectionHit;
+use crate::history::{HistoryPage, HistoryQuery};
+use crate::logging::{self, TranscribeLogEntry};
+use crate::paths::AppPaths;
+use crate::timing::StageTimings;
+use chrono::NaiveDate;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::sync::Mutex;
+
+pub const DB_FILENAME: &str = "history.db";
+/// trigram 分词要求关键词至少 3 个字符，更短时回退到 LIKE
+const FTS_MIN_QUERY_CHARS: usize = 3;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transcriptions (
+    id TEXT PRIMARY KEY,
+    timestamp TEXT NOT NULL,
+    day TEXT NOT NULL,
+    stt_provider TEXT NOT NULL,
+    stt_model TEXT NOT NULL,
+    stt_text TEXT NOT NULL,
+    pre_correction_text TEXT,
+    post_correction_text TEXT,
+    enhancement_enabled INTEGER NOT NULL DEFAULT 0,
+    final_text TEXT NOT NULL,
+    audio_file TEXT,
+    status TEXT,
+    pinned INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS idx_transcriptions_day ON transcriptions(day);
+CREATE INDEX IF NOT EXISTS idx_transcriptions_timestamp ON transcriptions(timestamp);
+
+CREATE TABLE IF NOT EXISTS correction_hits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    transcription_id TEXT NOT NULL REFERENCES transcriptions(id) ON DELETE CASCADE,
+    variant TEXT NOT NULL,
+    correct TEXT NOT NULL,
+    start_pos INTEGER NOT NULL,
+    end_pos INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_correction_hits_transcription ON correction_hits(transcription_id);
+
+CREATE TABLE IF NOT EXISTS enhancement_attempts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    transcription_id TEXT NOT NULL REFERENCES transcriptions(id) ON DELETE CASCADE,
+    provider TEXT,
+    model TEXT,
+    status TEXT,
+    error TEXT,
+    duration_ms INTEGER,
+    output_text TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_enhancement_attempts_transcription ON enhancement_attempts(transcription_id);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS transcriptions_fts USING fts5(
+    final_text, stt_text,
+    content='transcriptions', content_rowid='rowid',
+    tokenize='trigram'
+);
+CREATE TRIGGER IF NOT EXISTS transcriptions_fts_ai AFTER INSERT ON transcriptions BEGIN
+    INSERT INTO transcriptions_fts(rowid, final_text, stt_text)
+    VALUES (new.rowid, new.final_text, new.stt_text);
+END;
+CREATE TRIGGER IF NOT EXISTS transcriptions_fts_ad AFTER DELETE ON transcriptions BEGIN
+    INSERT INTO transcriptions_fts(transcriptions_fts, rowid, final_text, stt_text)
+    VALUES ('delete', old.rowid, old.final_text, old.stt_text);
+END;
+CREATE TRIGGER IF NOT EXISTS transcriptions_fts_au AFTER UPDATE OF final_text, stt_text ON transcriptions BEGIN
+    INSERT INTO transcriptions_fts(transcriptions_fts, rowid, final_text, stt_text)
+    VALUES ('delete', old.rowid, old.final_text, old.stt_text);
+    INSERT INTO transcriptions_fts(rowid, final_text, stt_text)
+    VALUES (new.rowid, new.final_text, new.stt_text);
+END;
+
+CREATE TABLE IF NOT EXISTS imported_files (
+    file_name TEXT PRIMARY KEY,
+    size INTEGER NOT NULL,
+    imported_at TEXT NOT NULL
+);
+";
+
+/// 增量迁移，按顺序执行，已执行的版本记录在 `PRAGMA user_version`
+const MIGRATIONS: &[&str] = &[
+    "ALTER TABLE transcriptions ADD COLUMN audio_duration_ms INTEGER;",
+    "ALTER TABLE transcriptions ADD COLUMN timings TEXT;",
+    "ALTER TABLE transcriptions ADD COLUMN profile TEXT;",
+];
+
+const TRANSCRIPTION_COLUMNS: &str = "id, timestamp, stt_provider, stt_model, stt_text, \
+     pre_correction_text, post_correction_text, enhancement_enabled, final_text, \
+     audio_file, status, pinned, audio_duration_ms, timings, profile";
+
+/// 数据库连接（作为 Tauri 托管状态）
+pub struct Store {
+    conn: Mutex<Connection>,
+}
+
+fn db_err(e: rusqlite::Error) -> String {
+    format!("数据库操作失败: {:?}", e)
+}
+
+impl Store {
+    /// 打开（或创建）app data 目录下的数据库
+    pub fn open(app: &impl AppPaths) -> Result<Self, String> {
+        let dir = app.app_data_dir()?;
+        std::fs::create_dir_all(&dir).map_err(|e| format!("创建数据目录失败: {:?}", e))?;
+        Self::open_path(&dir.join(DB_FILENAME))
+    }
+
+    fn open_path(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| format!("打开数据库失败: {:?}", e))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    fn open_in_memory() -> Result<Self, String> {
+        Self::init(Connection::open_in_memory().map_err(db_err)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        // secure_delete: 删除的记录内容会被覆盖，而不是仅标记为空闲页
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON; PRAGMA secure_delete = ON;",
+        )
+        .map_err(db_err)?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("初始化数据库失败: {:?}", e))?;
+        run_migrations(&conn).map_err(|e| format!("升级数据库失败: {:?}", e))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("获取数据库连接失败: {:?}", e))?;
+        f(&mut conn).map_err(db_err)
+    }
+
+    /// 写入一条转写记录（ID 已存在时忽略），返回是否新增
+    pub fn insert_entry(&self, entry: &TranscribeLogEntry) -> Result<bool, String> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let inserted = insert_entry_tx(&tx, entry)?;
+            tx.commit()?;
+            Ok(inserted)
+        })
+    }
+
+    /// 按日志 ID 查找
+    pub fn find_entry(&self, log_id: &str) -> Result<Option<TranscribeLogEntry>, String> {
+        self.with_conn(|conn| {
+            let entry = conn
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM transcriptions WHERE id = ?1",
+                        TRANSCRIPTION_COLUMNS
+                    ),
+                    params![log_id],
+                    entry_from_row,
+                )
+                .optional()?;
+            match entry {
+                Some(mut entry) => {
+                    load_details(conn, &mut entry)?;
+                    Ok(Some(entry))
+                }
+                None => Ok(None),
+            }
+        })
+    }
+
+    /// 搜索历史：置顶优先，其余按时间倒序
+    pub fn search(&self, query: &HistoryQuery, limit: usize) -> Result<HistoryPage, String> {
+        let (where_sql, mut values) = build_filter(query);
+        self.with_conn(|conn| {
+            let total: i64 = conn.query_row(
+                &format!("SELECT COUNT(*) FROM transcriptions WHERE {}", where_sql),
+                rusqlite::params_from_iter(values.iter()),
+                |row| row.get(0),
+            )?;
+
+            values.push(Value::Integer(limit as i64));
+            values.push(Value::Integer(query.offset as i64));
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM transcriptions WHERE {} \
+                 ORDER BY pinned DESC, timestamp DESC LIMIT ? OFFSET ?",
+                TRANSCRIPTION_COLUMNS, where_sql
+            ))?;
+            let mut items = stmt
+                .query_map(rusqlite::params_from_iter(values.iter()), entry_from_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for entry in items.iter_mut() {
+                load_details(conn, entry)?;
+            }
+
+            let total = total.max(0) as usize;
+            Ok(HistoryPage {
+                has_more: query.offset + items.len() < total,
+                items,
+                total,
+                offset: query.offset,
+            })
+        })
+    }
+
+    /// 按时间升序列出符合条件的全部记录（用于导出）
+    pub fn list_entries(&self, query: &HistoryQuery) -> Result<Vec<TranscribeLogEntry>, String> {
+        let (where_sql, values) = build_filter(query);
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM transcriptions WHERE {} ORDER BY timestamp ASC",
+                TRANSCRIPTION_COLUMNS, where_sql
+            ))?;
+            let mut items = stmt
+                .query_map(rusqlite::params_from_iter(values.iter()), entry_from_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for entry in items.iter_mut() {
+                load_details(conn, entry)?;
+            }
+            Ok(items)
+        })
+    }
+
+    /// 置顶或取消置顶，返回是否找到该记录
+    pub fn set_pinned(&self, log_id: &str, pinned: bool) -> Result<bool, String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "UPDATE transcriptions SET pinned = ?1 WHERE id = ?2",
+                params![pinned, log_id],
+            )
+            .map(|changed| changed > 0)
+        })
+    }
+
+    /// 更新各阶段耗时，返回是否找到该记录
+    pub fn set_timings(&self, log_id: &str, timings: &StageTimings) -> Result<bool, String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "UPDATE transcriptions SET timings = ?1 WHERE id = ?2",
+                params![timings_json(timings), log_id],
+            )
+            .map(|changed| changed > 0)
+        })
+    }
+
+    /// 删除一条记录（命中与润色记录级联删除），返回被删除的记录
+    pub fn delete_entry(&self, log_id: &str) -> Result<Option<TranscribeLogEntry>, String> {
+        let entry = self.find_entry(log_id)?;
+        if entry.is_some() {
+            self.with_conn(|conn| {
+                conn.execute("DELETE FROM transcriptions WHERE id = ?1", params![log_id])
+            })?;
+        }
+        Ok(entry)
+    }
+
+    /// 按保留策略删除旧记录（置顶记录保留），返回删除条数
+    ///
+    /// 大小按各文本字段的字节数估算；`max_age_days` / `max_total_mb` 为 0 表示不限制。
+    pub fn prune(
+        &self,
+        today: NaiveDate,
+        max_age_days: u32,
+        max_total_mb: u32,
+    ) -> Result<usize, String> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let mut removed = 0;
+
+            if max_age_days > 0 {
+                let cutoff = (today - chrono::Duration::days(i64::from(max_age_days)))
+                    .format("%Y-%m-%d")
+                    .to_string();
+                removed += tx.execute(
+                    "DELETE FROM transcriptions WHERE pinned = 0 AND day < ?1",
+                    params![cutoff],
+                )?;
+            }
+
+            if max_total_mb > 0 {
+                let rows = {
+                    let mut stmt = tx.prepare(
+                        "SELECT id, pinned, \
+                         length(CAST(stt_text AS BLOB)) + length(CAST(final_text AS BLOB)) \
+                         + ifnull(length(CAST(pre_correction_text AS BLOB)), 0) \
+                         + ifnull(length(CAST(post_correction_text AS BLOB)), 0) \
+                         FROM transcriptions ORDER BY timestamp ASC",
+                    )?;
+                    let rows = stmt
+                        .query_map([], |row| {
+                            Ok((
+                                row.get::<_, String>(0)?,
+                                row.get::<_, bool>(1)?,
+                                row.get::<_, i64>(2)?.max(0) as u64,
+                            ))
+                        })?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    rows
+                };
+
+                let max_bytes = u64::from(max_total_mb) * 1024 * 1024;
+                let mut total: u64 = rows.iter().map(|(_, _, size)| size).sum();
+                for (id, pinned, size) in rows {
+                    if total <= max_bytes {
+                        break;
+                    }
+                    if pinned {
+                        continue;
+                    }
+                    removed +=
+                        tx.execute("DELETE FROM transcriptions WHERE id = ?1", params![id])?;
+                    total = total.saturating_sub(size);
+                }
+            }
+
+            tx.commit()?;
+            if removed > 0 {
+                conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")?;
+            }
+            Ok(removed)
+        })
+    }
+
+    /// 清空全部历史记录并压缩数据库文件，返回删除条数
+    pub fn wipe(&self) -> Result<usize, String> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let removed = tx.execute("DELETE FROM transcriptions", [])?;
+            tx.execute("DELETE FROM imported_files", [])?;
+            tx.execute(
+                "INSERT INTO transcriptions_fts(transcriptions_fts) VALUES ('rebuild')",
+                [],
+            )?;
+            tx.commit()?;
+            conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")?;
+            Ok(removed)
+        })
+    }
+
+    /// 日志文件被压缩改名后，同步更新导入记录，避免重复导入已删除的记录
+    pub fn rename_imported_file(
+        &self,
+        old_name: &str,
+        new_name: &str,
+        size: u64,
+    ) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "UPDATE imported_files SET file_name = ?1, size = ?2 WHERE file_name = ?3",
+                params![new_name, size as i64, old_name],
+            )
+            .map(|_| ())
+        })
+    }
+
+    /// 导入旧版日志文件（JSON 数组或 JSONL），按文件名与大小跳过已导入的文件
+    pub fn import_log_files(&self, app: &tauri::AppHandle) -> Result<usize, String> {
+        self.import_files(logging::list_log_files(app)?)
+    }
+
+    /// 导入日志文件，成功写入数据库后安全删除源文件
+    ///
+    /// 无法解析出任何记录的非空文件（如损坏的旧格式）保持原样，不做删除。
+    fn import_files(&self, files: Vec<logging::LogFile>) -> Result<usize, String> {
+        let mut imported = 0;
+        for file in files {
+            let file_name = match file.path.file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => continue,
+            };
+            let entries = logging::read_entries(&file.path);
+            imported += self.import_entries(&file_name, file.size, &entries)?;
+            if !entries.is_empty() || file.size == 0 {
+                logging::remove_imported_file(&file.path, file.size);
+            }
+        }
+        Ok(imported)
+    }
+
+    fn import_entries(
+        &self,
+        file_name: &str,
+        size: u64,
+        entries: &[TranscribeLogEntry],
+    ) -> Result<usize, String> {
+        self.with_conn(|conn| {
+            let known_size: Option<i64> = conn
+                .query_row(
+                    "SELECT size FROM imported_files WHERE file_name = ?1",
+                    params![file_name],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if known_size == Some(size as i64) {
+                return Ok(0);
+            }
+
+            let tx = conn.transaction()?;
+            let mut inserted = 0;
+            for entry in entries {
+                if insert_entry_tx(&tx, entry)? {
+                    inserted += 1;
+                }
+            }
+            tx.execute(
+                "INSERT OR REPLACE INTO imported_files (file_name, size, imported_at) \
+                 VALUES (?1, ?2, ?3)",
+                params![file_name, size as i64, chrono::Local::now().to_rfc3339()],
+            )?;
+            tx.commit()?;
+            Ok(inserted)
+        })
+    }
+}
+
+fn run_migrations(conn: &Connection) -> rusqlite::Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        conn.execute_batch(&format!(
+            "BEGIN; {} PRAGMA user_version = {}; COMMIT;",
+            sql,
+            index + 1
+        ))?;
+    }
+    Ok(())
+}
+
+fn insert_entry_tx(
+    tx: &rusqlite::Transaction,
+    entry: &TranscribeLogEntry,
+) -> rusqlite::Result<bool> {
+    let id = entry.log_id().to_string();
+    let day: String = entry.timestamp.chars().take(10).collect();
+    let changed = tx.execute(
+        "INSERT OR IGNORE INTO transcriptions (id, timestamp, day, stt_provider, stt_model, \
+         stt_text, pre_correction_text, post_correction_text, enhancement_enabled, final_text, \
+         audio_file, status, pinned, audio_duration_ms, timings, profile) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![
+            id,
+            entry.timestamp,
+            day,
+            entry.stt_provider,
+            entry.stt_model,
+            entry.stt_text,
+            entry.pre_correction_text,
+            entry.post_correction_text,
+            entry.enhancement_enabled,
+            entry.final_text,
+            entry.audio_file,
+            entry.status,
+            entry.pinned,
+            entry.audio_duration_ms.map(|ms| ms as i64),
+            entry.timings.as_ref().and_then(timings_json),
+            entry.profile,
+        ],
+    )?;
+    if changed == 0 {
+        return Ok(false);
+    }
+
+    for hit in entry.correction_hits.iter().flatten() {
+        tx.execute(
+            "INSERT INTO correction_hits (transcription_id, variant, correct, start_pos, end_pos) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                hit.variant,
+                hit.correct,
+                hit.start as i64,
+                hit.end as i64
+            ],
+        )?;
+    }
+
+    if entry.enhancement_provider.is_some() || entry.enhancement_status.is_some() {
+        tx.execute(
+            "INSERT INTO enhancement_attempts (transcription_id, provider, model, status, error, \
+             duration_ms, output_text) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                id,
+                entry.enhancement_provider,
+                entry.enhancement_model,
+                entry.enhancement_status,
+                entry.enhancement_error,
+                entry.enhancement_duration_ms.map(|ms| ms as i64),
+                entry.enhancement_text,
+            ],
+        )?;
+    }
+    Ok(true)
+}
+
+fn timings_json(timings: &StageTimings) -> Option<String> {
+    serde_json::to_string(timings).ok()
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<TranscribeLogEntry> {
+    Ok(TranscribeLogEntry {
+        id: row.get(0)?,
+        timestamp: row.get(1)?,
+        stt_provider: row.get(2)?,
+        stt_model: row.get(3)?,
+        stt_text: row.get(4)?,
+        pre_correction_text: row.get(5)?,
+        post_correction_text: row.get(6)?,
+        enhancement_enabled: row.get(7)?,
+        final_text: row.get(8)?,
+        audio_file: row.get(9)?,
+        status: row.get(10)?,
+        pinned: row.get(11)?,
+        audio_duration_ms: row.get::<_, Option<i64>>(12)?.map(|ms| ms.max(0) as u64),
+        timings: row
+            .get::<_, Option<String>>(13)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        profile: row.get(14)?,
+        ..Default::default()
+    })
+}
+
+/// 补全易错词命中与最近一次润色尝试
+fn load_details(conn: &Connection, entry: &mut TranscribeLogEntry) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT variant, correct, start_pos, end_pos FROM correction_hits \
+         WHERE transcription_id = ?1 ORDER BY id",
+    )?;
+    let hits = stmt
+        .query_map(params![entry.id], |row| {
+            Ok(CorrectionHit {
+                variant: row.get(0)?,
+                correct: row.get(1)?,
+                start: row.get::<_, i64>(2)? as usize,
+                end: row.get::<_, i64>(3)? as usize,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    entry.correction_hits = if hits.is_empty() { None } else { Some(hits) };
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT provider, model, status, error, duration_ms, output_text \
+         FROM enhancement_attempts WHERE transcription_id = ?1 ORDER BY id DESC LIMIT 1",
+    )?;
+    let attempt = stmt
+        .query_row(params![entry.id], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })
+        .optional()?;
+    if let Some((provider, model, status, error, duration_ms, output_text)) = attempt {
+        entry.enhancement_provider = provider;
+        entry.enhancement_model = model;
+        entry.enhancement_status = status;
+        entry.enhancement_error = error;
+        entry.enhancement_duration_ms = duration_ms.map(|ms| ms.max(0) as u64);
+        entry.enhancement_text = output_text;
+    }
+    Ok(())
+}
+
+fn escape_like(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// 根据查询条件生成 WHERE 子句与参数
+fn build_filter(query: &HistoryQuery) -> (String, Vec<Value>) {
+    let mut clauses = vec!["final_text <> ''".to_string()];
+    let mut values = Vec::new();
+
+    let needle = query.query.as_deref().unwrap_or("").trim();
+    if needle.chars().count() >= FTS_MIN_QUERY_CHARS {
+        clauses.push(
+            "rowid IN (SELECT rowid FROM transcriptions_fts WHERE transcriptions_fts MATCH ?)"
+                .to_string(),
+        );
+        values.push(Value::Text(format!("\"{}\"", needle.replace('"', "\"\""))));
+    } else if !needle.is_empty() {
+        clauses.push("(final_text LIKE ? ESCAPE '\\' OR stt_text LIKE ? ESCAPE '\\')".to_string());
+        let pattern = format!("%{}%", escape_like(needle));
+        values.push(Value::Text(pattern.clone()));
+        values.push(Value::Text(pattern));
+    }
+
+    if let Some(start) = query.start_date.as_deref().map(str::trim) {
+        if !start.is_empty() {
+            clauses.push("day >= ?".to_string());
+            values.push(Value::Text(start.to_string()));
+        }
+    }
+    if let Some(end) = query.end_date.as_deref().map(str::trim) {
+        if !end.is_empty() {
+            clauses.push("day <= ?".to_string());
+            values.push(Value::Text(end.to_string()));
+        }
+    }
+    if query.pinned_only {
+        clauses.push("pinned = 1".to_string());
+    }
+
+    (clauses.join(" AND "), values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, timestamp: &str, final_text: &str) -> TranscribeLogEntry {
+        TranscribeLogEntry {
+            id: id.to_string(),
+            timestamp: timestamp.to_string(),
+            stt_provider: "siliconflow".to_string(),
+            stt_model: "model".to_string(),
+            stt_text: format!("raw {}", final_text),
+            final_text: final_text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn search(store: &Store, text: &str) -> Vec<String> {
+        let query = HistoryQuery {
+            query: Some(text.to_string()),
+            ..Default::default()
+        };
+        store
+            .search(&query, 20)
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|item| item.id)
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_hits_and_enhancement_attempts() {
+        let store = Store::open_in_memory().unwrap();
+        let mut item = entry("1", "2026-03-01T10:00:00+08:00", "部署到 Kubernetes");
+        item.correction_hits = Some(vec![CorrectionHit {
+            variant: "库伯".to_string(),
+            correct: "Kubernetes".to_string(),
+            start: 4,
+            end: 14,
+        }]);
+        item.enhancement_enabled = true;
+        item.enhancement_provider = Some("openrouter".to_string());
+        item.enhancement_status = Some("success".to_string());
+        item.enhancement_duration_ms = Some(820);
+        item.timings = Some(StageTimings {
+            stop_ms: Some(60),
+            ..Default::default()
+        });
+        item.profile = Some("work".to_string());
+
+        assert!(store.insert_entry(&item).unwrap());
+        assert!(!store.insert_entry(&item).unwrap());
+
+        let loaded = store.find_entry("1").unwrap().unwrap();
+        assert_eq!(loaded.correction_hits.unwrap()[0].variant, "库伯");
+        assert_eq!(loaded.enhancement_status.as_deref(), Some("success"));
+        assert_eq!(loaded.enhancement_duration_ms, Some(820));
+        assert_eq!(loaded.timings.unwrap().stop_ms, Some(60));
+        assert_eq!(loaded.profile.as_deref(), Some("work"));
+
+        let timings = StageTimings {
+            output_ms: Some(35),
+            ..Default::default()
+        };
+        assert!(store.set_timings("1", &timings).unwrap());
+        assert_eq!(
+            store.find_entry("1").unwrap().unwrap().timings,
+            Some(timings)
+        );
+    }
+
+    #[test]
+    fn searches_chinese_and_short_queries() {
+        let store = Store::open_in_memory().unwrap();
+        store
+            .insert_entry(&entry("1", "2026-03-01T10:00:00+08:00", "今天讨论项目排期"))
+            .unwrap();
+        store
+            .insert_entry(&entry("2", "2026-03-02T10:00:00+08:00", "Deploy the API"))
+            .unwrap();
+
+        assert_eq!(search(&store, "项目排期"), vec!["1"]);
+        assert_eq!(search(&store, "项目"), vec!["1"]);
+        assert_eq!(search(&store, "deploy"), vec!["2"]);
+        assert_eq!(search(&store, "50%"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn filters_by_date_and_orders_pinned_first() {
+        let store = Store::open_in_memory().unwrap();
+        for (id, day) in [("1", "01"), ("2", "02"), ("3", "03")] {
+            let timestamp = format!("2026-03-{}T10:00:00+08:00", day);
+            store.insert_entry(&entry(id, &timestamp, "text")).unwrap();
+        }
+        assert!(store.set_pinned("1", true).unwrap());
+
+        let page = store.search(&HistoryQuery::default(), 20).unwrap();
+        let ids: Vec<&str> = page.items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "3", "2"]);
+
+        let query = HistoryQuery {
+            start_date: Some("2026-03-02".to_string()),
+            end_date: Some("2026-03-02".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(store.search(&query, 20).unwrap().total, 1);
+    }
+
+    #[test]
+    fn delete_removes_entry_from_search() {
+        let store = Store::open_in_memory().unwrap();
+        store
+            .insert_entry(&entry("1", "2026-03-01T10:00:00+08:00", "删除测试内容"))
+            .unwrap();
+        assert!(store.delete_entry("1").unwrap().is_some());
+        assert!(search(&store, "删除测试").is_empty());
+        assert!(store.delete_entry("1").unwrap().is_none());
+    }
+
+    #[test]
+    fn prune_respects_age_size_and_pins() {
+        let store = Store::open_in_memory().unwrap();
+        for (id, day) in [("1", "01"), ("2", "05"), ("3", "09")] {
+            let timestamp = format!("2026-03-{}T10:00:00+08:00", day);
+            store.insert_entry(&entry(id, &timestamp, "text")).unwrap();
+        }
+        store.set_pinned("1", true).unwrap();
+
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        assert_eq!(store.prune(today, 3, 0).unwrap(), 1);
+        assert!(store.find_entry("1").unwrap().is_some());
+        assert!(store.find_entry("2").unwrap().is_none());
+        assert!(store.find_entry("3").unwrap().is_some());
+
+        assert_eq!(store.wipe().unwrap(), 2);
+        assert_eq!(store.search(&HistoryQuery::default(), 20).unwrap().total, 0);
+    }
+
+    #[test]
+    fn deleted_entry_is_gone_from_db_and_log_files() {
+        let dir = std::env::temp_dir().join(format!("aitotype-db-import-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("transcribe_2026-03-01.jsonl");
+        let lines: Vec<String> = [
+            entry("1", "2026-03-01T10:00:00+08:00", "要删除的内容"),
+            entry("2", "2026-03-01T11:00:00+08:00", "保留的内容"),
+        ]
+        .iter()
+        .map(|item| serde_json::to_string(item).unwrap() + "\n")
+        .collect();
+        std::fs::write(&path, lines.concat()).unwrap();
+        let file = logging::LogFile {
+            date: "2026-03-01".to_string(),
+            size: std::fs::metadata(&path).unwrap().len(),
+            path: path.clone(),
+        };
+
+        let store = Store::open_in_memory().unwrap();
+        assert_eq!(store.import_files(vec![file]).unwrap(), 2);
+        assert!(!path.exists());
+
+        assert!(store.delete_entry("1").unwrap().is_some());
+        assert!(search(&store, "要删除").is_empty());
+        assert_eq!(search(&store, "保留的"), vec!["2"]);
+        let leftovers: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn import_skips_unchanged_files() {
+        let store = Store::open_in_memory().unwrap();
+        let entries = vec![entry("", "2026-03-01T10:00:00+08:00", "legacy")];
+        assert_eq!(store.import_entries("a.json", 10, &entries).unwrap(), 1);
+        assert_eq!(store.import_entries("a.json", 10, &entries).unwrap(), 0);
+        assert!(store
+            .find_entry("2026-03-01T10:00:00+08:00")
+            .unwrap()
+            .is_some());
+    }
+}
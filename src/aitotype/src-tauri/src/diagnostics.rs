@@ -0,0 +1,231 @@
+//! 诊断日志模块
+//!
+//! 基于 `tracing` 输出结构化日志：按天滚动写入 app log 目录下的 `diagnostics/`，
+//! 同时输出到 stderr。日志级别可在设置中调整，写入前会再次抹去疑似 API Key。
+//! 清空日志目录时暂停写入文件，清空后重新创建当天的日志文件。
+
+use crate::paths::AppPaths;
+use crate::redaction;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+pub const DIAGNOSTICS_DIR: &str = "diagnostics";
+const LOG_FILE_PREFIX: &str = "aitotype";
+const LOG_FILE_SUFFIX: &str = "log";
+/// 保留的滚动日志文件数（按天）
+const MAX_LOG_FILES: usize = 7;
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+pub const DEFAULT_LOG_LINES: usize = 500;
+pub const MAX_LOG_LINES: usize = 5000;
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+/// 后台写线程的 guard，进程结束前需保持存活
+static WRITER_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+static SESSION_SEQ: AtomicU64 = AtomicU64::new(0);
+/// 后台写线程实际写入的滚动日志文件（未启用文件日志时为 None）
+static FILE_APPENDER: Mutex<Option<RollingFileAppender>> = Mutex::new(None);
+
+/// 规范化日志级别，无效值回退为 info
+pub fn normalize_level(level: &str) -> String {
+    let level = level.trim().to_lowercase();
+    match level.as_str() {
+        "error" | "warn" | "info" | "debug" | "trace" => level,
+        _ => DEFAULT_LOG_LEVEL.to_string(),
+    }
+}
+
+/// 诊断日志目录
+pub fn diagnostics_dir(app: &impl AppPaths) -> Result<PathBuf, String> {
+    Ok(app.app_log_dir()?.join(DIAGNOSTICS_DIR))
+}
+
+/// 初始化全局 tracing 订阅者（重复调用无效）
+pub fn init(app: &impl AppPaths, level: &str) {
+    if FILTER_HANDLE.get().is_some() {
+        return;
+    }
+
+    let (filter, handle) = reload::Layer::new(EnvFilter::new(filter_directive(level)));
+    let stderr_layer = tracing_subscriber::fmt::layer().with_writer(std::io::stderr);
+
+    let file_layer = match diagnostics_dir(app).and_then(|dir| build_appender(&dir)) {
+        Ok(appender) => {
+            if let Ok(mut slot) = FILE_APPENDER.lock() {
+                *slot = Some(appender);
+            }
+            let (writer, guard) = tracing_appender::non_blocking(SharedAppender);
+            let _ = WRITER_GUARD.set(guard);
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(ScrubbingMakeWriter(writer)),
+            )
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    };
+
+    let result = tracing_subscriber::registry()
+        .with(filter)
+        .with(stderr_layer)
+        .with(file_layer)
+        .try_init();
+    match result {
+        Ok(()) => {
+            let _ = FILTER_HANDLE.set(handle);
+        }
+        Err(e) => eprintln!("初始化诊断日志失败: {}", e),
+    }
+}
+
+fn build_appender(dir: &Path) -> Result<RollingFileAppender, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("创建诊断日志目录失败: {:?}", e))?;
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| format!("创建诊断日志文件失败: {:?}", e))
+}
+
+/// 暂停写入诊断日志文件并执行 `f`（如清空日志目录），结束后重新创建日志文件
+///
+/// 暂停期间后台写线程阻塞在锁上，日志暂存在内存队列中，恢复后写入新文件。
+pub fn with_file_log_paused<T>(app: &impl AppPaths, f: impl FnOnce() -> T) -> T {
+    let mut slot = FILE_APPENDER.lock().unwrap_or_else(|e| e.into_inner());
+    // 先关闭文件句柄，避免清空时还有内容写进被删除的文件
+    let was_enabled = slot.take().is_some();
+    let result = f();
+    if was_enabled {
+        match diagnostics_dir(app).and_then(|dir| build_appender(&dir)) {
+            Ok(appender) => *slot = Some(appender),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    result
+}
+
+/// 调整日志级别（立即生效）
+pub fn set_level(level: &str) {
+    if let Some(handle) = FILTER_HANDLE.get() {
+        if let Err(e) = handle.reload(EnvFilter::new(filter_directive(level))) {
+            tracing::warn!("调整诊断日志级别失败: {}", e);
+        }
+    }
+}
+
+/// 只调整本应用的级别，依赖库保持 warn，避免 HTTP/窗口事件刷屏
+fn filter_directive(level: &str) -> String {
+    format!("warn,aitotype={0},aitotype_lib={0}", normalize_level(level))
+}
+
+/// 为一次录音会话创建 span，会话内的日志都带上会话编号
+pub fn session_span() -> tracing::Span {
+    let id = SESSION_SEQ.fetch_add(1, Ordering::Relaxed) + 1;
+    tracing::info_span!("recording_session", session = id)
+}
+
+/// 读取最近的诊断日志（按时间顺序），用于附在问题反馈中
+pub fn recent_lines(app: &impl AppPaths, max_lines: usize) -> Result<Vec<String>, String> {
+    let dir = diagnostics_dir(app)?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    // 文件名形如 aitotype.2026-03-01.log，按名称倒序即按日期倒序
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX))
+        })
+        .collect();
+    files.sort();
+    files.reverse();
+
+    let mut lines: Vec<String> = Vec::new();
+    for path in files {
+        if lines.len() >= max_lines {
+            break;
+        }
+        let content = match std::fs::read(&path) {
+            Ok(content) => String::from_utf8_lossy(&content).into_owned(),
+            Err(e) => {
+                tracing::warn!("读取诊断日志失败 {:?}: {:?}", path, e);
+                continue;
+            }
+        };
+        let remaining = max_lines - lines.len();
+        let mut newest: Vec<String> = content
+            .lines()
+            .rev()
+            .take(remaining)
+            .map(str::to_string)
+            .collect();
+        lines.append(&mut newest);
+    }
+    lines.reverse();
+    Ok(lines)
+}
+
+/// 转发到当前滚动日志文件的 writer，文件日志暂停时丢弃写入
+struct SharedAppender;
+
+impl Write for SharedAppender {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match FILE_APPENDER.lock() {
+            Ok(mut slot) => match slot.as_mut() {
+                Some(appender) => appender.write(buf),
+                None => Ok(buf.len()),
+            },
+            Err(_) => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match FILE_APPENDER.lock() {
+            Ok(mut slot) => slot.as_mut().map_or(Ok(()), |appender| appender.flush()),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// 写入前抹去疑似密钥的 writer
+struct ScrubbingMakeWriter(NonBlocking);
+
+struct ScrubbingWriter(NonBlocking);
+
+impl<'a> MakeWriter<'a> for ScrubbingMakeWriter {
+    type Writer = ScrubbingWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        ScrubbingWriter(self.0.clone())
+    }
+}
+
+impl Write for ScrubbingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0
+            .write_all(redaction::scrub_secrets(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
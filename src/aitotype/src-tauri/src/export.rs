@@ -0,0 +1,282 @@
+//! 导出模块
+//!
+//! 按日期范围把转写记录导出为 Markdown、CSV 或纯文本。
+
+use crate::history;
+use crate::logging::TranscribeLogEntry;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Csv,
+    Text,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Text => "txt",
+        }
+    }
+}
+
+/// 导出参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// 起始日期 YYYY-MM-DD（含）
+    #[serde(default)]
+    pub start_date: Option<String>,
+    /// 结束日期 YYYY-MM-DD（含）
+    #[serde(default)]
+    pub end_date: Option<String>,
+    /// 导出文件路径；为空时写入系统下载目录
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportResult {
+    pub path: String,
+    pub count: usize,
+}
+
+const CSV_HEADER: &[&str] = &[
+    "id",
+    "timestamp",
+    "stt_provider",
+    "stt_model",
+    "stt_text",
+    "pre_correction_text",
+    "post_correction_text",
+    "correction_hits",
+    "enhancement_enabled",
+    "enhancement_provider",
+    "enhancement_model",
+    "enhancement_text",
+    "enhancement_status",
+    "enhancement_error",
+    "enhancement_duration_ms",
+    "final_text",
+    "audio_file",
+    "audio_duration_ms",
+    "status",
+    "pinned",
+    "timings",
+    "profile",
+];
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn entry_date(entry: &TranscribeLogEntry) -> &str {
+    entry.timestamp.get(..10).unwrap_or(&entry.timestamp)
+}
+
+fn entry_time(entry: &TranscribeLogEntry) -> String {
+    chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+        .map(|time| time.format("%H:%M:%S").to_string())
+        .unwrap_or_else(|_| entry.timestamp.clone())
+}
+
+fn range_label(options: &ExportOptions) -> String {
+    match (non_empty(&options.start_date), non_empty(&options.end_date)) {
+        (Some(start), Some(end)) if start == end => start.to_string(),
+        (Some(start), Some(end)) => format!("{} ~ {}", start, end),
+        (Some(start), None) => format!("{} ~", start),
+        (None, Some(end)) => format!("~ {}", end),
+        (None, None) => "全部".to_string(),
+    }
+}
+
+/// Markdown：按日期分组，每条记录带时间
+fn render_markdown(entries: &[TranscribeLogEntry], range: &str) -> String {
+    let mut output = format!("# AItoType 转写记录（{}）\n", range);
+    let mut current_date = "";
+    for entry in entries {
+        let date = entry_date(entry);
+        if date != current_date {
+            output.push_str(&format!("\n## {}\n\n", date));
+            current_date = date;
+        }
+        let text = entry.final_text.trim().replace('\n', "\n  ");
+        output.push_str(&format!("- **{}** {}\n", entry_time(entry), text));
+    }
+    output
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// CSV：包含 `TranscribeLogEntry` 的全部字段，易错词命中以 JSON 字符串保存
+fn render_csv(entries: &[TranscribeLogEntry]) -> String {
+    let opt = |value: &Option<String>| value.clone().unwrap_or_default();
+    let opt_num = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_default();
+
+    // UTF-8 BOM，便于 Excel 正确识别中文
+    let mut output = String::from("\u{feff}");
+    output.push_str(&CSV_HEADER.join(","));
+    output.push_str("\r\n");
+    for entry in entries {
+        let hits = entry
+            .correction_hits
+            .as_ref()
+            .and_then(|hits| serde_json::to_string(hits).ok())
+            .unwrap_or_default();
+        let row = [
+            entry.log_id().to_string(),
+            entry.timestamp.clone(),
+            entry.stt_provider.clone(),
+            entry.stt_model.clone(),
+            entry.stt_text.clone(),
+            opt(&entry.pre_correction_text),
+            opt(&entry.post_correction_text),
+            hits,
+            entry.enhancement_enabled.to_string(),
+            opt(&entry.enhancement_provider),
+            opt(&entry.enhancement_model),
+            opt(&entry.enhancement_text),
+            opt(&entry.enhancement_status),
+            opt(&entry.enhancement_error),
+            opt_num(entry.enhancement_duration_ms),
+            entry.final_text.clone(),
+            opt(&entry.audio_file),
+            opt_num(entry.audio_duration_ms),
+            opt(&entry.status),
+            entry.pinned.to_string(),
+            entry
+                .timings
+                .as_ref()
+                .and_then(|timings| serde_json::to_string(timings).ok())
+                .unwrap_or_default(),
+            opt(&entry.profile),
+        ];
+        let fields: Vec<String> = row.iter().map(|value| csv_field(value)).collect();
+        output.push_str(&fields.join(","));
+        output.push_str("\r\n");
+    }
+    output
+}
+
+/// 纯文本：只保留最终文本，条目之间空一行
+fn render_text(entries: &[TranscribeLogEntry]) -> String {
+    let texts: Vec<&str> = entries
+        .iter()
+        .map(|entry| entry.final_text.trim())
+        .collect();
+    let mut output = texts.join("\n\n");
+    output.push('\n');
+    output
+}
+
+fn render(format: ExportFormat, entries: &[TranscribeLogEntry], range: &str) -> String {
+    match format {
+        ExportFormat::Markdown => render_markdown(entries, range),
+        ExportFormat::Csv => render_csv(entries),
+        ExportFormat::Text => render_text(entries),
+    }
+}
+
+fn default_export_path(app: &tauri::AppHandle, options: &ExportOptions) -> Result<PathBuf, String> {
+    use tauri::Manager;
+
+    let dir = app
+        .path()
+        .download_dir()
+        .map_err(|e| format!("获取下载目录失败: {:?}", e))?;
+    let range = match (non_empty(&options.start_date), non_empty(&options.end_date)) {
+        (Some(start), Some(end)) if start == end => start.to_string(),
+        (Some(start), Some(end)) => format!("{}_{}", start, end),
+        (Some(start), None) => start.to_string(),
+        (None, Some(end)) => end.to_string(),
+        (None, None) => chrono::Local::now().format("%Y-%m-%d").to_string(),
+    };
+    Ok(dir.join(format!("aitotype_{}.{}", range, options.format.extension())))
+}
+
+/// 导出转写记录到文件，返回写入路径与条数
+pub fn export_history(
+    app: &tauri::AppHandle,
+    options: &ExportOptions,
+) -> Result<ExportResult, String> {
+    let entries = history::load_entries_in_range(
+        app,
+        non_empty(&options.start_date),
+        non_empty(&options.end_date),
+    )?;
+    if entries.is_empty() {
+        return Err("所选日期范围内没有转写记录".to_string());
+    }
+
+    let content = render(options.format, &entries, &range_label(options));
+    let path = match non_empty(&options.path) {
+        Some(path) => PathBuf::from(path),
+        None => default_export_path(app, options)?,
+    };
+    std::fs::write(&path, content).map_err(|e| format!("写入导出文件失败: {:?}", e))?;
+
+    Ok(ExportResult {
+        path: path.to_string_lossy().to_string(),
+        count: entries.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, final_text: &str, duration_ms: Option<u64>) -> TranscribeLogEntry {
+        TranscribeLogEntry {
+            id: "1".to_string(),
+            timestamp: timestamp.to_string(),
+            final_text: final_text.to_string(),
+            audio_duration_ms: duration_ms,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn markdown_groups_by_day() {
+        let entries = vec![
+            entry("2026-03-01T09:15:00+08:00", "第一条", None),
+            entry("2026-03-01T10:00:00+08:00", "第二条\n换行", None),
+            entry("2026-03-02T08:00:00+08:00", "第三条", None),
+        ];
+        let output = render_markdown(&entries, "2026-03-01 ~ 2026-03-02");
+        assert!(output.starts_with("# AItoType 转写记录（2026-03-01 ~ 2026-03-02）"));
+        assert_eq!(output.matches("## 2026-03-01").count(), 1);
+        assert!(output.contains("- **09:15:00** 第一条\n"));
+        assert!(output.contains("- **10:00:00** 第二条\n  换行\n"));
+        assert!(output.contains("## 2026-03-02"));
+    }
+
+    #[test]
+    fn csv_escapes_special_characters() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+
+        let output = render_csv(&[entry("2026-03-01T09:15:00+08:00", "x, y", Some(1200))]);
+        let lines: Vec<&str> = output.split("\r\n").collect();
+        assert_eq!(
+            lines[0].trim_start_matches('\u{feff}').split(',').count(),
+            CSV_HEADER.len()
+        );
+        assert!(lines[1].contains("\"x, y\""));
+    }
+}
@@ -0,0 +1,115 @@
+//! 历史记录模块
+//!
+//! 基于 SQLite 存储（见 `db` 模块）提供跨天分页、全文搜索、日期筛选、删除与置顶。
+
+use crate::archive;
+use crate::db::Store;
+use crate::logging::{self, TranscribeLogEntry};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 200;
+
+/// 历史查询条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryQuery {
+    /// 搜索关键词（匹配 final_text 与 stt_text，忽略大小写）
+    #[serde(default)]
+    pub query: Option<String>,
+    /// 起始日期 YYYY-MM-DD（含）
+    #[serde(default)]
+    pub start_date: Option<String>,
+    /// 结束日期 YYYY-MM-DD（含）
+    #[serde(default)]
+    pub end_date: Option<String>,
+    #[serde(default)]
+    pub pinned_only: bool,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// 一页历史记录
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HistoryPage {
+    pub items: Vec<TranscribeLogEntry>,
+    pub total: usize,
+    pub offset: usize,
+    pub has_more: bool,
+}
+
+fn store(app: &tauri::AppHandle) -> Result<tauri::State<'_, Store>, String> {
+    use tauri::Manager;
+
+    app.try_state::<Store>()
+        .ok_or_else(|| "历史数据库不可用".to_string())
+}
+
+/// 搜索历史记录
+pub fn search(app: &tauri::AppHandle, query: &HistoryQuery) -> Result<HistoryPage, String> {
+    search_store(&*store(app)?, query)
+}
+
+/// 在指定数据库中搜索（命令行工具直接打开数据库时使用）
+pub fn search_store(store: &Store, query: &HistoryQuery) -> Result<HistoryPage, String> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    store.search(query, limit)
+}
+
+/// 读取日期范围内的全部记录（按时间升序），用于导出与统计
+///
+/// 数据库不可用时回退读取日志文件。
+pub fn load_entries_in_range(
+    app: &tauri::AppHandle,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Vec<TranscribeLogEntry>, String> {
+    use tauri::Manager;
+
+    if let Some(store) = app.try_state::<Store>() {
+        let query = HistoryQuery {
+            start_date: start_date.map(str::to_string),
+            end_date: end_date.map(str::to_string),
+            ..Default::default()
+        };
+        return store.list_entries(&query);
+    }
+
+    let mut entries: Vec<TranscribeLogEntry> = logging::list_log_files(app)?
+        .into_iter()
+        .filter(|file| start_date.is_none_or(|start| file.date.as_str() >= start))
+        .filter(|file| end_date.is_none_or(|end| file.date.as_str() <= end))
+        .flat_map(|file| logging::read_entries(&file.path))
+        .filter(|entry| !entry.final_text.trim().is_empty())
+        .collect();
+    entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    Ok(entries)
+}
+
+/// 置顶或取消置顶
+pub fn set_pinned(app: &tauri::AppHandle, log_id: &str, pinned: bool) -> Result<(), String> {
+    if store(app)?.set_pinned(log_id, pinned)? {
+        Ok(())
+    } else {
+        Err(format!("未找到日志: {}", log_id))
+    }
+}
+
+/// 删除一条历史记录（连同其归档录音）
+pub fn delete_entry(app: &tauri::AppHandle, log_id: &str) -> Result<(), String> {
+    let removed = store(app)?
+        .delete_entry(log_id)?
+        .ok_or_else(|| format!("未找到日志: {}", log_id))?;
+
+    if let Some(audio_file) = removed.audio_file {
+        if let Err(e) = archive::remove_archived(app, &audio_file) {
+            warn!("删除归档录音失败 {}: {}", audio_file, e);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,1339 @@
+//! 逆文本规范化（ITN）模块
+//!
+//! 把转写结果中口语读法的数字改写为阿拉伯数字，支持中文与英文：
+//! 基数与小数、序数、日期、时间、百分比、货币与计量单位，每类规则可单独开关。
+//! 纯本地规则，不发任何网络请求。
+//!
+//! 规则偏保守：单独出现的小数字（"一个"、"one of them"）、约数（"三四天"）
+//! 和常见成语保持原样，有歧义时宁可不改。
+
+use serde::{Deserialize, Serialize};
+
+/// ITN 配置（默认关闭，开启后各规则默认全部启用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItnConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 基数与小数："三百二十" → 320，"three point five" → 3.5
+    #[serde(default = "default_rule_enabled")]
+    pub numbers: bool,
+    /// 序数："第三十" → 第30，"twenty first" → 21st
+    #[serde(default = "default_rule_enabled")]
+    pub ordinals: bool,
+    /// 日期："二零二六年三月五号" → 2026年3月5号，"March fifth" → March 5
+    #[serde(default = "default_rule_enabled")]
+    pub dates: bool,
+    /// 时间："下午三点半" → 下午3:30，"three thirty pm" → 3:30 pm
+    #[serde(default = "default_rule_enabled")]
+    pub times: bool,
+    /// 百分比："百分之五十" → 50%，"fifty percent" → 50%
+    #[serde(default = "default_rule_enabled")]
+    pub percentages: bool,
+    /// 货币："三百块钱" → 300块钱，"three hundred twenty dollars" → $320
+    #[serde(default = "default_rule_enabled")]
+    pub currencies: bool,
+    /// 计量单位："五公里" → 5公里，"five kilometers" → 5 km
+    #[serde(default = "default_rule_enabled")]
+    pub units: bool,
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+impl Default for ItnConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            numbers: true,
+            ordinals: true,
+            dates: true,
+            times: true,
+            percentages: true,
+            currencies: true,
+            units: true,
+        }
+    }
+}
+
+/// 对文本执行 ITN，未启用时原样返回
+pub fn apply(text: &str, config: &ItnConfig) -> String {
+    if !config.enabled || text.trim().is_empty() {
+        return text.to_string();
+    }
+    let text = apply_chinese(text, config);
+    apply_english(&text, config)
+}
+
+// ---------------------------------------------------------------------------
+// 中文
+// ---------------------------------------------------------------------------
+
+/// 含数字但不应转换的常见说法
+const ZH_IDIOMS: &[&str] = &[
+    "一五一十",
+    "三三两两",
+    "七七八八",
+    "一点一点",
+    "第一时间",
+    "十万八千里",
+];
+
+/// 单独的 "三点" 需要前面有时段词才按时间处理，避免误改 "有三点建议"
+const ZH_DAY_PERIODS: &[&str] = &[
+    "凌晨", "早上", "早晨", "上午", "中午", "下午", "傍晚", "晚上", "夜里",
+];
+
+/// 货币后缀（长的在前）
+const ZH_CURRENCY_WORDS: &[&str] = &[
+    "块钱", "美元", "美金", "欧元", "英镑", "日元", "港币", "港元", "元", "块",
+];
+
+/// 计量单位后缀（长的在前）
+const ZH_UNIT_WORDS: &[&str] = &[
+    "个百分点",
+    "平方公里",
+    "平方千米",
+    "平方米",
+    "立方米",
+    "摄氏度",
+    "华氏度",
+    "公里",
+    "千米",
+    "厘米",
+    "毫米",
+    "公斤",
+    "千克",
+    "毫克",
+    "毫升",
+    "小时",
+    "分钟",
+    "秒钟",
+    "米",
+    "克",
+    "斤",
+    "吨",
+    "升",
+    "度",
+    "秒",
+    "天",
+    "岁",
+    "倍",
+];
+
+fn zh_digit(c: char) -> Option<u64> {
+    Some(match c {
+        '零' | '〇' => 0,
+        '一' => 1,
+        '二' | '两' => 2,
+        '三' => 3,
+        '四' => 4,
+        '五' => 5,
+        '六' => 6,
+        '七' => 7,
+        '八' => 8,
+        '九' => 9,
+        _ => return None,
+    })
+}
+
+fn zh_unit(c: char) -> Option<u64> {
+    Some(match c {
+        '十' => 10,
+        '百' => 100,
+        '千' => 1_000,
+        '万' => 10_000,
+        '亿' => 100_000_000,
+        _ => return None,
+    })
+}
+
+fn is_zh_numeral(c: char) -> bool {
+    zh_digit(c).is_some() || zh_unit(c).is_some()
+}
+
+fn zh_run_end(chars: &[char], start: usize) -> usize {
+    let mut end = start;
+    while end < chars.len() && is_zh_numeral(chars[end]) {
+        end += 1;
+    }
+    end
+}
+
+fn zh_starts_with(chars: &[char], start: usize, word: &str) -> bool {
+    word.chars()
+        .enumerate()
+        .all(|(offset, c)| chars.get(start + offset) == Some(&c))
+}
+
+fn zh_suffix(chars: &[char], start: usize, words: &[&'static str]) -> Option<&'static str> {
+    words
+        .iter()
+        .copied()
+        .find(|word| zh_starts_with(chars, start, word))
+}
+
+fn zh_idiom_at(chars: &[char], start: usize) -> Option<usize> {
+    ZH_IDIOMS.iter().find_map(|idiom| {
+        let len = idiom.chars().count();
+        let followed_by_numeral = chars.get(start + len).is_some_and(|c| is_zh_numeral(*c));
+        (zh_starts_with(chars, start, idiom) && !followed_by_numeral).then_some(len)
+    })
+}
+
+fn zh_digits_string(run: &[char]) -> Option<String> {
+    run.iter()
+        .map(|c| zh_digit(*c).and_then(|d| char::from_digit(d as u32, 10)))
+        .collect()
+}
+
+/// 带单位的读法求值："两千零二十六" → 2026，"三千五" → 3500；
+/// 约数（"二十三四"）以及十/百/千重复或没有从大到小排列（"十十"、"十百"）返回 None
+fn zh_value(run: &[char]) -> Option<u64> {
+    let mut total: u64 = 0;
+    let mut wan: u64 = 0;
+    let mut section: u64 = 0;
+    let mut digit: Option<u64> = None;
+    let mut last_unit: u64 = 0;
+    // 当前节（万、亿之间）中上一个十/百/千单位
+    let mut small_unit: u64 = 0;
+    let mut after_zero = false;
+
+    for &c in run {
+        if let Some(d) = zh_digit(c) {
+            if d == 0 {
+                after_zero = true;
+                continue;
+            }
+            if digit.is_some() {
+                return None;
+            }
+            digit = Some(d);
+            continue;
+        }
+        let unit = zh_unit(c)?;
+        match unit {
+            10 | 100 | 1_000 => {
+                if small_unit != 0 && unit >= small_unit {
+                    return None;
+                }
+                small_unit = unit;
+                let d = match digit.take() {
+                    Some(d) => d,
+                    None if unit == 10 && !after_zero => 1,
+                    None => return None,
+                };
+                section = section.checked_add(d.checked_mul(unit)?)?;
+            }
+            10_000 => {
+                if wan > 0 {
+                    return None;
+                }
+                wan = section
+                    .checked_add(digit.take().unwrap_or(0))?
+                    .checked_mul(unit)?;
+                if wan == 0 {
+                    return None;
+                }
+                section = 0;
+                small_unit = 0;
+            }
+            _ => {
+                let level = total
+                    .checked_add(wan)?
+                    .checked_add(section)?
+                    .checked_add(digit.take().unwrap_or(0))?;
+                if level == 0 {
+                    return None;
+                }
+                total = level.checked_mul(unit)?;
+                wan = 0;
+                section = 0;
+                small_unit = 0;
+            }
+        }
+        last_unit = unit;
+        after_zero = false;
+    }
+
+    let mut tail = digit.unwrap_or(0);
+    // 口语省略末位单位："三千五" = 3500，"两万三" = 23000
+    if tail > 0 && !after_zero && last_unit >= 100 {
+        tail = tail.checked_mul(last_unit / 10)?;
+    }
+    total
+        .checked_add(wan)?
+        .checked_add(section)?
+        .checked_add(tail)
+}
+
+/// 日期、时间等位置上的小整数：单个数字或带单位的读法（不接受 "一二" 这类逐位读法）
+fn zh_small_value(run: &[char]) -> Option<u64> {
+    match run {
+        [] => None,
+        [c] => zh_digit(*c).or_else(|| zh_value(run)),
+        _ if run.iter().all(|c| zh_digit(*c).is_some()) => None,
+        _ => zh_value(run),
+    }
+}
+
+struct ZhNumber {
+    text: String,
+    end: usize,
+    /// 只有一个数字字（"三"、"十"），单独出现时不转换
+    lone: bool,
+    run_len: usize,
+    digits_only: bool,
+    decimal: bool,
+}
+
+fn parse_zh_number(chars: &[char], start: usize) -> Option<ZhNumber> {
+    let run_end = zh_run_end(chars, start);
+    if run_end == start {
+        return None;
+    }
+    let run = &chars[start..run_end];
+    let digits_only = run.iter().all(|c| zh_digit(*c).is_some());
+    let mut text = if digits_only {
+        zh_digits_string(run)?
+    } else {
+        zh_value(run)?.to_string()
+    };
+
+    // 小数："三点一四"；"三点十五"、"三点一刻" 不是小数
+    let mut end = run_end;
+    let mut decimal = false;
+    if chars.get(run_end) == Some(&'点') {
+        let mut frac_end = run_end + 1;
+        while frac_end < chars.len()
+            && chars[frac_end] != '两'
+            && zh_digit(chars[frac_end]).is_some()
+        {
+            frac_end += 1;
+        }
+        let followed_by = chars.get(frac_end).copied();
+        let blocked = followed_by.is_some_and(|c| zh_unit(c).is_some() && c != '万' && c != '亿')
+            || matches!(followed_by, Some('刻') | Some('分'));
+        if frac_end > run_end + 1 && !blocked {
+            text.push('.');
+            text.push_str(&zh_digits_string(&chars[run_end + 1..frac_end])?);
+            end = frac_end;
+            decimal = true;
+        }
+    }
+
+    Some(ZhNumber {
+        text,
+        end,
+        lone: run.len() == 1 && !decimal,
+        run_len: run.len(),
+        digits_only,
+        decimal,
+    })
+}
+
+fn apply_chinese(text: &str, config: &ItnConfig) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < chars.len() {
+        if let Some(len) = zh_idiom_at(&chars, index) {
+            out.extend(&chars[index..index + len]);
+            index += len;
+            continue;
+        }
+        if let Some((replacement, end)) = zh_rule_at(&chars, index, config) {
+            out.push_str(&replacement);
+            index = end;
+            continue;
+        }
+        // 未转换的数字串整体跳过，避免从中间重新匹配（"三四天" 不应变成 "三4天"）
+        let end = if is_zh_numeral(chars[index]) {
+            zh_run_end(&chars, index)
+        } else {
+            index + 1
+        };
+        out.extend(&chars[index..end]);
+        index = end;
+    }
+    out
+}
+
+fn zh_rule_at(chars: &[char], start: usize, config: &ItnConfig) -> Option<(String, usize)> {
+    let keep = |end: usize| Some((chars[start..end].iter().collect::<String>(), end));
+
+    for (prefix, sign) in [("百分之", "%"), ("千分之", "‰")] {
+        if zh_starts_with(chars, start, prefix) {
+            let number = parse_zh_number(chars, start + 3)?;
+            if !config.percentages {
+                return keep(number.end);
+            }
+            return Some((format!("{}{}", number.text, sign), number.end));
+        }
+    }
+
+    if chars[start] == '第' {
+        let number = parse_zh_number(chars, start + 1)?;
+        if number.decimal || !config.ordinals {
+            return keep(number.end);
+        }
+        return Some((format!("第{}", number.text), number.end));
+    }
+
+    let negative = chars[start] == '负' && chars.get(start + 1).is_some_and(|c| is_zh_numeral(*c));
+    let number_start = if negative { start + 1 } else { start };
+    if !is_zh_numeral(chars[number_start]) {
+        return None;
+    }
+    if !negative {
+        if config.times {
+            if let Some(time) = zh_time(chars, start) {
+                return Some(time);
+            }
+        }
+        if config.dates {
+            if let Some(date) = zh_date(chars, start) {
+                return Some(date);
+            }
+        }
+    }
+
+    let number = parse_zh_number(chars, number_start)?;
+    let sign = if negative { "-" } else { "" };
+    // 单独的 "一" 在量词前多是虚指（"一块去"、"一度"），两位逐字读法多是约数（"三四天"），都不转换
+    let lone_one = number.lone && chars[number_start] == '一';
+    let approximate = number.digits_only && number.run_len == 2 && !number.decimal;
+
+    if config.currencies {
+        if let Some(word) = zh_suffix(chars, number.end, ZH_CURRENCY_WORDS) {
+            if !lone_one && !approximate && (word != "块" || !number.lone) {
+                let end = number.end + word.chars().count();
+                return Some((format!("{}{}{}", sign, number.text, word), end));
+            }
+        }
+    }
+    if config.units {
+        if let Some(word) = zh_suffix(chars, number.end, ZH_UNIT_WORDS) {
+            if !lone_one && !approximate {
+                let end = number.end + word.chars().count();
+                return Some((format!("{}{}{}", sign, number.text, word), end));
+            }
+        }
+    }
+
+    let convert = number.decimal
+        || (number.digits_only && number.run_len >= 3)
+        || (!number.digits_only && number.run_len >= 2);
+    if config.numbers && convert {
+        return Some((format!("{}{}", sign, number.text), number.end));
+    }
+    None
+}
+
+fn zh_time(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let hour_end = zh_run_end(chars, start);
+    let hour = zh_small_value(&chars[start..hour_end])?;
+    if hour > 24 {
+        return None;
+    }
+    let separator = *chars.get(hour_end)?;
+    if separator != '点' && separator != '时' {
+        return None;
+    }
+    let pos = hour_end + 1;
+
+    let quarter = match (chars.get(pos), chars.get(pos + 1)) {
+        (Some('半'), _) => Some((30, pos + 1)),
+        (Some('一'), Some('刻')) => Some((15, pos + 2)),
+        (Some('三'), Some('刻')) => Some((45, pos + 2)),
+        _ => None,
+    };
+    if let Some((minute, end)) = quarter {
+        return Some((format!("{}:{:02}", hour, minute), end));
+    }
+
+    let minute_end = zh_run_end(chars, pos);
+    if minute_end > pos {
+        let run = &chars[pos..minute_end];
+        let zero_padded = run.len() == 2 && zh_digit(run[0]) == Some(0);
+        let minute = if zero_padded {
+            zh_digit(run[1])?
+        } else {
+            zh_small_value(run)?
+        };
+        if minute >= 60 {
+            return None;
+        }
+        if chars.get(minute_end) == Some(&'分') && chars.get(minute_end + 1) != Some(&'钟') {
+            let mut text = format!("{}:{:02}", hour, minute);
+            let mut end = minute_end + 1;
+            let second_end = zh_run_end(chars, end);
+            if second_end > end && chars.get(second_end) == Some(&'秒') {
+                if let Some(second) = zh_small_value(&chars[end..second_end]).filter(|s| *s < 60) {
+                    text.push_str(&format!(":{:02}", second));
+                    end = second_end + 1;
+                }
+            }
+            return Some((text, end));
+        }
+        // "三点十五"、"三点零五" 省略了 "分"；"三点五"、"三点零五元" 按小数处理
+        let spoken = zero_padded || run.iter().any(|c| zh_unit(*c).is_some());
+        let followed_by_unit = zh_suffix(chars, minute_end, ZH_CURRENCY_WORDS).is_some()
+            || zh_suffix(chars, minute_end, ZH_UNIT_WORDS).is_some();
+        if spoken && !followed_by_unit {
+            return Some((format!("{}:{:02}", hour, minute), minute_end));
+        }
+        return None;
+    }
+
+    let after_period = ZH_DAY_PERIODS.iter().any(|period| {
+        let len = period.chars().count();
+        start >= len && zh_starts_with(chars, start - len, period)
+    });
+    after_period.then(|| (format!("{}{}", hour, separator), pos))
+}
+
+fn zh_date(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let run_end = zh_run_end(chars, start);
+    let run = &chars[start..run_end];
+    if chars.get(run_end) != Some(&'年') {
+        return zh_month_day(chars, start);
+    }
+
+    let digits_only = run.iter().all(|c| zh_digit(*c).is_some());
+    let year = if digits_only && run.len() >= 2 {
+        zh_digits_string(run)?
+    } else if !digits_only {
+        zh_value(run).filter(|year| *year >= 1000)?.to_string()
+    } else {
+        return None;
+    };
+    let mut text = format!("{}年", year);
+    let mut end = run_end + 1;
+    if let Some((month_day, month_day_end)) = zh_month_day(chars, end) {
+        text.push_str(&month_day);
+        end = month_day_end;
+    }
+    Some((text, end))
+}
+
+fn zh_month_day(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let run_end = zh_run_end(chars, start);
+    let value = zh_small_value(&chars[start..run_end])?;
+    match chars.get(run_end) {
+        Some('月') if (1..=12).contains(&value) => {
+            let mut text = format!("{}月", value);
+            let mut end = run_end + 1;
+            let day_end = zh_run_end(chars, end);
+            if let (Some(day), Some(&suffix)) =
+                (zh_small_value(&chars[end..day_end]), chars.get(day_end))
+            {
+                if (1..=31).contains(&day) && (suffix == '日' || suffix == '号') {
+                    text.push_str(&format!("{}{}", day, suffix));
+                    end = day_end + 1;
+                }
+            }
+            Some((text, end))
+        }
+        Some('号') if (1..=31).contains(&value) => Some((format!("{}号", value), run_end + 1)),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 英文
+// ---------------------------------------------------------------------------
+
+const EN_UNITS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const EN_TEENS: [&str; 10] = [
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const EN_TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const EN_UNIT_ORDINALS: [&str; 10] = [
+    "", "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+];
+const EN_TEEN_ORDINALS: [&str; 10] = [
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+];
+const EN_TENS_ORDINALS: [&str; 8] = [
+    "twentieth",
+    "thirtieth",
+    "fortieth",
+    "fiftieth",
+    "sixtieth",
+    "seventieth",
+    "eightieth",
+    "ninetieth",
+];
+const EN_MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+/// 没有 am/pm 时，"three thirty" 前面需要有这些词才按时间处理
+const EN_TIME_CUES: &[&str] = &[
+    "at", "by", "around", "until", "till", "from", "before", "after", "about",
+];
+
+/// 货币：单词（单复数均可）与前置符号
+const EN_CURRENCIES: &[(&str, &str)] = &[
+    ("dollar", "$"),
+    ("buck", "$"),
+    ("euro", "€"),
+    ("yuan", "¥"),
+    ("yen", "¥"),
+];
+
+/// 缩写的计量单位：单词序列（每个词可加复数 s）与缩写，长的在前
+const EN_UNIT_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("kilometer per hour", " km/h"),
+    ("kilometre per hour", " km/h"),
+    ("mile per hour", " mph"),
+    ("square kilometer", " km²"),
+    ("square kilometre", " km²"),
+    ("square meter", " m²"),
+    ("square metre", " m²"),
+    ("cubic meter", " m³"),
+    ("cubic metre", " m³"),
+    ("degree celsius", "°C"),
+    ("degree centigrade", "°C"),
+    ("degree fahrenheit", "°F"),
+    ("degree", "°"),
+    ("kilometer", " km"),
+    ("kilometre", " km"),
+    ("centimeter", " cm"),
+    ("centimetre", " cm"),
+    ("millimeter", " mm"),
+    ("millimetre", " mm"),
+    ("meter", " m"),
+    ("metre", " m"),
+    ("kilogram", " kg"),
+    ("kilo", " kg"),
+    ("milligram", " mg"),
+    ("gram", " g"),
+    ("milliliter", " mL"),
+    ("millilitre", " mL"),
+    ("liter", " L"),
+    ("litre", " L"),
+    ("terabyte", " TB"),
+    ("gigabyte", " GB"),
+    ("megabyte", " MB"),
+    ("kilobyte", " KB"),
+];
+
+/// 只把数字改写、单位保留原词的计量单位
+const EN_UNIT_WORDS: &[&str] = &[
+    "second", "minute", "hour", "day", "week", "month", "year", "mile", "foot", "feet", "inch",
+    "inches", "pound", "ounce",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnWord {
+    Unit(u64),
+    Teen(u64),
+    Tens(u64),
+    Hundred,
+    Scale(u64),
+}
+
+/// 数词分类，第二个值表示是否为序数形式
+fn en_word(word: &str) -> Option<(EnWord, bool)> {
+    let position = |list: &[&str]| list.iter().position(|item| *item == word).map(|i| i as u64);
+    if let Some(i) = position(&EN_UNITS) {
+        return Some((EnWord::Unit(i), false));
+    }
+    if let Some(i) = position(&EN_TEENS) {
+        return Some((EnWord::Teen(10 + i), false));
+    }
+    if let Some(i) = position(&EN_TENS) {
+        return Some((EnWord::Tens((i + 2) * 10), false));
+    }
+    if let Some(i) = position(&EN_UNIT_ORDINALS[1..]) {
+        return Some((EnWord::Unit(i + 1), true));
+    }
+    if let Some(i) = position(&EN_TEEN_ORDINALS) {
+        return Some((EnWord::Teen(10 + i), true));
+    }
+    if let Some(i) = position(&EN_TENS_ORDINALS) {
+        return Some((EnWord::Tens((i + 2) * 10), true));
+    }
+    Some(match word {
+        "hundred" => (EnWord::Hundred, false),
+        "hundredth" => (EnWord::Hundred, true),
+        "thousand" => (EnWord::Scale(1_000), false),
+        "thousandth" => (EnWord::Scale(1_000), true),
+        "million" => (EnWord::Scale(1_000_000), false),
+        "millionth" => (EnWord::Scale(1_000_000), true),
+        "billion" => (EnWord::Scale(1_000_000_000), false),
+        "billionth" => (EnWord::Scale(1_000_000_000), true),
+        _ => return None,
+    })
+}
+
+fn is_en_number_word(word: &str) -> bool {
+    en_word(word).is_some()
+}
+
+struct Word<'a> {
+    start: usize,
+    end: usize,
+    text: &'a str,
+    lower: String,
+}
+
+fn en_words(text: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+    for (index, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        let in_word = c.is_ascii_alphabetic() || (start.is_some() && (c == '\'' || c == '’'));
+        match (start, in_word) {
+            (None, true) => start = Some(index),
+            (Some(begin), false) => {
+                let word = &text[begin..index];
+                words.push(Word {
+                    start: begin,
+                    end: index,
+                    text: word,
+                    lower: word.to_ascii_lowercase().replace('’', "'"),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// 第 index 与 index + 1 个词之间只隔着空格或连字符
+fn joinable(text: &str, words: &[Word], index: usize) -> bool {
+    if index + 1 >= words.len() {
+        return false;
+    }
+    let separator = &text[words[index].end..words[index + 1].start];
+    !separator.is_empty() && separator.len() <= 3 && separator.chars().all(|c| c == ' ' || c == '-')
+}
+
+struct EnNumber {
+    integer: u64,
+    fraction: Option<String>,
+    negative: bool,
+    ordinal: bool,
+    /// 第一个数词（不含 minus）
+    first: usize,
+    /// 之后第一个未使用的词
+    end: usize,
+}
+
+impl EnNumber {
+    fn is_plain_integer(&self) -> bool {
+        !self.negative && !self.ordinal && self.fraction.is_none()
+    }
+
+    fn single_word(&self) -> bool {
+        self.end - self.first == 1
+    }
+
+    fn render(&self) -> String {
+        let sign = if self.negative { "-" } else { "" };
+        match &self.fraction {
+            Some(fraction) => format!("{}{}.{}", sign, self.integer, fraction),
+            None => format!("{}{}", sign, self.integer),
+        }
+    }
+}
+
+fn parse_en_number(text: &str, words: &[Word], start: usize) -> Option<EnNumber> {
+    let mut index = start;
+    let mut negative = false;
+    if matches!(words.get(index)?.lower.as_str(), "minus" | "negative")
+        && joinable(text, words, index)
+        && is_en_number_word(&words[index + 1].lower)
+    {
+        negative = true;
+        index += 1;
+    }
+
+    let first = index;
+    let mut total: u64 = 0;
+    let mut current: u64 = 0;
+    let mut last: Option<EnWord> = None;
+    let mut last_scale = u64::MAX;
+    let mut group_has_hundred = false;
+    let mut ordinal = false;
+
+    while index < words.len() {
+        if index > first && !joinable(text, words, index - 1) {
+            break;
+        }
+        let lower = words[index].lower.as_str();
+        if lower == "and" {
+            let next_is_small = joinable(text, words, index)
+                && matches!(
+                    en_word(&words[index + 1].lower),
+                    Some((EnWord::Unit(1..), _) | (EnWord::Teen(_), _) | (EnWord::Tens(_), _))
+                );
+            if matches!(last, Some(EnWord::Hundred) | Some(EnWord::Scale(_))) && next_is_small {
+                index += 1;
+                continue;
+            }
+            break;
+        }
+        let Some((kind, is_ordinal)) = en_word(lower) else {
+            break;
+        };
+        let after_group_start =
+            matches!(last, None | Some(EnWord::Hundred) | Some(EnWord::Scale(_)));
+        let accepted = match kind {
+            EnWord::Unit(0) => last.is_none(),
+            EnWord::Unit(d) => {
+                let ok = after_group_start || matches!(last, Some(EnWord::Tens(_)));
+                if ok {
+                    current += d;
+                }
+                ok
+            }
+            EnWord::Teen(value) | EnWord::Tens(value) => {
+                if after_group_start {
+                    current += value;
+                }
+                after_group_start
+            }
+            EnWord::Hundred => {
+                let ok = (1..100).contains(&current)
+                    && !group_has_hundred
+                    && !matches!(last, None | Some(EnWord::Scale(_)));
+                if ok {
+                    current *= 100;
+                    group_has_hundred = true;
+                }
+                ok
+            }
+            EnWord::Scale(scale) => {
+                let ok =
+                    current > 0 && scale < last_scale && !matches!(last, Some(EnWord::Scale(_)));
+                if ok {
+                    total = total.checked_add(current.checked_mul(scale)?)?;
+                    current = 0;
+                    last_scale = scale;
+                    group_has_hundred = false;
+                }
+                ok
+            }
+        };
+        if !accepted {
+            break;
+        }
+        last = Some(kind);
+        index += 1;
+        if is_ordinal {
+            ordinal = true;
+            break;
+        }
+    }
+    if index == first {
+        return None;
+    }
+
+    // 小数："three point one four"
+    let mut fraction = None;
+    if !ordinal
+        && words.get(index).is_some_and(|word| word.lower == "point")
+        && joinable(text, words, index - 1)
+    {
+        let mut digits = String::new();
+        let mut cursor = index + 1;
+        while cursor < words.len() && joinable(text, words, cursor - 1) {
+            let digit = match words[cursor].lower.as_str() {
+                "oh" => 0,
+                lower => match en_word(lower) {
+                    Some((EnWord::Unit(d), false)) => d,
+                    _ => break,
+                },
+            };
+            digits.push(char::from_digit(digit as u32, 10)?);
+            cursor += 1;
+        }
+        if !digits.is_empty() {
+            fraction = Some(digits);
+            index = cursor;
+        }
+    }
+
+    Some(EnNumber {
+        integer: total.checked_add(current)?,
+        fraction,
+        negative,
+        ordinal,
+        first,
+        end: index,
+    })
+}
+
+fn ordinal_suffix(value: u64) -> &'static str {
+    match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// 匹配单词序列，每个词允许复数形式；返回之后第一个未使用的词
+fn en_match_phrase(text: &str, words: &[Word], start: usize, phrase: &str) -> Option<usize> {
+    let mut index = start;
+    for (offset, expected) in phrase.split(' ').enumerate() {
+        let word = words.get(index)?;
+        if offset > 0 && !joinable(text, words, index - 1) {
+            return None;
+        }
+        let plural = word.lower.strip_suffix('s') == Some(expected);
+        if word.lower != expected && !plural {
+            return None;
+        }
+        index += 1;
+    }
+    Some(index)
+}
+
+/// 紧跟在 `from` 之后的 am / pm（含 a.m. / p.m.），返回结束位置与原文
+fn en_meridiem(text: &str, from: usize) -> Option<(usize, &str)> {
+    let rest = &text[from..];
+    let trimmed = rest.trim_start_matches(' ');
+    if trimmed.len() == rest.len() {
+        return None;
+    }
+    let offset = from + rest.len() - trimmed.len();
+    ["a.m.", "p.m.", "am", "pm"].iter().find_map(|candidate| {
+        let matched = trimmed.get(..candidate.len())?;
+        let boundary = !trimmed[candidate.len()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphanumeric());
+        (matched.eq_ignore_ascii_case(candidate) && boundary)
+            .then(|| (offset + candidate.len(), matched))
+    })
+}
+
+fn apply_english(text: &str, config: &ItnConfig) -> String {
+    let words = en_words(text);
+    let mut out = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for index in 0..words.len() {
+        if words[index].start < cursor {
+            continue;
+        }
+        if let Some((replacement, end)) = en_rule_at(text, &words, index, config) {
+            out.push_str(&text[cursor..words[index].start]);
+            out.push_str(&replacement);
+            cursor = end;
+        }
+    }
+    out.push_str(&text[cursor..]);
+    out
+}
+
+fn en_rule_at(
+    text: &str,
+    words: &[Word],
+    index: usize,
+    config: &ItnConfig,
+) -> Option<(String, usize)> {
+    if config.dates {
+        if let Some(date) = en_date(text, words, index) {
+            return Some(date);
+        }
+    }
+    if config.times {
+        if let Some(time) = en_time(text, words, index) {
+            return Some(time);
+        }
+    }
+
+    // 前面紧挨着另一个数词（"nineteen ninety"、"three thirty"）时读法有歧义，不转换
+    if index > 0 && joinable(text, words, index - 1) && is_en_number_word(&words[index - 1].lower) {
+        return None;
+    }
+    let number = parse_en_number(text, words, index)?;
+    let last_end = words[number.end - 1].end;
+
+    if config.percentages {
+        for phrase in ["percent", "per cent"] {
+            if number.end < words.len() && joinable(text, words, number.end - 1) {
+                if let Some(end) = en_match_phrase(text, words, number.end, phrase) {
+                    return Some((format!("{}%", number.render()), words[end - 1].end));
+                }
+            }
+        }
+    }
+
+    let next_joinable = number.end < words.len() && joinable(text, words, number.end - 1);
+    if config.currencies && next_joinable && !number.ordinal {
+        if let Some(currency) = en_currency(text, words, &number) {
+            return Some(currency);
+        }
+    }
+    if config.units && next_joinable && !number.ordinal {
+        for (phrase, abbreviation) in EN_UNIT_ABBREVIATIONS {
+            if let Some(end) = en_match_phrase(text, words, number.end, phrase) {
+                return Some((
+                    format!("{}{}", number.render(), abbreviation),
+                    words[end - 1].end,
+                ));
+            }
+        }
+        let lone_one = number.single_word() && number.integer == 1 && number.is_plain_integer();
+        let unit = &words[number.end].lower;
+        let matches_unit = EN_UNIT_WORDS
+            .iter()
+            .any(|word| unit == word || unit.strip_suffix('s') == Some(word));
+        // "one day" 多是 "有一天"，保持原样
+        if matches_unit && !lone_one {
+            let unit_word = words[number.end].text;
+            return Some((
+                format!("{} {}", number.render(), unit_word),
+                words[number.end].end,
+            ));
+        }
+    }
+
+    // 后面紧跟数词时同样有歧义（"three thirty" 的 "three"）
+    let followed_by_number = next_joinable && is_en_number_word(&words[number.end].lower);
+    if followed_by_number {
+        return None;
+    }
+    if number.ordinal {
+        let convert = config.ordinals && (number.integer >= 10 || !number.single_word());
+        return convert.then(|| {
+            (
+                format!("{}{}", number.integer, ordinal_suffix(number.integer)),
+                last_end,
+            )
+        });
+    }
+    let convert = number.integer >= 10
+        || !number.single_word()
+        || number.fraction.is_some()
+        || number.negative;
+    (config.numbers && convert).then(|| (number.render(), last_end))
+}
+
+fn en_currency(text: &str, words: &[Word], number: &EnNumber) -> Option<(String, usize)> {
+    let sign = if number.negative { "-" } else { "" };
+    let amount = number.render().trim_start_matches('-').to_string();
+    let next = &words[number.end];
+    if matches!(next.lower.as_str(), "cent" | "cents") {
+        return Some((format!("{}{}¢", sign, amount), next.end));
+    }
+    let (_, symbol) = EN_CURRENCIES
+        .iter()
+        .find(|(word, _)| next.lower == *word || next.lower.strip_suffix('s') == Some(word))?;
+
+    // "five dollars and fifty cents" → $5.50
+    let and_index = number.end + 1;
+    if number.fraction.is_none()
+        && words.get(and_index).is_some_and(|word| word.lower == "and")
+        && joinable(text, words, number.end)
+        && joinable(text, words, and_index)
+    {
+        if let Some(cents) = parse_en_number(text, words, and_index + 1) {
+            let cents_word = words.get(cents.end);
+            let is_cents = cents_word
+                .is_some_and(|word| matches!(word.lower.as_str(), "cent" | "cents"))
+                && joinable(text, words, cents.end - 1);
+            if is_cents && cents.is_plain_integer() && (1..100).contains(&cents.integer) {
+                return Some((
+                    format!("{}{}{}.{:02}", sign, symbol, amount, cents.integer),
+                    words[cents.end].end,
+                ));
+            }
+        }
+    }
+    Some((format!("{}{}{}", sign, symbol, amount), next.end))
+}
+
+fn en_time(text: &str, words: &[Word], index: usize) -> Option<(String, usize)> {
+    let hour = match en_word(&words[index].lower)? {
+        (EnWord::Unit(d), false) if d >= 1 => d,
+        (EnWord::Teen(value), false) if value <= 12 => value,
+        _ => return None,
+    };
+    if joinable(text, words, index)
+        && matches!(words[index + 1].lower.as_str(), "o'clock" | "oclock")
+    {
+        return Some((format!("{}:00", hour), words[index + 1].end));
+    }
+
+    let mut minute = None;
+    let mut last = index;
+    if joinable(text, words, index) {
+        if words[index + 1].lower == "oh" {
+            if joinable(text, words, index + 1) {
+                if let Some((EnWord::Unit(d), false)) = en_word(&words[index + 2].lower) {
+                    if d >= 1 {
+                        minute = Some(d);
+                        last = index + 2;
+                    }
+                }
+            }
+        } else if let Some(number) = parse_en_number(text, words, index + 1) {
+            let spoken = matches!(
+                en_word(&words[index + 1].lower),
+                Some((EnWord::Teen(_), false) | (EnWord::Tens(_), false))
+            );
+            if spoken && number.is_plain_integer() && (10..60).contains(&number.integer) {
+                minute = Some(number.integer);
+                last = number.end - 1;
+            }
+        }
+    }
+
+    let cued = index > 0
+        && joinable(text, words, index - 1)
+        && EN_TIME_CUES.contains(&words[index - 1].lower.as_str());
+    match (minute, en_meridiem(text, words[last].end)) {
+        (Some(minute), Some((end, suffix))) => {
+            Some((format!("{}:{:02} {}", hour, minute, suffix), end))
+        }
+        (Some(minute), None) if cued => Some((format!("{}:{:02}", hour, minute), words[last].end)),
+        (None, Some((end, suffix))) => Some((format!("{} {}", hour, suffix), end)),
+        _ => None,
+    }
+}
+
+fn en_date(text: &str, words: &[Word], index: usize) -> Option<(String, usize)> {
+    let word = &words[index];
+    if !EN_MONTHS.contains(&word.lower.as_str()) || !joinable(text, words, index) {
+        return None;
+    }
+    // "may" 多是情态动词，只认大写的月份
+    if word.lower == "may" && !word.text.starts_with('M') {
+        return None;
+    }
+    let day = parse_en_number(text, words, index + 1)?;
+    if day.negative
+        || day.fraction.is_some()
+        || day.end - day.first > 2
+        || !(1..=31).contains(&day.integer)
+    {
+        return None;
+    }
+
+    let mut result = format!("{} {}", word.text, day.integer);
+    let mut end = words[day.end - 1].end;
+    if day.end < words.len() {
+        let separator = &text[end..words[day.end].start];
+        let separated = !separator.is_empty()
+            && separator.len() <= 3
+            && separator.trim_start_matches(',').chars().all(|c| c == ' ');
+        if separated {
+            if let Some((year, year_end)) = en_year(text, words, day.end) {
+                result.push_str(&format!(", {}", year));
+                end = words[year_end - 1].end;
+            }
+        }
+    }
+    Some((result, end))
+}
+
+/// 年份："twenty twenty six"、"nineteen oh five"、"nineteen hundred"、"two thousand and five"
+fn en_year(text: &str, words: &[Word], index: usize) -> Option<(u64, usize)> {
+    let century = match en_word(&words[index].lower) {
+        Some((EnWord::Teen(value), false)) => Some(value),
+        Some((EnWord::Tens(20), false)) => Some(20),
+        _ => None,
+    };
+    if let Some(century) = century.filter(|_| joinable(text, words, index)) {
+        let next = words[index + 1].lower.as_str();
+        if next == "hundred" {
+            return Some((century * 100, index + 2));
+        }
+        if next == "oh" && joinable(text, words, index + 1) {
+            if let Some((EnWord::Unit(d), false)) = en_word(&words[index + 2].lower) {
+                if d >= 1 {
+                    return Some((century * 100 + d, index + 3));
+                }
+            }
+        }
+        let spoken = matches!(
+            en_word(next),
+            Some((EnWord::Teen(_), false) | (EnWord::Tens(_), false))
+        );
+        if spoken {
+            if let Some(rest) = parse_en_number(text, words, index + 1) {
+                if rest.is_plain_integer() && (10..100).contains(&rest.integer) {
+                    return Some((century * 100 + rest.integer, rest.end));
+                }
+            }
+        }
+    }
+
+    let number = parse_en_number(text, words, index)?;
+    (number.is_plain_integer() && (1000..3000).contains(&number.integer))
+        .then_some((number.integer, number.end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> ItnConfig {
+        ItnConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn converts_chinese() {
+        let config = enabled();
+        let cases = [
+            ("二零二六年三月五号开会", "2026年3月5号开会"),
+            ("两千零二十六年十二月三十一日", "2026年12月31日"),
+            ("一共三百二十个", "一共320个"),
+            ("大约三千五", "大约3500"),
+            ("一万两千零五", "12005"),
+            ("圆周率是三点一四一五", "圆周率是3.1415"),
+            ("下午三点半见", "下午3:30见"),
+            ("十二点十五分三十秒", "12:15:30"),
+            ("晚上八点出发", "晚上8点出发"),
+            ("增长了百分之三十五点五", "增长了35.5%"),
+            ("花了三百块钱", "花了300块钱"),
+            ("五十美元", "50美元"),
+            ("跑了五公里", "跑了5公里"),
+            ("负五度", "-5度"),
+            ("第三十名", "第30名"),
+            ("坐三号线", "坐3号线"),
+            ("电话一三八零零一三八零零零", "电话13800138000"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(apply(input, &config), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn keeps_ambiguous_chinese() {
+        let config = enabled();
+        for text in [
+            "一个人",
+            "有三点建议",
+            "快一点",
+            "三四天",
+            "一块去吃饭",
+            "一五一十地说",
+            "第一时间通知",
+            "三三两两",
+            "十分好",
+            "万一下雨",
+            "千万别忘了",
+            "十十",
+        ] {
+            assert_eq!(apply(text, &config), text);
+        }
+    }
+
+    #[test]
+    fn converts_english() {
+        let config = enabled();
+        let cases = [
+            ("it costs three hundred twenty dollars", "it costs $320"),
+            ("five dollars and fifty cents", "$5.50"),
+            ("one hundred and five people", "105 people"),
+            ("twenty-one thousand", "21000"),
+            ("pi is three point one four", "pi is 3.14"),
+            ("minus five degrees celsius", "-5°C"),
+            ("fifty percent off", "50% off"),
+            ("the twenty first floor", "the 21st floor"),
+            ("due March fifth twenty twenty six", "due March 5, 2026"),
+            ("on July fourth, nineteen oh five", "on July 4, 1905"),
+            ("meet at three thirty", "meet at 3:30"),
+            ("call me at seven pm", "call me at 7 pm"),
+            (
+                "wake up at six forty-five a.m. sharp",
+                "wake up at 6:45 a.m. sharp",
+            ),
+            ("it is ten o'clock", "it is 10:00"),
+            (
+                "ran ten kilometers in forty minutes",
+                "ran 10 km in 40 minutes",
+            ),
+            ("wait five seconds", "wait 5 seconds"),
+            ("Twelve apples", "12 apples"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(apply(input, &config), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn keeps_ambiguous_english() {
+        let config = enabled();
+        for text in [
+            "one of them",
+            "no one knows",
+            "at first",
+            "wait a second",
+            "one day I will",
+            "you may find one",
+            "nineteen ninety nine",
+            "someone",
+        ] {
+            assert_eq!(apply(text, &config), text);
+        }
+    }
+
+    #[test]
+    fn respects_rule_switches() {
+        assert_eq!(apply("三百二十", &ItnConfig::default()), "三百二十");
+
+        let config = ItnConfig {
+            currencies: false,
+            percentages: false,
+            ..enabled()
+        };
+        assert_eq!(apply("百分之五十", &config), "百分之五十");
+        assert_eq!(apply("twenty dollars", &config), "20 dollars");
+        assert_eq!(apply("fifty percent", &config), "50 percent");
+
+        let config = ItnConfig {
+            numbers: false,
+            ..enabled()
+        };
+        assert_eq!(apply("三百二十个, 三百块", &config), "三百二十个, 300块");
+        assert_eq!(apply("twenty people", &config), "twenty people");
+    }
+}
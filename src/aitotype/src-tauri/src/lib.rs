@@ -5,22 +5,52 @@
 //! - 语音识别 (stt 模块) - 支持 OpenRouter / SiliconFlow
 //! - 键盘输入 (keyboard 模块)
 
+mod archive;
 mod audio;
+mod bundle;
+mod cli;
+mod config;
+mod control;
 mod corrections;
+mod db;
+mod diagnostics;
+mod export;
+mod history;
+mod itn;
 mod keyboard;
 mod logging;
+#[cfg(test)]
+mod mock_provider;
+mod paths;
+mod pipeline;
+mod policy;
+mod postprocess;
+mod profiles;
+mod queue;
+mod redaction;
+mod secrets;
+mod settings;
+mod stats;
 mod stt;
+mod timing;
 
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::Path;
 use std::sync::Mutex;
 use std::time::Duration;
 use stt::SttConfig;
 use tauri::{Emitter, State};
 use tauri_plugin_global_shortcut::GlobalShortcutExt;
-
-const LEGACY_ENHANCEMENT_OPENROUTER_MODEL: &str = "google/gemini-2.0-flash-001";
-const LEGACY_ENHANCEMENT_PROGRAMMER_PROMPT: &str = "你是程序员语音转文字的润色助手。请按规则处理文本：\n1) 去除口头禅、重复词和无意义停顿词；\n2) 修正技术术语、产品名、代码相关拼写错误；\n3) 保留原意，不扩写、不总结、不补充新信息；\n4) 仅做必要标点与断句优化；\n5) 只输出润色后的最终文本，不要任何解释。\n\n原文：\n{text}";
+use tauri_plugin_notification::NotificationExt;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+const TRANSCRIPTION_CANCELLED_ERROR: &str = "cancelled|转写已取消";
+const LOG_STATUS_SUCCESS: &str = "success";
+const LOG_STATUS_CANCELLED: &str = "cancelled";
+const QUEUE_WORKER_INTERVAL_SECS: u64 = 30;
+const LOG_RETENTION_INTERVAL_SECS: u64 = 24 * 60 * 60;
 const GITHUB_RELEASE_LATEST_API: &str =
     "https://api.github.com/repos/robinshi2010/AItoType/releases/latest";
 
@@ -43,7 +73,8 @@ fn load_env_files() {
 }
 
 fn normalize_stt_config(config: SttConfig) -> SttConfig {
-    let mut normalized = config;
+    // 策略锁定的字段以管理员下发的值为准
+    let mut normalized = policy::current().enforce(config);
     normalized.provider = stt::normalize_provider(&normalized.provider);
 
     if normalized.base_url.trim().is_empty() {
@@ -66,32 +97,24 @@ fn normalize_stt_config(config: SttConfig) -> SttConfig {
         .trim_end_matches('/')
         .to_string();
     normalized.enhancement_api_key = normalized.enhancement_api_key.trim().to_string();
+    normalized.diagnostic_log_level = diagnostics::normalize_level(&normalized.diagnostic_log_level);
+    normalized.post_process = normalized.post_process.normalized();
+    if normalized.control_api_port == 0 {
+        normalized.control_api_port = control::DEFAULT_PORT;
+    }
     if normalized.enhancement_model.trim().is_empty() {
         normalized.enhancement_model =
             stt::default_enhancement_model_for_provider(&normalized.enhancement_provider)
                 .to_string();
-    } else if normalized.enhancement_provider == stt::PROVIDER_OPENROUTER
-        && normalized
-            .enhancement_model
-            .trim()
-            .eq_ignore_ascii_case(LEGACY_ENHANCEMENT_OPENROUTER_MODEL)
-    {
-        normalized.enhancement_model =
-            stt::default_enhancement_model_for_provider(&normalized.enhancement_provider)
-                .to_string();
     } else {
         normalized.enhancement_model = normalized.enhancement_model.trim().to_string();
     }
     if normalized.enhancement_prompt.trim().is_empty() {
         normalized.enhancement_prompt = SttConfig::default().enhancement_prompt;
     } else {
-        let trimmed_prompt = normalized.enhancement_prompt.trim();
-        if trimmed_prompt == LEGACY_ENHANCEMENT_PROGRAMMER_PROMPT {
-            normalized.enhancement_prompt = SttConfig::default().enhancement_prompt;
-        } else {
-            normalized.enhancement_prompt = trimmed_prompt.to_string();
-        }
+        normalized.enhancement_prompt = normalized.enhancement_prompt.trim().to_string();
     }
+    normalized.version = config::CONFIG_VERSION;
     normalized
 }
 
@@ -104,29 +127,253 @@ fn has_resolved_api_key(config: &SttConfig) -> bool {
     matches!(std::env::var(env_key), Ok(value) if !value.trim().is_empty())
 }
 
+/// 没有配置文件时的初始配置：策略默认值，API Key 取自环境变量
+fn initial_stt_config() -> SttConfig {
+    load_env_files();
+
+    let mut config = policy::current()
+        .apply_defaults(SttConfig::default())
+        .unwrap_or_default();
+    let env_key = stt::env_key_for_provider(&config.provider);
+    if let Ok(value) = std::env::var(env_key) {
+        config.api_key = value.trim().to_string();
+    }
+    normalize_stt_config(config)
+}
+
 /// 应用状态
 pub struct AppState {
     stt_config: Mutex<SttConfig>,
     shortcut_plugin_ready: AtomicBool,
+    /// 当前转写任务的取消令牌（无任务时为 None）
+    transcription_cancel: Mutex<Option<CancellationToken>>,
+    /// 录音快捷键（空字符串表示未启用）
+    shortcut: Mutex<String>,
+    /// 取消转写快捷键（空字符串表示未启用）
+    cancel_shortcut: Mutex<String>,
+    /// 串行化离线队列重试，避免自动与手动重试同时处理同一条录音
+    queue_lock: tokio::sync::Mutex<()>,
+    /// 最近一次听写等待粘贴输出，用于补记输出耗时
+    pending_output: Mutex<Option<PendingOutput>>,
+    /// 当前录音会话的诊断日志 span（开始录音时创建，转写结束时关闭）
+    recording_span: Mutex<Option<tracing::Span>>,
+    /// 启动时配置无法解析被重置的提示（前端加载后取走）
+    config_reset_notice: Mutex<Option<config::ConfigResetPayload>>,
+    /// 当前配置方案名称
+    active_profile: Mutex<String>,
+    /// 最近一次听写结果（供本地控制接口查询）
+    last_result: Mutex<Option<control::LastResult>>,
+    /// 每次 `stop_and_transcribe` 结束时广播结果，本地控制接口据此等待最终文本
+    transcription_done: tokio::sync::broadcast::Sender<Result<String, String>>,
+}
+
+/// 已写入日志、尚未粘贴输出的听写结果
+struct PendingOutput {
+    log_id: String,
+    text: String,
+    timings: timing::StageTimings,
 }
 
 impl Default for AppState {
     fn default() -> Self {
-        load_env_files();
+        Self {
+            stt_config: Mutex::new(initial_stt_config()),
+            shortcut_plugin_ready: AtomicBool::new(false),
+            transcription_cancel: Mutex::new(None),
+            shortcut: Mutex::new(String::new()),
+            cancel_shortcut: Mutex::new(String::new()),
+            queue_lock: tokio::sync::Mutex::new(()),
+            pending_output: Mutex::new(None),
+            recording_span: Mutex::new(None),
+            config_reset_notice: Mutex::new(None),
+            active_profile: Mutex::new(profiles::DEFAULT_PROFILE.to_string()),
+            last_result: Mutex::new(None),
+            transcription_done: tokio::sync::broadcast::channel(4).0,
+        }
+    }
+}
 
-        let mut config = SttConfig::default();
-        let env_key = stt::env_key_for_provider(&config.provider);
-        if let Ok(value) = std::env::var(env_key) {
-            config.api_key = value.trim().to_string();
+/// 一次转写任务的生命周期，结束（含提前返回）时自动移除取消令牌
+struct TranscriptionSession<'a> {
+    state: &'a AppState,
+    token: CancellationToken,
+}
+
+impl<'a> TranscriptionSession<'a> {
+    fn begin(state: &'a AppState) -> Self {
+        let token = CancellationToken::new();
+        if let Ok(mut guard) = state.transcription_cancel.lock() {
+            *guard = Some(token.clone());
         }
+        Self { state, token }
+    }
+}
 
-        Self {
-            stt_config: Mutex::new(normalize_stt_config(config)),
-            shortcut_plugin_ready: AtomicBool::new(false),
+impl Drop for TranscriptionSession<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut guard) = self.state.transcription_cancel.lock() {
+            *guard = None;
+        }
+    }
+}
+
+/// 开启录音归档时压缩保存录音并按保留策略清理，返回归档文件名
+fn archive_recording_if_enabled(
+    app: &tauri::AppHandle,
+    config: &SttConfig,
+    wav_path: &Path,
+) -> Option<String> {
+    if !config.audio_archive_enabled {
+        return None;
+    }
+
+    let file_name = match archive::archive_recording(app, wav_path) {
+        Ok(file_name) => file_name,
+        Err(e) => {
+            warn!("归档录音失败: {}", e);
+            return None;
         }
+    };
+    if let Err(e) = archive::prune(
+        app,
+        config.audio_archive_max_age_days,
+        config.audio_archive_max_total_mb,
+    ) {
+        warn!("清理归档录音失败: {}", e);
+    }
+    Some(file_name)
+}
+
+/// 未关闭日志记录时写入转写日志
+fn append_log_if_enabled(
+    app: &tauri::AppHandle,
+    config: &SttConfig,
+    mut entry: logging::TranscribeLogEntry,
+) {
+    if config.log_enabled {
+        redaction::mask_log_entry(&mut entry, &config.redaction);
+        logging::append_log(app, entry);
+    }
+}
+
+fn active_profile_name(state: &AppState) -> String {
+    state
+        .active_profile
+        .lock()
+        .map(|name| name.clone())
+        .unwrap_or_else(|_| profiles::DEFAULT_PROFILE.to_string())
+}
+
+/// 写入日志并发出各阶段耗时（调试事件 `pipeline-timings-event`）
+///
+/// 成功的听写会等待 `paste_text` 补记输出耗时。
+fn finish_pipeline_log(
+    app: &tauri::AppHandle,
+    config: &SttConfig,
+    mut entry: logging::TranscribeLogEntry,
+    timings: timing::StageTimings,
+) {
+    use tauri::Manager;
+
+    entry.timings = Some(timings.clone());
+    entry.profile = Some(active_profile_name(&app.state::<AppState>()));
+    let log_id = entry.log_id().to_string();
+    let succeeded = entry.status.as_deref() == Some(LOG_STATUS_SUCCESS);
+    let text = entry.final_text.clone();
+    append_log_if_enabled(app, config, entry);
+
+    debug!("转写各阶段耗时 {}: {:?}", log_id, timings);
+    let _ = app.emit(
+        "pipeline-timings-event",
+        PipelineTimingsPayload {
+            log_id: log_id.clone(),
+            timings: timings.clone(),
+        },
+    );
+
+    if let Ok(mut pending) = app.state::<AppState>().pending_output.lock() {
+        *pending = succeeded.then_some(PendingOutput {
+            log_id,
+            text,
+            timings,
+        });
     }
 }
 
+/// 按保留策略清理转写日志（数据库与日志文件），按需压缩非当天的日志文件
+fn enforce_log_retention(app: &tauri::AppHandle, config: &SttConfig) {
+    use tauri::Manager;
+
+    let store = app.try_state::<db::Store>();
+    if let Some(store) = store.as_ref() {
+        let today = chrono::Local::now().date_naive();
+        if let Err(e) = store.prune(today, config.log_max_age_days, config.log_max_total_mb) {
+            warn!("清理历史记录失败: {}", e);
+        }
+    }
+    if let Err(e) =
+        logging::prune_log_files(app, config.log_max_age_days, config.log_max_total_mb)
+    {
+        warn!("清理日志文件失败: {}", e);
+    }
+
+    if !config.log_compress_old_files {
+        return;
+    }
+    match logging::compress_old_log_files(app) {
+        Ok(compressed) => {
+            for (old_name, new_name, size) in compressed {
+                if let Some(store) = store.as_ref() {
+                    if let Err(e) = store.rename_imported_file(&old_name, &new_name, size) {
+                        warn!("更新日志导入记录失败: {}", e);
+                    }
+                }
+            }
+        }
+        Err(e) => warn!("压缩日志文件失败: {}", e),
+    }
+}
+
+fn remove_temp_recording(file_path: &str) {
+    if let Err(e) = std::fs::remove_file(file_path) {
+        warn!("清理临时录音文件失败 {}: {:?}", file_path, e);
+    }
+}
+
+/// 取消当前录音或转写
+///
+/// 转写中：触发取消令牌，由 `stop_and_transcribe` 负责清理与记录日志；
+/// 录音中：直接停止录音并删除临时文件。
+fn cancel_active_transcription(app: &tauri::AppHandle) -> bool {
+    use tauri::Manager;
+
+    let state = app.state::<AppState>();
+    let token = state
+        .transcription_cancel
+        .lock()
+        .ok()
+        .and_then(|guard| guard.clone());
+
+    let cancelled = if let Some(token) = token {
+        token.cancel();
+        true
+    } else if audio::is_recording() {
+        match audio::stop_recording() {
+            Ok(file_path) => remove_temp_recording(&file_path),
+            Err(e) => warn!("取消录音失败: {}", e),
+        }
+        true
+    } else {
+        false
+    };
+
+    if cancelled {
+        let _ = hide_overlay(app.clone());
+        let _ = app.emit("transcription-cancelled-event", ());
+    }
+    cancelled
+}
+
 fn default_global_shortcut() -> &'static str {
     #[cfg(target_os = "windows")]
     {
@@ -142,6 +389,27 @@ fn default_global_shortcut() -> &'static str {
 struct ToggleRecordingEventPayload {
     background: bool,
     action: String,
+    /// 为 false 时只转写不粘贴（本地控制接口可要求直接返回文本）
+    paste: bool,
+}
+
+/// 发出录音开始/停止/切换事件，由前端执行与点击按钮相同的流程
+fn emit_recording_action(app: &tauri::AppHandle, action: &str, paste: bool) {
+    use tauri::Manager;
+
+    let background = app
+        .get_webview_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .map(|is_focused| !is_focused)
+        .unwrap_or(true);
+    let _ = app.emit(
+        "toggle-recording-event",
+        ToggleRecordingEventPayload {
+            background,
+            action: action.to_string(),
+            paste,
+        },
+    );
 }
 
 #[derive(Clone, Serialize)]
@@ -164,6 +432,59 @@ struct EnhancementFallbackEventPayload {
     reason: String,
 }
 
+#[derive(Clone, Serialize)]
+struct PipelineTimingsPayload {
+    log_id: String,
+    timings: timing::StageTimings,
+}
+
+#[derive(Clone, Serialize)]
+struct TranscriptionQueuedPayload {
+    id: String,
+    reason: String,
+}
+
+#[derive(Clone, Serialize)]
+struct QueuedTranscriptionDonePayload {
+    id: String,
+    text: String,
+}
+
+/// 重新转写时覆盖的配置项（未提供的沿用当前配置）
+#[derive(Debug, Clone, Default, Deserialize)]
+struct RetranscribeOverrides {
+    provider: Option<String>,
+    model: Option<String>,
+    base_url: Option<String>,
+    api_key: Option<String>,
+    enhancement_enabled: Option<bool>,
+    enhancement_provider: Option<String>,
+    enhancement_model: Option<String>,
+    enhancement_base_url: Option<String>,
+    enhancement_api_key: Option<String>,
+    enhancement_prompt: Option<String>,
+}
+
+/// 重新转写结果，与原日志并排对比
+#[derive(Clone, Serialize)]
+struct RetranscribeComparison {
+    log_id: String,
+    original_stt_provider: String,
+    original_stt_model: String,
+    original_stt_text: String,
+    original_enhancement_model: Option<String>,
+    original_final_text: String,
+    stt_provider: String,
+    stt_model: String,
+    stt_text: String,
+    enhancement_model: Option<String>,
+    enhancement_text: Option<String>,
+    enhancement_error: Option<String>,
+    final_text: String,
+    stt_duration_ms: u64,
+    enhancement_duration_ms: Option<u64>,
+}
+
 #[derive(Clone, Serialize)]
 struct UpdateAvailablePayload {
     current_version: String,
@@ -317,8 +638,16 @@ fn show_or_create_main_window(app: &tauri::AppHandle) -> Result<(), String> {
 
 /// 开始录音
 #[tauri::command]
-fn start_recording() -> Result<(), String> {
-    audio::start_recording()
+fn start_recording(state: State<AppState>) -> Result<(), String> {
+    let span = diagnostics::session_span();
+    let result = span.in_scope(audio::start_recording);
+    if result.is_ok() {
+        info!(parent: &span, "开始录音");
+        if let Ok(mut guard) = state.recording_span.lock() {
+            *guard = Some(span);
+        }
+    }
+    result
 }
 
 /// 停止录音并返回音频文件路径
@@ -434,10 +763,47 @@ async fn stop_and_transcribe(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<String, String> {
+    use tracing::Instrument;
+
+    let span = state
+        .recording_span
+        .lock()
+        .ok()
+        .and_then(|mut guard| guard.take())
+        .unwrap_or_else(diagnostics::session_span);
+    let result = run_stop_and_transcribe(app, &state)
+        .instrument(span.clone())
+        .await;
+    match &result {
+        Ok(text) => info!(parent: &span, chars = text.chars().count(), "转写完成"),
+        Err(e) => warn!(parent: &span, "转写未完成: {}", e),
+    }
+    if let Ok(text) = &result {
+        if let Ok(mut last) = state.last_result.lock() {
+            *last = Some(control::LastResult {
+                text: text.clone(),
+                finished_at: chrono::Local::now().to_rfc3339(),
+            });
+        }
+    }
+    // 没有等待者时发送失败，忽略即可
+    let _ = state.transcription_done.send(result.clone());
+    result
+}
+
+async fn run_stop_and_transcribe(
+    app: tauri::AppHandle,
+    state: &AppState,
+) -> Result<String, String> {
+    let session = TranscriptionSession::begin(state);
+    let mut timer = timing::PipelineTimer::start();
+
     // 停止录音
-    let file_path = audio::stop_recording()?;
+    let stopped = audio::stop_recording_timed()?;
+    timer.timings.stop_ms = Some(stopped.stop_ms);
+    timer.timings.finalize_ms = Some(stopped.finalize_ms);
+    let file_path = stopped.path;
 
-    // 转录
     let config = state
         .stt_config
         .lock()
@@ -445,130 +811,419 @@ async fn stop_and_transcribe(
         .clone();
     let config = normalize_stt_config(config);
 
-    let transcribe_result = stt::transcribe(&file_path, &config).await;
+    let mut ctx = pipeline::PipelineContext::new(
+        config,
+        file_path.clone(),
+        corrections::load_corrections(&app),
+        timer,
+        session.token.clone(),
+    );
+    ctx.entry.audio_duration_ms = audio::wav_duration_ms(&file_path);
+    let result = pipeline::Pipeline::for_config(&ctx.config)
+        .run(&AppProviders { app: &app }, &mut ctx)
+        .await;
+
+    match result {
+        // 网络、超时、限流或服务端错误时保留录音，移入离线队列等待重试；
+        // 鉴权失败、模型不存在等错误重试也无济于事，直接返回。
+        Err(err) if ctx.transcribe_failed() && ctx.transcribe_error_transient => {
+            match queue::enqueue(&app, &file_path, &ctx.config, &err) {
+                Ok(job) => {
+                    let _ = app.emit(
+                        "transcription-queued-event",
+                        TranscriptionQueuedPayload {
+                            id: job.id,
+                            reason: err.clone(),
+                        },
+                    );
+                    Err(format!("{}（录音已加入重试队列）", err))
+                }
+                Err(e) => {
+                    warn!("加入重试队列失败: {}", e);
+                    remove_temp_recording(&file_path);
+                    Err(err)
+                }
+            }
+        }
+        result => {
+            // 清理系统临时目录中的录音文件，避免持续堆积。
+            remove_temp_recording(&file_path);
+            result
+        }
+    }
+}
+
+/// GUI 中的流水线输出：归档录音、保存易错词命中次数、写日志并等待粘贴
+struct AppProviders<'a> {
+    app: &'a tauri::AppHandle,
+}
 
-    // 清理系统临时目录中的录音文件，避免持续堆积。
-    if let Err(e) = std::fs::remove_file(&file_path) {
-        eprintln!("清理临时录音文件失败 {}: {:?}", file_path, e);
+impl pipeline::Providers for AppProviders<'_> {
+    fn report(&self, report: &pipeline::StageReport) {
+        if report.stage == pipeline::StageKind::Enhance
+            && report.status == pipeline::StageStatus::Fallback
+        {
+            let _ = self.app.emit(
+                "enhancement-fallback-event",
+                EnhancementFallbackEventPayload {
+                    reason: report.error.clone().unwrap_or_default(),
+                },
+            );
+        }
     }
 
-    let raw_text = transcribe_result?;
-    let mut correction_store = corrections::load_corrections(&app);
+    fn output(&self, ctx: &mut pipeline::PipelineContext) {
+        if ctx.transcribed() {
+            ctx.entry.audio_file =
+                archive_recording_if_enabled(self.app, &ctx.config, Path::new(&ctx.audio_path));
+        }
+        if ctx.corrections_dirty {
+            let _ = corrections::save_corrections(self.app, &ctx.corrections);
+        }
+        finish_pipeline_log(self.app, &ctx.config, ctx.entry.clone(), ctx.timer.finish());
+    }
+}
 
-    // 第一次替换：STT 后、Enhancement 前
-    let first_pass = corrections::apply_corrections(&raw_text, &correction_store);
-    let corrected_text = first_pass.text.clone();
-    let mut correction_hits = first_pass.hits.clone();
-    let mut correction_store_dirty =
-        corrections::increment_hit_counts(&mut correction_store, &first_pass.hits);
-
-    let mut log_entry = logging::TranscribeLogEntry {
-        timestamp: chrono::Local::now().to_rfc3339(),
-        stt_provider: config.provider.clone(),
-        stt_model: config.model.clone(),
-        stt_text: raw_text.clone(),
-        pre_correction_text: Some(raw_text.clone()),
-        post_correction_text: Some(corrected_text.clone()),
-        correction_hits: if correction_hits.is_empty() {
-            None
-        } else {
-            Some(correction_hits.clone())
-        },
-        enhancement_enabled: config.enhancement_enabled,
-        enhancement_provider: None,
-        enhancement_model: None,
-        enhancement_text: None,
-        enhancement_status: None,
-        enhancement_error: None,
-        enhancement_duration_ms: None,
-        final_text: corrected_text.clone(),
-    };
+/// 在当前配置上叠加重新转写的覆盖项
+///
+/// 切换服务商但未提供 Key 时清空 Key，回退到对应服务商的环境变量。
+fn apply_retranscribe_overrides(base: &SttConfig, overrides: RetranscribeOverrides) -> SttConfig {
+    let mut config = base.clone();
+
+    if let Some(provider) = overrides.provider {
+        let provider = stt::normalize_provider(&provider);
+        if provider != config.provider {
+            config.provider = provider;
+            config.base_url = String::new();
+            config.model = String::new();
+            config.api_key = String::new();
+        }
+    }
+    if let Some(model) = overrides.model {
+        config.model = model;
+    }
+    if let Some(base_url) = overrides.base_url {
+        config.base_url = base_url;
+    }
+    if let Some(api_key) = overrides.api_key {
+        config.api_key = api_key;
+    }
 
-    if !config.enhancement_enabled {
-        if correction_store_dirty {
-            let _ = corrections::save_corrections(&app, &correction_store);
+    if let Some(enabled) = overrides.enhancement_enabled {
+        config.enhancement_enabled = enabled;
+    }
+    if let Some(provider) = overrides.enhancement_provider {
+        let provider = stt::normalize_provider(&provider);
+        if provider != config.enhancement_provider {
+            config.enhancement_provider = provider;
+            config.enhancement_base_url = String::new();
+            config.enhancement_model = String::new();
+            config.enhancement_api_key = String::new();
         }
-        logging::append_log(&app, log_entry);
-        return Ok(corrected_text);
+    }
+    if let Some(model) = overrides.enhancement_model {
+        config.enhancement_model = model;
+    }
+    if let Some(base_url) = overrides.enhancement_base_url {
+        config.enhancement_base_url = base_url;
+    }
+    if let Some(api_key) = overrides.enhancement_api_key {
+        config.enhancement_api_key = api_key;
+    }
+    if let Some(prompt) = overrides.enhancement_prompt {
+        config.enhancement_prompt = prompt;
     }
 
-    log_entry.enhancement_provider = Some(config.enhancement_provider.clone());
-    log_entry.enhancement_model = Some(config.enhancement_model.clone());
+    normalize_stt_config(config)
+}
 
-    let enhancement_started = std::time::Instant::now();
-    let enhance_result = tokio::time::timeout(
-        std::time::Duration::from_secs(stt::ENHANCEMENT_REQUEST_TIMEOUT_SECS),
-        stt::enhance_text(&corrected_text, &config),
-    )
-    .await;
-
-    let after_enhance = match enhance_result {
-        Ok(Ok(enhanced_text)) => {
-            log_entry.enhancement_text = Some(enhanced_text.clone());
-            log_entry.enhancement_status = Some("success".to_string());
-            log_entry.enhancement_duration_ms =
-                Some(enhancement_started.elapsed().as_millis() as u64);
-            enhanced_text
+/// 重试离线队列中的一条录音，成功后移出队列并通知用户
+async fn retry_queued_transcription_job(
+    app: &tauri::AppHandle,
+    id: &str,
+) -> Result<String, String> {
+    use tauri::Manager;
+
+    let state = app.state::<AppState>();
+    let _queue_guard = state.queue_lock.lock().await;
+
+    let mut job = queue::load_job(app, id)?;
+    let audio_path = queue::job_audio_path(app, &job)?;
+    let config = state
+        .stt_config
+        .lock()
+        .map_err(|e| format!("获取配置失败: {:?}", e))?
+        .clone();
+    let config = normalize_stt_config(config);
+
+    let mut ctx = pipeline::PipelineContext::new(
+        config,
+        audio_path.to_string_lossy().to_string(),
+        corrections::load_corrections(app),
+        timing::PipelineTimer::start(),
+        CancellationToken::new(),
+    );
+    ctx.entry.audio_duration_ms = audio::wav_duration_ms(&ctx.audio_path);
+    let result = pipeline::Pipeline::for_config(&ctx.config)
+        .run(&AppProviders { app }, &mut ctx)
+        .await;
+    let text = match result {
+        Err(err) if ctx.transcribe_failed() => {
+            queue::record_failure(app, &mut job, &err, ctx.transcribe_error_transient);
+            return Err(err);
         }
-        Ok(Err(err)) => {
-            log_entry.enhancement_status = Some("failed".to_string());
-            log_entry.enhancement_error = Some(err.clone());
-            log_entry.enhancement_duration_ms =
-                Some(enhancement_started.elapsed().as_millis() as u64);
-            log_entry.enhancement_text = None;
-
-            eprintln!("LLM enhancement 失败，回退原始文本: {}", err);
-            let _ = app.emit(
-                "enhancement-fallback-event",
-                EnhancementFallbackEventPayload {
-                    reason: err.clone(),
-                },
-            );
-            corrected_text.clone()
+        result => result?,
+    };
+    if let Err(e) = queue::remove_job(app, &job.id) {
+        warn!("移除已完成的队列任务失败 {}: {}", job.id, e);
+    }
+
+    let _ = app.emit(
+        "queued-transcription-done-event",
+        QueuedTranscriptionDonePayload {
+            id: job.id.clone(),
+            text: text.clone(),
+        },
+    );
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("AItoType: 离线录音已转写")
+        .body(text.clone())
+        .show()
+    {
+        warn!("发送通知失败: {}", e);
+    }
+
+    Ok(text)
+}
+
+/// 后台轮询离线队列：网络可达时按退避间隔自动重试，
+/// 超过次数上限或遇到非暂时性错误的任务只能手动重试
+async fn run_queue_worker(app: tauri::AppHandle) {
+    use tauri::Manager;
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(QUEUE_WORKER_INTERVAL_SECS)).await;
+
+        let now = chrono::Local::now();
+        let due_jobs: Vec<queue::QueuedJob> = queue::list_jobs(&app)
+            .into_iter()
+            .filter(|job| queue::is_due(job, now))
+            .collect();
+        if due_jobs.is_empty() {
+            continue;
         }
-        Err(_) => {
-            let reason = format!(
-                "LLM 润色超时（{} 秒）",
-                stt::ENHANCEMENT_REQUEST_TIMEOUT_SECS
-            );
-            log_entry.enhancement_status = Some("timeout".to_string());
-            log_entry.enhancement_error = Some(reason.clone());
-            log_entry.enhancement_duration_ms =
-                Some(enhancement_started.elapsed().as_millis() as u64);
-            log_entry.enhancement_text = None;
-
-            eprintln!("LLM enhancement 超时，回退原始文本: {}", reason);
-            let _ = app.emit(
-                "enhancement-fallback-event",
-                EnhancementFallbackEventPayload {
-                    reason: reason.clone(),
-                },
-            );
-            corrected_text.clone()
+
+        let config = match app.state::<AppState>().stt_config.lock() {
+            Ok(guard) => normalize_stt_config(guard.clone()),
+            Err(_) => continue,
+        };
+        if !stt::is_reachable(&config).await {
+            continue;
         }
-    };
 
-    // 第二次替换：Enhancement 后兜底
-    let second_pass = corrections::apply_corrections(&after_enhance, &correction_store);
-    if !second_pass.hits.is_empty() {
-        correction_hits.extend(second_pass.hits.clone());
+        for job in due_jobs {
+            if let Err(e) = retry_queued_transcription_job(&app, &job.id).await {
+                warn!("离线队列重试失败 {}: {}", job.id, e);
+            }
+        }
     }
-    correction_store_dirty |= corrections::increment_hit_counts(&mut correction_store, &second_pass.hits);
+}
 
-    log_entry.post_correction_text = Some(second_pass.text.clone());
-    log_entry.correction_hits = if correction_hits.is_empty() {
-        None
-    } else {
-        Some(correction_hits)
-    };
-    log_entry.final_text = second_pass.text.clone();
+/// 每天按保留策略清理一次转写日志
+async fn run_log_retention_worker(app: tauri::AppHandle) {
+    use tauri::Manager;
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(LOG_RETENTION_INTERVAL_SECS)).await;
 
-    if correction_store_dirty {
-        let _ = corrections::save_corrections(&app, &correction_store);
+        let config = match app.state::<AppState>().stt_config.lock() {
+            Ok(guard) => guard.clone(),
+            Err(_) => continue,
+        };
+        enforce_log_retention(&app, &config);
     }
+}
+
+/// 按日期范围导出转写记录（Markdown / CSV / 纯文本）
+#[tauri::command]
+fn export_history(
+    app: tauri::AppHandle,
+    options: export::ExportOptions,
+) -> Result<export::ExportResult, String> {
+    export::export_history(&app, &options)
+}
+
+/// 汇总日期范围内的使用统计
+#[tauri::command]
+fn get_stats(
+    app: tauri::AppHandle,
+    range: Option<stats::StatsRange>,
+) -> Result<stats::UsageStats, String> {
+    stats::get_stats(&app, &range.unwrap_or_default())
+}
+
+/// 清空全部转写日志的结果
+#[derive(Clone, Serialize)]
+struct WipeLogsResult {
+    removed_entries: usize,
+    removed_files: usize,
+}
+
+/// 清空全部转写日志：历史数据库与日志目录下的所有文件
+#[tauri::command]
+fn wipe_all_logs(app: tauri::AppHandle) -> Result<WipeLogsResult, String> {
+    use tauri::Manager;
 
-    logging::append_log(&app, log_entry);
-    Ok(second_pass.text)
+    let removed_entries = match app.try_state::<db::Store>() {
+        Some(store) => store.wipe()?,
+        None => 0,
+    };
+    let removed_files = logging::wipe_log_dir(&app)?;
+    Ok(WipeLogsResult {
+        removed_entries,
+        removed_files,
+    })
+}
+
+/// 列出离线队列中的待重试录音
+#[tauri::command]
+fn list_queued_transcriptions(app: tauri::AppHandle) -> Vec<queue::QueuedJob> {
+    queue::list_jobs(&app)
+}
+
+/// 手动重试离线队列中的一条录音
+#[tauri::command]
+async fn retry_queued_transcription(app: tauri::AppHandle, id: String) -> Result<String, String> {
+    retry_queued_transcription_job(&app, &id).await
+}
+
+/// 删除离线队列中的一条录音
+#[tauri::command]
+fn delete_queued_transcription(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    queue::remove_job(&app, &id)
+}
+
+/// 用其他模型/提示词重新转写一条历史录音，返回与原结果的对比
+///
+/// 仅用于评估，不写入日志，也不累计易错词命中次数。
+#[tauri::command]
+async fn retranscribe(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    log_id: String,
+    overrides: Option<RetranscribeOverrides>,
+) -> Result<RetranscribeComparison, String> {
+    let original = logging::find_entry(&app, &log_id)?;
+    let audio_file = original
+        .audio_file
+        .clone()
+        .ok_or_else(|| "该条记录没有归档录音（需开启保留录音）".to_string())?;
+
+    let base = state
+        .stt_config
+        .lock()
+        .map_err(|e| format!("获取配置失败: {:?}", e))?
+        .clone();
+    let config = apply_retranscribe_overrides(&base, overrides.unwrap_or_default());
+
+    let wav_path = archive::restore_to_temp_wav(&app, &audio_file)?;
+    let mut ctx = pipeline::PipelineContext::new(
+        config,
+        wav_path.to_string_lossy().to_string(),
+        corrections::load_corrections(&app),
+        timing::PipelineTimer::start(),
+        CancellationToken::new(),
+    );
+    let result = pipeline::Pipeline::new(EVALUATION_STAGES)
+        .run(&EvaluationProviders, &mut ctx)
+        .await;
+    remove_temp_recording(&ctx.audio_path);
+    let final_text = result?;
+    let stt_duration_ms = ctx
+        .report(pipeline::StageKind::Transcribe)
+        .map(|report| report.duration_ms)
+        .unwrap_or_default();
+
+    Ok(RetranscribeComparison {
+        log_id: original.log_id().to_string(),
+        original_stt_provider: original.stt_provider,
+        original_stt_model: original.stt_model,
+        original_stt_text: original.stt_text,
+        original_enhancement_model: original.enhancement_model,
+        original_final_text: original.final_text,
+        stt_provider: ctx.config.provider.clone(),
+        stt_model: ctx.config.model.clone(),
+        stt_text: ctx.entry.stt_text,
+        enhancement_model: ctx.entry.enhancement_model,
+        enhancement_text: ctx.entry.enhancement_text,
+        enhancement_error: ctx.entry.enhancement_error,
+        final_text,
+        stt_duration_ms,
+        enhancement_duration_ms: ctx.entry.enhancement_duration_ms,
+    })
+}
+
+/// 重新转写只比较文本，不做后处理与输出
+const EVALUATION_STAGES: &[pipeline::StageKind] = &[
+    pipeline::StageKind::Transcribe,
+    pipeline::StageKind::Itn,
+    pipeline::StageKind::Correct,
+    pipeline::StageKind::Enhance,
+    pipeline::StageKind::Correct,
+];
+
+/// 评估用：不写日志，也不保存易错词命中次数
+struct EvaluationProviders;
+
+impl pipeline::Providers for EvaluationProviders {
+    fn output(&self, _ctx: &mut pipeline::PipelineContext) {}
+}
+
+/// 分页搜索历史记录（跨天）
+#[tauri::command]
+fn list_history(
+    app: tauri::AppHandle,
+    query: Option<history::HistoryQuery>,
+) -> Result<history::HistoryPage, String> {
+    history::search(&app, &query.unwrap_or_default())
+}
+
+/// 置顶或取消置顶一条历史记录
+#[tauri::command]
+fn set_history_pinned(app: tauri::AppHandle, log_id: String, pinned: bool) -> Result<(), String> {
+    history::set_pinned(&app, &log_id, pinned)
+}
+
+/// 删除一条历史记录
+#[tauri::command]
+fn delete_history_entry(app: tauri::AppHandle, log_id: String) -> Result<(), String> {
+    history::delete_entry(&app, &log_id)
+}
+
+/// 按当前保留策略立即清理归档录音
+#[tauri::command]
+fn prune_audio_archive(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<archive::PruneResult, String> {
+    let config = state
+        .stt_config
+        .lock()
+        .map_err(|e| format!("获取配置失败: {:?}", e))?
+        .clone();
+    archive::prune(
+        &app,
+        config.audio_archive_max_age_days,
+        config.audio_archive_max_total_mb,
+    )
+}
+
+/// 取消当前录音或正在进行的转写/润色
+#[tauri::command]
+fn cancel_transcription(app: tauri::AppHandle) -> bool {
+    cancel_active_transcription(&app)
 }
 
 /// 模拟键盘输入
@@ -577,10 +1232,65 @@ fn type_text(text: String) -> Result<(), String> {
     keyboard::type_text(&text)
 }
 
+/// 读取最近的诊断日志（用于附在问题反馈中）
+#[tauri::command]
+fn get_diagnostic_logs(app: tauri::AppHandle, lines: Option<usize>) -> Result<Vec<String>, String> {
+    let lines = lines
+        .unwrap_or(diagnostics::DEFAULT_LOG_LINES)
+        .clamp(1, diagnostics::MAX_LOG_LINES);
+    diagnostics::recent_lines(&app, lines)
+}
+
+/// 生成诊断包（脱敏配置、设备、权限、诊断日志、最近转写记录与版本信息）
+#[tauri::command]
+fn create_diagnostics_bundle(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    options: Option<bundle::BundleOptions>,
+) -> Result<bundle::BundleResult, String> {
+    let config = state
+        .stt_config
+        .lock()
+        .map(|c| c.clone())
+        .map_err(|e| format!("获取配置失败: {:?}", e))?;
+    bundle::create_bundle(
+        &app,
+        &config,
+        check_accessibility_permissions(),
+        &options.unwrap_or_default(),
+    )
+}
+
 /// 粘贴文字
+///
+/// 若粘贴的是最近一次听写结果，补记输出耗时到该条日志。
 #[tauri::command]
-fn paste_text(text: String) -> Result<(), String> {
-    keyboard::paste_text(&text)
+fn paste_text(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    text: String,
+) -> Result<(), String> {
+    let output_started = std::time::Instant::now();
+    keyboard::paste_text(&text)?;
+    let output_ms = timing::elapsed_ms(output_started);
+
+    let pending = state
+        .pending_output
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take_if(|pending| pending.text == text));
+    if let Some(mut pending) = pending {
+        pending.timings.output_ms = Some(output_ms);
+        logging::update_timings(&app, &pending.log_id, &pending.timings);
+        let _ = app.emit(
+            "pipeline-timings-event",
+            PipelineTimingsPayload {
+                log_id: pending.log_id,
+                timings: pending.timings,
+            },
+        );
+    }
+    Ok(())
 }
 
 /// 复制文字到剪贴板
@@ -590,15 +1300,57 @@ fn copy_to_clipboard(text: String) -> Result<(), String> {
 }
 
 /// 获取当前 STT 配置
+///
+/// API Key 以掩码返回，前端原样提交时保持不变。
 #[tauri::command]
 fn get_stt_config(state: State<AppState>) -> Result<SttConfig, String> {
     state
         .stt_config
         .lock()
-        .map(|c| normalize_stt_config(c.clone()))
+        .map(|c| secrets::masked(&normalize_stt_config(c.clone())))
         .map_err(|e| format!("获取配置失败: {:?}", e))
 }
 
+/// 取走启动时的配置重置提示（配置文件损坏被备份并重置时非空）
+#[tauri::command]
+fn take_config_reset_notice(state: State<AppState>) -> Option<config::ConfigResetPayload> {
+    state
+        .config_reset_notice
+        .lock()
+        .ok()
+        .and_then(|mut notice| notice.take())
+}
+
+/// 读取管理员策略锁定的配置字段（设置界面据此禁用对应控件）
+#[tauri::command]
+fn get_config_policy() -> policy::PolicyInfo {
+    policy::current().info()
+}
+
+/// 本地控制接口信息（地址、令牌），供设置界面展示
+#[tauri::command]
+fn get_control_api_info(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+) -> Result<control::ControlInfo, String> {
+    let config = state
+        .stt_config
+        .lock()
+        .map(|c| c.clone())
+        .map_err(|e| format!("获取配置失败: {:?}", e))?;
+    control::info(&app, &config)
+}
+
+/// 重新生成本地控制接口令牌
+#[tauri::command]
+fn regenerate_control_token(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+) -> Result<control::ControlInfo, String> {
+    control::regenerate_token(&app)?;
+    get_control_api_info(app, state)
+}
+
 /// 保存 STT 配置
 #[tauri::command]
 fn save_stt_config(
@@ -606,35 +1358,285 @@ fn save_stt_config(
     config: SttConfig,
     state: State<AppState>,
 ) -> Result<(), String> {
-    let normalized = normalize_stt_config(config);
+    redaction::validate(&config.redaction)?;
+    postprocess::validate(&config.post_process)?;
+    pipeline::validate_stages(&config.pipeline_stages)?;
+    let mut config = config;
 
     // 更新内存状态
-    {
+    let normalized = {
         let mut current = state
             .stt_config
             .lock()
             .map_err(|e| format!("获取配置失败: {:?}", e))?;
+        secrets::unmask(&mut config, &current)?;
+        let normalized = normalize_stt_config(config);
         *current = normalized.clone();
+        normalized
+    };
+    diagnostics::set_level(&normalized.diagnostic_log_level);
+
+    persist_active_config(&app, &active_profile_name(&state), &normalized)?;
+
+    // 保留策略可能变严格，立即生效
+    enforce_log_retention(&app, &normalized);
+    control::apply(&app, &normalized);
+
+    Ok(())
+}
+
+/// 持久化当前配置：API Key 写入密钥存储，其余写入 config.json 与当前方案
+fn persist_active_config(
+    app: &tauri::AppHandle,
+    profile: &str,
+    config: &SttConfig,
+) -> Result<(), String> {
+    // API Key 写入系统密钥库（或加密文件），config.json 不保存明文
+    secrets::save_config_keys(app, profile, config)?;
+
+    // 持久化到磁盘（原子写入）
+    if let Err(e) = config::save(app, &secrets::stripped(config)) {
+        error!("保存配置失败: {}", e);
+        return Err(format!("保存配置失败: {}", e));
     }
 
-    // 持久化到磁盘
+    let mut store = profiles::load_store(app);
+    store.profiles.insert(profile.to_string(), config.clone());
+    store.active = profile.to_string();
+    profiles::save_store(app, &store)
+}
+
+/// 列出配置方案
+#[tauri::command]
+fn list_profiles(app: tauri::AppHandle) -> profiles::ProfileList {
+    profiles::load_store(&app).list()
+}
+
+/// 以当前配置为模板新建配置方案（不切换）
+#[tauri::command]
+fn create_profile(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    name: String,
+) -> Result<profiles::ProfileList, String> {
+    let current = state
+        .stt_config
+        .lock()
+        .map(|c| c.clone())
+        .map_err(|e| format!("获取配置失败: {:?}", e))?;
+    let mut store = profiles::load_store(&app);
+    let name = store.create(&name, &current)?;
+    secrets::save_config_keys(&app, &name, &current)?;
+    profiles::save_store(&app, &store)?;
+    refresh_tray_menu(&app);
+    Ok(store.list())
+}
+
+/// 重命名配置方案
+#[tauri::command]
+fn rename_profile(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    old_name: String,
+    new_name: String,
+) -> Result<profiles::ProfileList, String> {
+    let mut store = profiles::load_store(&app);
+    let new_name = store.rename(&old_name, &new_name)?;
+    if new_name != old_name {
+        secrets::rename_profile_keys(&app, &old_name, &new_name)?;
+    }
+    profiles::save_store(&app, &store)?;
+    if let Ok(mut active) = state.active_profile.lock() {
+        *active = store.active.clone();
+    }
+    refresh_tray_menu(&app);
+    Ok(store.list())
+}
+
+/// 删除配置方案（不能删除当前方案）
+#[tauri::command]
+fn delete_profile(app: tauri::AppHandle, name: String) -> Result<profiles::ProfileList, String> {
+    let mut store = profiles::load_store(&app);
+    store.delete(&name)?;
+    profiles::save_store(&app, &store)?;
+    if let Err(e) = secrets::delete_profile_keys(&app, &name) {
+        warn!("删除配置方案 {} 的 API Key 失败: {}", name, e);
+    }
+    refresh_tray_menu(&app);
+    Ok(store.list())
+}
+
+/// 切换到指定配置方案
+#[tauri::command]
+fn activate_profile(app: tauri::AppHandle, name: String) -> Result<profiles::ProfileList, String> {
+    switch_profile(&app, &name)?;
+    Ok(profiles::load_store(&app).list())
+}
+
+fn switch_profile(app: &tauri::AppHandle, name: &str) -> Result<(), String> {
     use tauri::Manager;
-    if let Ok(path) = app.path().app_config_dir() {
-        let config_path = path.join("config.json");
-        // 确保目录存在
-        if let Some(parent) = config_path.parent() {
-            let _ = std::fs::create_dir_all(parent);
+
+    let state = app.state::<AppState>();
+    let store = profiles::load_store(app);
+    let mut config = store
+        .profiles
+        .get(name)
+        .cloned()
+        .ok_or_else(|| format!("未找到配置方案: {}", name))?;
+    secrets::load_config_keys(app, name, &mut config);
+    let normalized = normalize_stt_config(config);
+
+    {
+        let mut current = state
+            .stt_config
+            .lock()
+            .map_err(|e| format!("获取配置失败: {:?}", e))?;
+        *current = normalized.clone();
+    }
+    if let Ok(mut active) = state.active_profile.lock() {
+        *active = name.to_string();
+    }
+    diagnostics::set_level(&normalized.diagnostic_log_level);
+    persist_active_config(app, name, &normalized)?;
+    enforce_log_retention(app, &normalized);
+    control::apply(app, &normalized);
+
+    info!("已切换配置方案: {}", name);
+    let _ = app.emit(profiles::PROFILE_CHANGED_EVENT, name.to_string());
+    refresh_tray_menu(app);
+    Ok(())
+}
+
+/// 导出设置（配置、快捷键、易错词、其他配置方案），API Key 默认不导出
+#[tauri::command]
+fn export_settings(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    options: Option<settings::ExportSettingsOptions>,
+) -> Result<settings::ExportSettingsResult, String> {
+    let config = state
+        .stt_config
+        .lock()
+        .map(|c| c.clone())
+        .map_err(|e| format!("获取配置失败: {:?}", e))?;
+    settings::export_settings(&app, &config, &options.unwrap_or_default())
+}
+
+/// 导入设置：先校验并返回差异预览，`apply` 为 true 时才真正应用
+///
+/// 快捷键保存在前端，由前端根据返回的预览自行应用。
+#[tauri::command]
+fn import_settings(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    content: String,
+    apply: Option<bool>,
+) -> Result<settings::ImportPreview, String> {
+    let (bundle, parsed) = settings::parse_bundle(&content)?;
+    let current = state
+        .stt_config
+        .lock()
+        .map(|c| c.clone())
+        .map_err(|e| format!("获取配置失败: {:?}", e))?;
+    let mut correction_store = corrections::load_corrections(&app);
+    let mut profile_store = profiles::load_store(&app);
+    let mut preview = settings::preview(
+        &bundle,
+        &parsed,
+        &current,
+        &correction_store,
+        &profile_store,
+    )?;
+    if !apply.unwrap_or(false) {
+        return Ok(preview);
+    }
+
+    corrections::merge_store(&mut correction_store, &parsed.corrections)?;
+    corrections::save_corrections(&app, &correction_store)?;
+
+    let active = active_profile_name(&state);
+    let defaults = SttConfig::default();
+    for (name, profile) in &parsed.profiles {
+        if *name != active {
+            let existing = profile_store.profiles.get(name).unwrap_or(&defaults);
+            let profile = settings::keep_local_settings(profile, existing);
+            profile_store.profiles.insert(name.clone(), profile);
         }
+    }
+    profiles::save_store(&app, &profile_store)?;
+
+    let normalized = normalize_stt_config(settings::resolve_config(&parsed.config, &current));
+    {
+        let mut config = state
+            .stt_config
+            .lock()
+            .map_err(|e| format!("获取配置失败: {:?}", e))?;
+        *config = normalized.clone();
+    }
+    diagnostics::set_level(&normalized.diagnostic_log_level);
+    persist_active_config(&app, &active, &normalized)?;
+    enforce_log_retention(&app, &normalized);
+    control::apply(&app, &normalized);
+    refresh_tray_menu(&app);
+
+    info!(
+        "已导入设置：{} 项配置变更，新增 {} 个易错词变体",
+        preview.config_changes.len(),
+        preview.corrections.added
+    );
+    preview.applied = true;
+    Ok(preview)
+}
 
-        if let Ok(json) = serde_json::to_string_pretty(&normalized) {
-            if let Err(e) = std::fs::write(&config_path, json) {
-                eprintln!("保存配置失败: {:?}", e);
-                return Err(format!("保存配置失败: {}", e));
+const TRAY_ID: &str = "main";
+const TRAY_PROFILE_PREFIX: &str = "profile:";
+
+/// 托盘菜单：显示窗口 / 配置方案子菜单 / 退出
+fn build_tray_menu(app: &tauri::AppHandle) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+    use tauri::Manager;
+
+    let store = profiles::load_store(app);
+    let active = active_profile_name(&app.state::<AppState>());
+    let profile_items = store
+        .profiles
+        .keys()
+        .map(|name| {
+            CheckMenuItem::with_id(
+                app,
+                format!("{}{}", TRAY_PROFILE_PREFIX, name),
+                name,
+                true,
+                *name == active,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let profile_refs: Vec<&dyn IsMenuItem<tauri::Wry>> = profile_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<tauri::Wry>)
+        .collect();
+
+    let show_i = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
+    let profiles_i = Submenu::with_id_and_items(app, "profiles", "Profiles", true, &profile_refs)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let quit_i = MenuItem::with_id(app, "quit", "Quit AItoType", true, None::<&str>)?;
+    Menu::with_items(app, &[&show_i, &profiles_i, &separator, &quit_i])
+}
+
+/// 方案增删改或切换后重建托盘菜单
+fn refresh_tray_menu(app: &tauri::AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_tray_menu(app) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                warn!("更新托盘菜单失败: {}", e);
             }
         }
+        Err(e) => warn!("构建托盘菜单失败: {}", e),
     }
-
-    Ok(())
 }
 
 /// 测试 API 连接
@@ -647,42 +1649,45 @@ async fn test_connection(
         .lock()
         .map_err(|e| format!("获取配置失败: {:?}", e))?
         .clone();
-    let config = normalize_stt_config(config);
+    Ok(run_connection_test(&normalize_stt_config(config)).await)
+}
+
+async fn run_connection_test(config: &SttConfig) -> ConnectionTestResultPayload {
     let provider = config.provider.clone();
     let model = config.model.clone();
     let started_at = std::time::Instant::now();
 
-    if !has_resolved_api_key(&config) {
+    if !has_resolved_api_key(config) {
         let env_key = stt::env_key_for_provider(&config.provider);
-        return Ok(ConnectionTestResultPayload {
+        return ConnectionTestResultPayload {
             success: false,
             provider,
             model,
             message: format!("API Key 不能为空（可通过环境变量 {} 提供）", env_key),
             error_type: Some(stt::CONNECTION_ERROR_AUTH_FAILED.to_string()),
             latency_ms: started_at.elapsed().as_millis() as u64,
-        });
+        };
     }
 
-    match stt::test_connection(&config).await {
-        Ok(_) => Ok(ConnectionTestResultPayload {
+    match stt::test_connection(config).await {
+        Ok(_) => ConnectionTestResultPayload {
             success: true,
             provider,
             model,
             message: "连接测试成功".to_string(),
             error_type: None,
             latency_ms: started_at.elapsed().as_millis() as u64,
-        }),
+        },
         Err(err) => {
             let (error_type, message) = parse_typed_error(&err);
-            Ok(ConnectionTestResultPayload {
+            ConnectionTestResultPayload {
                 success: false,
                 provider,
                 model,
                 message,
                 error_type: Some(error_type),
                 latency_ms: started_at.elapsed().as_millis() as u64,
-            })
+            }
         }
     }
 }
@@ -699,14 +1704,95 @@ fn update_shortcut(
     }
 
     let shortcut = shortcut.trim().to_string();
+    let cancel_shortcut = state
+        .cancel_shortcut
+        .lock()
+        .map(|s| s.clone())
+        .unwrap_or_default();
+    if same_shortcut(&shortcut, &cancel_shortcut) {
+        return Err(format!("快捷键 {} 已用作取消转写快捷键", shortcut));
+    }
+
+    let mut current = state
+        .shortcut
+        .lock()
+        .map_err(|e| format!("获取快捷键失败: {:?}", e))?;
 
     // 忽略 unregister 错误（可能本来就没有）
     let _ = app.global_shortcut().unregister_all();
+    current.clear();
+
+    // unregister_all 会一并移除取消快捷键，这里重新注册
+    if !cancel_shortcut.is_empty() {
+        if let Err(e) = app.global_shortcut().register(cancel_shortcut.as_str()) {
+            warn!("re-register cancel shortcut ({}) failed: {}", cancel_shortcut, e);
+        }
+    }
+
+    if !shortcut.is_empty() {
+        app.global_shortcut()
+            .register(shortcut.as_str())
+            .map_err(|e| e.to_string())?;
+        *current = shortcut;
+    }
+    Ok(())
+}
+
+/// 两个快捷键是否相同：能解析时按按键比较（忽略修饰键顺序与大小写），空字符串不算相同
+fn same_shortcut(a: &str, b: &str) -> bool {
+    use tauri_plugin_global_shortcut::Shortcut;
+
+    let (a, b) = (a.trim(), b.trim());
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+    match (a.parse::<Shortcut>(), b.parse::<Shortcut>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.eq_ignore_ascii_case(b),
+    }
+}
+
+/// 更新取消转写快捷键（传空字符串表示禁用）
+#[tauri::command]
+fn update_cancel_shortcut(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    shortcut: String,
+) -> Result<(), String> {
+    use tauri_plugin_global_shortcut::Shortcut;
+
+    if !state.shortcut_plugin_ready.load(Ordering::Acquire) {
+        return Err("global shortcut plugin is not ready".to_string());
+    }
+
+    let shortcut = shortcut.trim().to_string();
+    let main_shortcut = state
+        .shortcut
+        .lock()
+        .map(|s| s.clone())
+        .unwrap_or_default();
+    if same_shortcut(&shortcut, &main_shortcut) {
+        return Err(format!("取消快捷键不能与录音快捷键 {} 相同", main_shortcut));
+    }
+
+    let mut current = state
+        .cancel_shortcut
+        .lock()
+        .map_err(|e| format!("获取快捷键失败: {:?}", e))?;
+
+    if !current.is_empty() {
+        let _ = app.global_shortcut().unregister(current.as_str());
+        current.clear();
+    }
 
     if !shortcut.is_empty() {
+        shortcut
+            .parse::<Shortcut>()
+            .map_err(|e| format!("快捷键格式无效: {}", e))?;
         app.global_shortcut()
             .register(shortcut.as_str())
             .map_err(|e| e.to_string())?;
+        *current = shortcut;
     }
     Ok(())
 }
@@ -766,7 +1852,7 @@ fn show_overlay_status(app: tauri::AppHandle, status: String) -> Result<(), Stri
     let _ = overlay.set_always_on_top(true);
 
     if let Err(e) = overlay.show() {
-        eprintln!("show overlay failed: {:?}", e);
+        warn!("show overlay failed: {:?}", e);
         return Ok(());
     }
 
@@ -776,7 +1862,7 @@ fn show_overlay_status(app: tauri::AppHandle, status: String) -> Result<(), Stri
             status: status.trim().to_lowercase(),
         },
     ) {
-        eprintln!("emit overlay-status failed: {:?}", e);
+        warn!("emit overlay-status failed: {:?}", e);
     }
 
     Ok(())
@@ -862,7 +1948,7 @@ fn hide_overlay(app: tauri::AppHandle) -> Result<(), String> {
         .ok_or_else(|| "overlay window not found".to_string())?;
 
     if let Err(e) = overlay.hide() {
-        eprintln!("hide overlay failed: {:?}", e);
+        warn!("hide overlay failed: {:?}", e);
     }
     Ok(())
 }
@@ -884,6 +1970,11 @@ fn show_main_window(app: &tauri::AppHandle) {
     }
 }
 
+/// 命令行工具入口（`aitotype-cli`）
+pub fn run_cli() -> std::process::ExitCode {
+    cli::run()
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -902,34 +1993,130 @@ pub fn run() {
             }
         })
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             load_env_files();
+            diagnostics::init(app.handle(), diagnostics::DEFAULT_LOG_LEVEL);
             // --- 加载持久化配置 ---
             use tauri::Manager;
-            if let Ok(path) = app.path().app_config_dir() {
-                let config_path = path.join("config.json");
-                if config_path.exists() {
-                    if let Ok(content) = std::fs::read_to_string(&config_path) {
-                        if let Ok(saved_config) = serde_json::from_str::<SttConfig>(&content) {
-                            let state = app.state::<AppState>();
-                            if let Ok(mut guard) = state.stt_config.lock() {
-                                *guard = normalize_stt_config(saved_config);
-                                println!("✅ 已加载配置文件: {:?}", config_path);
-                            };
+            let mut profile_store = profiles::load_store(app.handle());
+            let active_profile = if profile_store.active.is_empty() {
+                profiles::DEFAULT_PROFILE.to_string()
+            } else {
+                profile_store.active.clone()
+            };
+            match config::load(app.handle()) {
+                config::LoadOutcome::Loaded {
+                    config: saved_config,
+                    migrated,
+                } => {
+                    let mut saved_config = *saved_config;
+                    // 旧版明文 API Key 迁移到安全存储后从 config.json 中移除
+                    let keys_migrated = secrets::migrate_plaintext_keys(
+                        app.handle(),
+                        &active_profile,
+                        &saved_config,
+                    );
+                    if migrated || keys_migrated {
+                        let to_save = if keys_migrated {
+                            secrets::stripped(&saved_config)
+                        } else {
+                            saved_config.clone()
+                        };
+                        if let Err(e) = config::save(app.handle(), &to_save) {
+                            warn!("写回迁移后的配置失败: {}", e);
                         }
                     }
+                    secrets::load_config_keys(app.handle(), &active_profile, &mut saved_config);
+                    let state = app.state::<AppState>();
+                    if let Ok(mut guard) = state.stt_config.lock() {
+                        *guard = normalize_stt_config(saved_config);
+                        diagnostics::set_level(&guard.diagnostic_log_level);
+                    };
+                }
+                config::LoadOutcome::Reset(notice) => {
+                    let _ = app.emit(config::CONFIG_RESET_EVENT, notice.clone());
+                    let state = app.state::<AppState>();
+                    if let Ok(mut guard) = state.config_reset_notice.lock() {
+                        *guard = Some(notice);
+                    };
+                }
+                config::LoadOutcome::Missing => {}
+            }
+
+            // --- 配置方案：config.json 即当前方案的配置 ---
+            {
+                let state = app.state::<AppState>();
+                let current = state.stt_config.lock().map(|c| c.clone());
+                if let Ok(current) = current {
+                    profile_store.active = active_profile.clone();
+                    profile_store
+                        .profiles
+                        .insert(active_profile.clone(), secrets::stripped(&current));
+                    if let Err(e) = profiles::save_store(app.handle(), &profile_store) {
+                        warn!("保存配置方案失败: {}", e);
+                    }
+                }
+                if let Ok(mut guard) = state.active_profile.lock() {
+                    *guard = profile_store.active.clone();
+                };
+            }
+
+            // --- 旧版整数组日志一次性迁移为 JSONL ---
+            logging::migrate_legacy_logs(app.handle());
+
+            // --- 打开历史数据库并导入日志文件 ---
+            match db::Store::open(app.handle()) {
+                Ok(store) => {
+                    match store.import_log_files(app.handle()) {
+                        Ok(count) if count > 0 => info!("已导入 {} 条历史日志", count),
+                        Ok(_) => {}
+                        Err(e) => warn!("导入历史日志失败: {}", e),
+                    }
+                    app.manage(store);
+                }
+                Err(e) => error!("打开历史数据库失败: {}", e),
+            }
+
+            // --- 按保留策略清理转写日志 ---
+            {
+                let state = app.state::<AppState>();
+                let config = state.stt_config.lock().map(|c| c.clone());
+                if let Ok(config) = config {
+                    enforce_log_retention(app.handle(), &config);
+                }
+            }
+
+            // --- 按配置启动本地控制接口 ---
+            {
+                let state = app.state::<AppState>();
+                let config = state.stt_config.lock().map(|c| c.clone());
+                if let Ok(config) = config {
+                    control::apply(app.handle(), &config);
+                }
+            }
+
+            // --- 按保留策略清理归档录音 ---
+            {
+                let state = app.state::<AppState>();
+                let config = state.stt_config.lock().map(|c| c.clone());
+                if let Ok(config) = config {
+                    if let Err(e) = archive::prune(
+                        app.handle(),
+                        config.audio_archive_max_age_days,
+                        config.audio_archive_max_total_mb,
+                    ) {
+                        warn!("清理归档录音失败: {}", e);
+                    }
                 }
             }
 
             // --- System Tray ---
-            use tauri::menu::{Menu, MenuItem};
             use tauri::tray::TrayIconBuilder;
 
-            let quit_i = MenuItem::with_id(app, "quit", "Quit AItoType", true, None::<&str>)?;
-            let show_i = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
+            let menu = build_tray_menu(app.handle())?;
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(app.default_window_icon().unwrap().clone())
                 .icon_as_template(false)
                 .menu(&menu)
@@ -938,10 +2125,17 @@ pub fn run() {
                     "quit" => app.exit(0),
                     "show" => {
                         if let Err(e) = show_or_create_main_window(app) {
-                            eprintln!("show main window failed: {}", e);
+                            warn!("show main window failed: {}", e);
+                        }
+                    }
+                    id => {
+                        if let Some(name) = id.strip_prefix(TRAY_PROFILE_PREFIX) {
+                            if let Err(e) = switch_profile(app, name) {
+                                warn!("切换配置方案失败: {}", e);
+                                refresh_tray_menu(app);
+                            }
                         }
                     }
-                    _ => {}
                 })
                 .build(app)?;
 
@@ -956,11 +2150,25 @@ pub fn run() {
             // --- Global Shortcut ---
             #[cfg(desktop)]
             {
-                use tauri_plugin_global_shortcut::ShortcutState;
+                use tauri_plugin_global_shortcut::{Shortcut, ShortcutState};
 
                 let plugin_result = app.handle().plugin(
                     tauri_plugin_global_shortcut::Builder::new()
-                        .with_handler(|app, _shortcut, event| {
+                        .with_handler(|app, shortcut, event| {
+                            let is_cancel_shortcut = app
+                                .state::<AppState>()
+                                .cancel_shortcut
+                                .lock()
+                                .ok()
+                                .and_then(|s| s.parse::<Shortcut>().ok())
+                                .is_some_and(|cancel| &cancel == shortcut);
+                            if is_cancel_shortcut {
+                                if event.state == ShortcutState::Pressed {
+                                    cancel_active_transcription(app);
+                                }
+                                return;
+                            }
+
                             let record_mode = app
                                 .state::<AppState>()
                                 .stt_config
@@ -968,35 +2176,17 @@ pub fn run() {
                                 .map(|c| c.record_mode.clone())
                                 .unwrap_or_else(|_| "toggle".to_string());
 
-                            let background = app
-                                .get_webview_window("main")
-                                .and_then(|w| w.is_focused().ok())
-                                .map(|is_focused| !is_focused)
-                                .unwrap_or(true);
-
                             match record_mode.as_str() {
                                 "hold" => {
                                     let action = match event.state {
                                         ShortcutState::Pressed => "start",
                                         ShortcutState::Released => "stop",
                                     };
-                                    let _ = app.emit(
-                                        "toggle-recording-event",
-                                        ToggleRecordingEventPayload {
-                                            background,
-                                            action: action.to_string(),
-                                        },
-                                    );
+                                    emit_recording_action(app, action, true);
                                 }
                                 _ => {
                                     if event.state == ShortcutState::Pressed {
-                                        let _ = app.emit(
-                                            "toggle-recording-event",
-                                            ToggleRecordingEventPayload {
-                                                background,
-                                                action: "toggle".to_string(),
-                                            },
-                                        );
+                                        emit_recording_action(app, "toggle", true);
                                     }
                                 }
                             }
@@ -1008,16 +2198,21 @@ pub fn run() {
                     Ok(_) => {
                         let state = app.state::<AppState>();
                         state.shortcut_plugin_ready.store(true, Ordering::Release);
-                        if let Err(e) = app.global_shortcut().register(default_global_shortcut()) {
-                            eprintln!(
+                        match app.global_shortcut().register(default_global_shortcut()) {
+                            Ok(_) => {
+                                if let Ok(mut current) = state.shortcut.lock() {
+                                    *current = default_global_shortcut().to_string();
+                                }
+                            }
+                            Err(e) => warn!(
                                 "register default global shortcut ({}) failed: {}",
                                 default_global_shortcut(),
                                 e
-                            );
+                            ),
                         }
                     }
                     Err(e) => {
-                        eprintln!("init global shortcut plugin failed: {}", e);
+                        error!("init global shortcut plugin failed: {}", e);
                     }
                 }
             }
@@ -1027,6 +2222,16 @@ pub fn run() {
                 check_for_updates(app_handle).await;
             });
 
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                run_queue_worker(app_handle).await;
+            });
+
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                run_log_retention_worker(app_handle).await;
+            });
+
             Ok(())
         })
         .manage(AppState::default())
@@ -1047,13 +2252,39 @@ pub fn run() {
             apply_corrections_preview,
             transcribe_audio,
             stop_and_transcribe,
+            cancel_transcription,
+            list_queued_transcriptions,
+            retry_queued_transcription,
+            delete_queued_transcription,
+            prune_audio_archive,
+            retranscribe,
+            list_history,
+            set_history_pinned,
+            delete_history_entry,
+            wipe_all_logs,
+            export_history,
+            get_stats,
+            get_diagnostic_logs,
+            create_diagnostics_bundle,
             type_text,
             paste_text,
             copy_to_clipboard,
             get_stt_config,
+            take_config_reset_notice,
+            get_config_policy,
+            get_control_api_info,
+            regenerate_control_token,
+            list_profiles,
+            create_profile,
+            rename_profile,
+            delete_profile,
+            activate_profile,
+            export_settings,
+            import_settings,
             save_stt_config,
             test_connection,
             update_shortcut,
+            update_cancel_shortcut,
             is_shortcut_ready,
             show_overlay_status,
             hide_overlay,
@@ -1068,7 +2299,7 @@ pub fn run() {
             #[cfg(target_os = "macos")]
             if matches!(event, tauri::RunEvent::Reopen { .. }) {
                 if let Err(e) = show_or_create_main_window(app) {
-                    eprintln!("reopen main window failed: {}", e);
+                    warn!("reopen main window failed: {}", e);
                 }
             }
         });
@@ -1,16 +1,50 @@
 //! 转写日志模块
 //!
-//! 记录每次转写与润色结果，按天写入 JSON 文件。
+//! 记录每次转写与润色结果。正常写入 SQLite（见 `db` 模块）；
+//! 数据库不可用时按天追加写入 JSON Lines 文件（每行一条，fsync），下次启动时导入数据库并删除文件。
+//! 旧版本的整数组 JSON 文件仍可读取，并在启动时一次性迁移为 JSONL。
 
 use crate::corrections::CorrectionHit;
-use chrono::Local;
+use crate::db::Store;
+use crate::diagnostics;
+use crate::timing::StageTimings;
+use chrono::{Local, NaiveDate};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
+use tracing::warn;
+
+lazy_static::lazy_static! {
+    /// 串行化日志文件的追加与迁移
+    static ref LOG_WRITE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+const LOG_FILE_PREFIX: &str = "transcribe_";
+const LOG_FILE_SUFFIX: &str = ".jsonl";
+/// 旧版整数组格式的日志文件后缀
+const LEGACY_LOG_FILE_SUFFIX: &str = ".json";
+/// 压缩后的非当天日志文件后缀
+const COMPRESSED_LOG_FILE_SUFFIX: &str = ".jsonl.gz";
+
+/// 日志目录中的一个按天日志文件
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    pub date: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
 
 /// 单条转写日志
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TranscribeLogEntry {
+    /// 日志 ID（旧日志没有该字段，以 timestamp 代替）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
     pub timestamp: String,
     pub stt_provider: String,
     pub stt_model: String,
@@ -35,6 +69,62 @@ pub struct TranscribeLogEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enhancement_duration_ms: Option<u64>,
     pub final_text: String,
+    /// 归档录音文件名（仅开启录音归档时存在）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_file: Option<String>,
+    /// 录音时长（毫秒），用于导出字幕等需要时间信息的场景
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_duration_ms: Option<u64>,
+    /// 流程状态: success / cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// 是否在历史记录中置顶
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// 各阶段耗时
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<StageTimings>,
+    /// 转写时使用的配置方案
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+impl TranscribeLogEntry {
+    /// 日志 ID，旧日志回退为 timestamp
+    pub fn log_id(&self) -> &str {
+        if self.id.is_empty() {
+            &self.timestamp
+        } else {
+            &self.id
+        }
+    }
+}
+
+/// 生成新的日志 ID（以日期开头，便于定位日志文件）
+pub fn new_entry_id() -> String {
+    Local::now().format("%Y%m%d%H%M%S%6f").to_string()
+}
+
+/// 从日志 ID 推断所在日期（YYYY-MM-DD）
+fn date_from_log_id(log_id: &str) -> Option<String> {
+    let digits: String = log_id
+        .chars()
+        .filter(|ch| ch.is_ascii_digit())
+        .take(8)
+        .collect();
+    if digits.len() < 8 {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}",
+        &digits[0..4],
+        &digits[4..6],
+        &digits[6..8]
+    ))
+}
+
+fn day_log_path(dir: &Path, date: &str) -> PathBuf {
+    dir.join(format!("{}{}{}", LOG_FILE_PREFIX, date, LOG_FILE_SUFFIX))
 }
 
 /// 获取日志目录
@@ -50,44 +140,457 @@ fn log_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
 fn today_log_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     let dir = log_dir(app)?;
     let today = Local::now().format("%Y-%m-%d").to_string();
-    Ok(dir.join(format!("transcribe_{}.json", today)))
+    Ok(day_log_path(&dir, &today))
 }
 
 /// 追加一条日志
 ///
-/// 读取现有 JSON 数组后追加写回；失败不影响主流程。
+/// 优先写入数据库，失败时回退为 JSONL 文件；失败不影响主流程。
 pub fn append_log(app: &tauri::AppHandle, entry: TranscribeLogEntry) {
+    use tauri::Manager;
+
+    if let Some(store) = app.try_state::<Store>() {
+        match store.insert_entry(&entry) {
+            Ok(_) => return,
+            Err(e) => warn!("append_log: 写入数据库失败，回退为日志文件: {}", e),
+        }
+    }
+    append_to_day_file(app, &entry);
+}
+
+/// 更新已写入记录的各阶段耗时（输出阶段在记录写入之后才完成）
+///
+/// 仅更新数据库；回退写入的日志文件为追加格式，不做原地修改。
+pub fn update_timings(app: &tauri::AppHandle, log_id: &str, timings: &StageTimings) {
+    use tauri::Manager;
+
+    if let Some(store) = app.try_state::<Store>() {
+        if let Err(e) = store.set_timings(log_id, timings) {
+            warn!("update_timings: {}", e);
+        }
+    }
+}
+
+/// 以追加模式写入一行 JSON 并 fsync
+fn append_to_day_file(app: &tauri::AppHandle, entry: &TranscribeLogEntry) {
     let path = match today_log_path(app) {
         Ok(path) => path,
         Err(e) => {
-            eprintln!("append_log: {}", e);
+            warn!("append_log: {}", e);
             return;
         }
     };
 
     if let Some(parent) = path.parent() {
         if let Err(e) = std::fs::create_dir_all(parent) {
-            eprintln!("append_log: create_dir_all failed {:?}: {:?}", parent, e);
+            warn!("append_log: create_dir_all failed {:?}: {:?}", parent, e);
+            return;
+        }
+    }
+
+    let line = match serde_json::to_string(entry) {
+        Ok(line) => line,
+        Err(e) => {
+            warn!("append_log: serialize failed: {:?}", e);
+            return;
+        }
+    };
+
+    let _guard = LOG_WRITE_LOCK.lock();
+    if let Err(e) = append_line(&path, &line) {
+        warn!("append_log: write failed {:?}: {:?}", path, e);
+    }
+}
+
+/// 追加一行 JSONL；上次写入中断导致文件末尾没有换行时先补一个换行，
+/// 避免新记录和写了一半的行粘在一起被一并丢弃
+fn append_line(path: &Path, line: &str) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+
+    let mut content = String::with_capacity(line.len() + 2);
+    let len = file.metadata()?.len();
+    if len > 0 {
+        let mut last = [0u8; 1];
+        file.seek(SeekFrom::Start(len - 1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            content.push('\n');
+        }
+    }
+    content.push_str(line);
+    content.push('\n');
+
+    file.write_all(content.as_bytes())?;
+    file.sync_data()
+}
+
+/// 解析 JSONL 内容，跳过无法解析的行（如崩溃时写了一半的最后一行）
+fn parse_jsonl(content: &str) -> Vec<TranscribeLogEntry> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("read_entries: skip malformed line: {:?}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// 解析旧版整数组格式
+fn parse_legacy_array(content: &str) -> Result<Vec<TranscribeLogEntry>, String> {
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(content).map_err(|e| format!("解析旧日志失败: {:?}", e))
+}
+
+fn is_legacy_log_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+fn is_compressed_log_file(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(COMPRESSED_LOG_FILE_SUFFIX)
+}
+
+fn read_log_content(path: &Path) -> std::io::Result<String> {
+    if !is_compressed_log_file(path) {
+        return std::fs::read_to_string(path);
+    }
+    let compressed = std::fs::read(path)?;
+    let mut content = String::new();
+    GzDecoder::new(compressed.as_slice()).read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// 读取某个日志文件中的全部日志（兼容旧版整数组格式与压缩文件）
+pub fn read_entries(path: &Path) -> Vec<TranscribeLogEntry> {
+    let content = match read_log_content(path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+
+    if is_legacy_log_file(path) {
+        parse_legacy_array(&content).unwrap_or_else(|e| {
+            warn!("read_entries: {:?}: {}", path, e);
+            Vec::new()
+        })
+    } else {
+        parse_jsonl(&content)
+    }
+}
+
+fn serialize_jsonl(entries: &[TranscribeLogEntry]) -> Result<String, String> {
+    let mut content = String::new();
+    for entry in entries {
+        let line = serde_json::to_string(entry).map_err(|e| format!("序列化日志失败: {:?}", e))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+/// 整体重写一个 JSONL 文件：先写临时文件并 fsync，再原子替换
+fn write_entries(path: &Path, entries: &[TranscribeLogEntry]) -> Result<(), String> {
+    let content = serialize_jsonl(entries)?;
+    let tmp_path = path.with_extension("jsonl.tmp");
+    let mut file = std::fs::File::create(&tmp_path)
+        .map_err(|e| format!("写入日志失败 {:?}: {:?}", tmp_path, e))?;
+    file.write_all(content.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("写入日志失败 {:?}: {:?}", tmp_path, e))?;
+    std::fs::rename(&tmp_path, path).map_err(|e| format!("替换日志文件失败 {:?}: {:?}", path, e))
+}
+
+/// 把旧版整数组日志一次性迁移为 JSONL
+///
+/// 同一天已有 JSONL 时旧日志排在前面；无法解析的旧文件保持原样，不做删除。
+pub fn migrate_legacy_logs(app: &tauri::AppHandle) {
+    let dir = match log_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            warn!("migrate_legacy_logs: {}", e);
             return;
         }
+    };
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    let _guard = LOG_WRITE_LOCK.lock();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let date = match name
+            .strip_prefix(LOG_FILE_PREFIX)
+            .and_then(|rest| rest.strip_suffix(LEGACY_LOG_FILE_SUFFIX))
+        {
+            Some(date) => date.to_string(),
+            None => continue,
+        };
+
+        let legacy_path = entry.path();
+        let legacy_entries = match std::fs::read_to_string(&legacy_path)
+            .map_err(|e| format!("读取旧日志失败: {:?}", e))
+            .and_then(|content| parse_legacy_array(&content))
+        {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("migrate_legacy_logs: {:?}: {}", legacy_path, e);
+                continue;
+            }
+        };
+
+        let target = day_log_path(&dir, &date);
+        let mut merged = legacy_entries;
+        merged.extend(read_entries(&target));
+        if let Err(e) = write_entries(&target, &merged) {
+            warn!("migrate_legacy_logs: {}", e);
+            continue;
+        }
+        if let Err(e) = std::fs::remove_file(&legacy_path) {
+            warn!(
+                "migrate_legacy_logs: remove {:?} failed: {:?}",
+                legacy_path, e
+            );
+        }
     }
+}
+
+/// 按天列出日志文件（含未迁移的旧文件与压缩文件），日期降序
+pub fn list_log_files(app: &tauri::AppHandle) -> Result<Vec<LogFile>, String> {
+    let dir = log_dir(app)?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut files: Vec<LogFile> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let rest = name.strip_prefix(LOG_FILE_PREFIX)?;
+            let date = rest
+                .strip_suffix(LOG_FILE_SUFFIX)
+                .or_else(|| rest.strip_suffix(COMPRESSED_LOG_FILE_SUFFIX))
+                .or_else(|| rest.strip_suffix(LEGACY_LOG_FILE_SUFFIX))?
+                .to_string();
+            Some(LogFile {
+                date,
+                size: entry.metadata().map(|meta| meta.len()).unwrap_or(0),
+                path: entry.path(),
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| b.date.cmp(&a.date));
+    Ok(files)
+}
+
+/// 计算需要删除的日志文件：先按天数淘汰，再从最旧开始删除直到总大小不超过上限
+///
+/// 当天的文件不会被删除；`max_age_days` / `max_total_mb` 为 0 表示不限制。
+fn select_files_to_remove(
+    mut files: Vec<LogFile>,
+    today: NaiveDate,
+    max_age_days: u32,
+    max_total_mb: u32,
+) -> Vec<LogFile> {
+    files.sort_by(|a, b| a.date.cmp(&b.date));
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let cutoff = (today - chrono::Duration::days(i64::from(max_age_days)))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let mut removed = Vec::new();
+    let mut kept = Vec::new();
+    for file in files {
+        if max_age_days > 0 && file.date < cutoff && file.date != today_str {
+            removed.push(file);
+        } else {
+            kept.push(file);
+        }
+    }
+
+    if max_total_mb > 0 {
+        let max_bytes = u64::from(max_total_mb) * 1024 * 1024;
+        let mut total: u64 = kept.iter().map(|file| file.size).sum();
+        for file in kept {
+            if total <= max_bytes || file.date == today_str {
+                break;
+            }
+            total = total.saturating_sub(file.size);
+            removed.push(file);
+        }
+    }
+
+    removed
+}
+
+/// 先用 0 覆盖文件内容再删除，降低被恢复的可能（SSD/写时复制文件系统上无法保证）
+pub fn secure_remove_file(path: &Path) -> std::io::Result<()> {
+    if let Ok(metadata) = std::fs::metadata(path) {
+        if metadata.is_file() {
+            let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+            let zeros = vec![0u8; 64 * 1024];
+            let mut remaining = metadata.len();
+            while remaining > 0 {
+                let chunk = remaining.min(zeros.len() as u64) as usize;
+                file.write_all(&zeros[..chunk])?;
+                remaining -= chunk as u64;
+            }
+            file.sync_all()?;
+        }
+    }
+    std::fs::remove_file(path)
+}
+
+/// 安全删除已导入数据库的日志文件；导入后又有追加写入（大小变化）时保留，下次启动再导入
+pub fn remove_imported_file(path: &Path, imported_size: u64) {
+    let _guard = LOG_WRITE_LOCK.lock();
+    let size = std::fs::metadata(path).map(|meta| meta.len()).ok();
+    if size != Some(imported_size) {
+        return;
+    }
+    if let Err(e) = secure_remove_file(path) {
+        warn!("remove_imported_file: remove {:?} failed: {:?}", path, e);
+    }
+}
+
+/// 按保留策略删除日志文件，返回删除的文件数
+pub fn prune_log_files(
+    app: &tauri::AppHandle,
+    max_age_days: u32,
+    max_total_mb: u32,
+) -> Result<usize, String> {
+    let files = list_log_files(app)?;
+    let _guard = LOG_WRITE_LOCK.lock();
 
-    let mut entries: Vec<TranscribeLogEntry> = std::fs::read_to_string(&path)
-        .ok()
-        .and_then(|content| serde_json::from_str(&content).ok())
-        .unwrap_or_default();
-    entries.push(entry);
+    let mut removed = 0;
+    let today = Local::now().date_naive();
+    for file in select_files_to_remove(files, today, max_age_days, max_total_mb) {
+        match secure_remove_file(&file.path) {
+            Ok(_) => removed += 1,
+            Err(e) => warn!("prune_log_files: remove {:?} failed: {:?}", file.path, e),
+        }
+    }
+    Ok(removed)
+}
+
+/// gzip 压缩非当天的 JSONL 日志文件，返回 (原文件名, 压缩后文件名, 压缩后大小)
+pub fn compress_old_log_files(
+    app: &tauri::AppHandle,
+) -> Result<Vec<(String, String, u64)>, String> {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let files = list_log_files(app)?;
+    let _guard = LOG_WRITE_LOCK.lock();
 
-    match serde_json::to_string_pretty(&entries) {
-        Ok(json) => {
-            if let Err(e) = std::fs::write(&path, json) {
-                eprintln!("append_log: write failed {:?}: {:?}", path, e);
+    let mut compressed = Vec::new();
+    for file in files {
+        let is_plain_jsonl = file.path.to_string_lossy().ends_with(LOG_FILE_SUFFIX);
+        if !is_plain_jsonl || file.date >= today {
+            continue;
+        }
+
+        let target = file.path.with_extension("jsonl.gz");
+        let result = std::fs::read(&file.path).and_then(|content| {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&content)?;
+            let bytes = encoder.finish()?;
+            let mut output = std::fs::File::create(&target)?;
+            output.write_all(&bytes)?;
+            output.sync_all()?;
+            Ok(bytes.len() as u64)
+        });
+        match result {
+            Ok(size) => {
+                if let Err(e) = secure_remove_file(&file.path) {
+                    warn!(
+                        "compress_old_log_files: remove {:?} failed: {:?}",
+                        file.path, e
+                    );
+                }
+                let file_name = |path: &Path| {
+                    path.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                };
+                compressed.push((file_name(&file.path), file_name(&target), size));
             }
+            Err(e) => warn!("compress_old_log_files: {:?}: {:?}", file.path, e),
         }
+    }
+    Ok(compressed)
+}
+
+/// 安全删除日志目录下的全部内容（含 `diagnostics/` 等子目录），返回删除的文件数
+///
+/// 清空期间暂停诊断日志的文件写入，清空后重新创建日志文件。
+pub fn wipe_log_dir(app: &tauri::AppHandle) -> Result<usize, String> {
+    let dir = log_dir(app)?;
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let _guard = LOG_WRITE_LOCK.lock();
+    Ok(diagnostics::with_file_log_paused(app, || wipe_dir(&dir)))
+}
+
+/// 递归安全删除目录下的文件并移除清空的子目录（目录本身保留）；符号链接只删除链接
+fn wipe_dir(dir: &Path) -> usize {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
         Err(e) => {
-            eprintln!("append_log: serialize failed: {:?}", e);
+            warn!("wipe_log_dir: read {:?} failed: {:?}", dir, e);
+            return 0;
+        }
+    };
+
+    let mut removed = 0;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+        let result = if file_type.is_dir() {
+            removed += wipe_dir(&path);
+            std::fs::remove_dir(&path)
+        } else if file_type.is_symlink() {
+            std::fs::remove_file(&path)
+        } else {
+            secure_remove_file(&path).map(|_| removed += 1)
+        };
+        if let Err(e) = result {
+            warn!("wipe_log_dir: remove {:?} failed: {:?}", path, e);
         }
     }
+    removed
+}
+
+/// 按日志 ID 查找一条日志（数据库优先，其次当天的日志文件）
+pub fn find_entry(app: &tauri::AppHandle, log_id: &str) -> Result<TranscribeLogEntry, String> {
+    use tauri::Manager;
+
+    let log_id = log_id.trim();
+    if let Some(store) = app.try_state::<Store>() {
+        if let Some(entry) = store.find_entry(log_id)? {
+            return Ok(entry);
+        }
+    }
+
+    let date = date_from_log_id(log_id).ok_or_else(|| format!("无效的日志 ID: {}", log_id))?;
+    let path = day_log_path(&log_dir(app)?, &date);
+
+    read_entries(&path)
+        .into_iter()
+        .find(|entry| entry.log_id() == log_id)
+        .ok_or_else(|| format!("未找到日志: {}", log_id))
 }
 
 /// 获取日志目录（给前端展示）
@@ -131,3 +634,127 @@ pub fn open_log_dir(app: &tauri::AppHandle) -> Result<(), String> {
         Err(format!("打开日志目录失败，退出码: {:?}", status.code()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_from_new_and_legacy_ids() {
+        assert_eq!(
+            date_from_log_id("20260305093015123456").as_deref(),
+            Some("2026-03-05")
+        );
+        assert_eq!(
+            date_from_log_id("2026-03-05T09:30:15.123+08:00").as_deref(),
+            Some("2026-03-05")
+        );
+        assert_eq!(date_from_log_id("abc"), None);
+    }
+
+    fn entry(id: &str, final_text: &str) -> TranscribeLogEntry {
+        TranscribeLogEntry {
+            id: id.to_string(),
+            final_text: final_text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn jsonl_round_trip_skips_truncated_line() {
+        let mut content = serialize_jsonl(&[entry("1", "第一条"), entry("2", "second")]).unwrap();
+        content.push_str("{\"id\":\"3\",\"timest");
+
+        let entries = parse_jsonl(&content);
+        let texts: Vec<&str> = entries.iter().map(|e| e.final_text.as_str()).collect();
+        assert_eq!(texts, vec!["第一条", "second"]);
+    }
+
+    #[test]
+    fn append_starts_new_line_after_truncated_line() {
+        let path =
+            std::env::temp_dir().join(format!("aitotype-append-test-{}.jsonl", std::process::id()));
+        let mut content = serialize_jsonl(&[entry("1", "第一条")]).unwrap();
+        content.push_str("{\"id\":\"2\",\"timest");
+        std::fs::write(&path, content).unwrap();
+
+        append_line(&path, &serde_json::to_string(&entry("3", "third")).unwrap()).unwrap();
+        append_line(
+            &path,
+            &serde_json::to_string(&entry("4", "fourth")).unwrap(),
+        )
+        .unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(written.ends_with("}\n"));
+        let entries = parse_jsonl(&written);
+        let texts: Vec<&str> = entries.iter().map(|e| e.final_text.as_str()).collect();
+        assert_eq!(texts, vec!["第一条", "third", "fourth"]);
+    }
+
+    #[test]
+    fn wipe_removes_nested_files() {
+        let dir = std::env::temp_dir().join(format!("aitotype-wipe-test-{}", std::process::id()));
+        let nested = dir.join(diagnostics::DIAGNOSTICS_DIR);
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join("transcribe_2026-03-01.jsonl"), "{}\n").unwrap();
+        std::fs::write(nested.join("aitotype.2026-03-01.log"), "secret line").unwrap();
+
+        assert_eq!(wipe_dir(&dir), 2);
+        let leftovers: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn parses_legacy_array_files() {
+        let legacy = serde_json::to_string_pretty(&vec![entry("", "old")]).unwrap();
+        let entries = parse_legacy_array(&legacy).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].final_text, "old");
+
+        assert!(parse_legacy_array("").unwrap().is_empty());
+        assert!(parse_legacy_array("[{\"timestamp\":").is_err());
+    }
+
+    fn log_file(date: &str, size_mb: u64) -> LogFile {
+        LogFile {
+            date: date.to_string(),
+            path: PathBuf::from(format!("transcribe_{}.jsonl", date)),
+            size: size_mb * 1024 * 1024,
+        }
+    }
+
+    fn dates(files: &[LogFile]) -> Vec<&str> {
+        files.iter().map(|file| file.date.as_str()).collect()
+    }
+
+    #[test]
+    fn removes_files_older_than_max_age() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let files = vec![
+            log_file("2026-03-10", 1),
+            log_file("2026-03-03", 1),
+            log_file("2026-03-02", 1),
+        ];
+        let removed = select_files_to_remove(files, today, 7, 0);
+        assert_eq!(dates(&removed), vec!["2026-03-02"]);
+    }
+
+    #[test]
+    fn size_limit_removes_oldest_but_keeps_today() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let files = vec![
+            log_file("2026-03-10", 4),
+            log_file("2026-03-08", 4),
+            log_file("2026-03-09", 4),
+        ];
+        let removed = select_files_to_remove(files.clone(), today, 0, 5);
+        assert_eq!(dates(&removed), vec!["2026-03-08", "2026-03-09"]);
+
+        let removed = select_files_to_remove(vec![log_file("2026-03-10", 9)], today, 1, 1);
+        assert!(removed.is_empty());
+        assert!(select_files_to_remove(files, today, 0, 0).is_empty());
+    }
+}
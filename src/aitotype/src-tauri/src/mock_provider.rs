@@ -0,0 +1,306 @@
+//! 测试用的本地 Provider 模拟服务
+//!
+//! 监听 127.0.0.1 随机端口，按「方法 + 路径」返回预先编排的响应，
+//! 并记录收到的完整请求（请求头与请求体），用于断言 OpenRouter / SiliconFlow
+//! 请求格式与错误分类。同一路由编排多个响应时依次返回，最后一个重复使用。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 编排的响应
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    pub fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: body.to_string(),
+        }
+    }
+
+    /// OpenRouter chat-completions 成功响应
+    pub fn chat(content: serde_json::Value) -> Self {
+        Self::json(
+            200,
+            serde_json::json!({
+                "choices": [{ "message": { "role": "assistant", "content": content } }]
+            }),
+        )
+    }
+}
+
+/// 收到的请求
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("请求体不是有效的 JSON")
+    }
+
+    /// 取 multipart/form-data 中指定字段的内容
+    pub fn multipart_field(&self, name: &str) -> Option<Vec<u8>> {
+        let content_type = self.header("content-type")?;
+        let boundary = content_type.split("boundary=").nth(1)?.trim_matches('"');
+        let delimiter = format!("--{}", boundary).into_bytes();
+        let disposition = format!("name=\"{}\"", name).into_bytes();
+
+        split_bytes(&self.body, &delimiter)
+            .into_iter()
+            .find_map(|part| {
+                let head_end = find_bytes(part, b"\r\n\r\n")?;
+                let head = &part[..head_end];
+                find_bytes(head, &disposition)?;
+                let content = &part[head_end + 4..];
+                Some(content.strip_suffix(b"\r\n").unwrap_or(content).to_vec())
+            })
+    }
+}
+
+type Routes = HashMap<(String, String), Vec<MockResponse>>;
+
+/// 模拟服务构建器
+#[derive(Default)]
+pub struct MockServerBuilder {
+    routes: Routes,
+}
+
+impl MockServerBuilder {
+    /// 为路由追加一个响应
+    pub fn on(mut self, method: &str, path: &str, response: MockResponse) -> Self {
+        self.routes
+            .entry((method.to_ascii_uppercase(), path.to_string()))
+            .or_default()
+            .push(response);
+        self
+    }
+
+    pub async fn start(self) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("绑定模拟服务端口失败");
+        let port = listener.local_addr().expect("读取模拟服务端口失败").port();
+        let routes = Arc::new(Mutex::new(self.routes));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let task = {
+            let requests = requests.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let routes = routes.clone();
+                    let requests = requests.clone();
+                    tokio::spawn(handle_connection(stream, routes, requests));
+                }
+            })
+        };
+
+        MockServer {
+            port,
+            requests,
+            task,
+        }
+    }
+}
+
+/// 运行中的模拟服务，drop 时停止监听
+pub struct MockServer {
+    port: u16,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder::default()
+    }
+
+    /// 形如 `http://127.0.0.1:端口/v1` 的 Base URL
+    pub fn base_url(&self) -> String {
+        format!("http://127.0.0.1:{}/v1", self.port)
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// 唯一一次请求，请求数不为 1 时 panic
+    pub fn single_request(&self) -> RecordedRequest {
+        let requests = self.requests();
+        assert_eq!(requests.len(), 1, "期望恰好一次请求: {:?}", requests);
+        requests.into_iter().next().unwrap()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    routes: Arc<Mutex<Routes>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+) {
+    let request = match read_request(&mut stream).await {
+        Some(request) => request,
+        None => return,
+    };
+    let response = {
+        let mut routes = routes.lock().unwrap();
+        match routes.get_mut(&(request.method.clone(), request.path.clone())) {
+            Some(queue) if queue.len() > 1 => queue.remove(0),
+            Some(queue) if !queue.is_empty() => queue[0].clone(),
+            _ => MockResponse::json(
+                404,
+                serde_json::json!({ "error": { "message": "no mock route" } }),
+            ),
+        }
+    };
+    requests.lock().unwrap().push(request);
+
+    let head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(response.body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// 读取完整请求，支持 Content-Length 与 chunked 两种请求体
+async fn read_request(stream: &mut TcpStream) -> Option<RecordedRequest> {
+    let mut buffer = Vec::new();
+    let head_end = loop {
+        if let Some(end) = find_bytes(&buffer, b"\r\n\r\n") {
+            break end;
+        }
+        if !read_more(stream, &mut buffer).await {
+            return None;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    };
+
+    let mut rest = buffer.split_off(head_end + 4);
+    let body = if header("transfer-encoding").is_some_and(|value| value.contains("chunked")) {
+        loop {
+            if let Some(body) = decode_chunked(&rest) {
+                break body;
+            }
+            if !read_more(stream, &mut rest).await {
+                return None;
+            }
+        }
+    } else {
+        let length: usize = header("content-length")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+        while rest.len() < length {
+            if !read_more(stream, &mut rest).await {
+                return None;
+            }
+        }
+        rest.truncate(length);
+        rest
+    };
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+async fn read_more(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> bool {
+    let mut chunk = [0u8; 8192];
+    match stream.read(&mut chunk).await {
+        Ok(0) | Err(_) => false,
+        Ok(read) => {
+            buffer.extend_from_slice(&chunk[..read]);
+            true
+        }
+    }
+}
+
+/// 解码完整的 chunked 请求体，数据尚未读完时返回 None
+fn decode_chunked(mut data: &[u8]) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = find_bytes(data, b"\r\n")?;
+        let size_line = std::str::from_utf8(&data[..line_end]).ok()?;
+        let size_hex = size_line.split(';').next()?.trim();
+        let size = usize::from_str_radix(size_hex, 16).ok()?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Some(body);
+        }
+        if data.len() < size + 2 {
+            return None;
+        }
+        body.extend_from_slice(&data[..size]);
+        data = &data[size + 2..];
+    }
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn split_bytes<'a>(data: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    let mut rest = data;
+    while let Some(index) = find_bytes(rest, delimiter) {
+        parts.push(&rest[..index]);
+        rest = &rest[index + delimiter.len()..];
+    }
+    parts.push(rest);
+    parts
+}
@@ -0,0 +1,69 @@
+//! 应用目录模块
+//!
+//! 配置、易错词、密钥、历史数据库等模块通过 `AppPaths` 获取目录，
+//! GUI 使用 Tauri 的路径解析，命令行工具没有 Tauri 运行时，
+//! 用 `HeadlessPaths` 按相同规则解析，两者读写同一份数据。
+
+use std::path::PathBuf;
+
+/// 应用标识（与 tauri.conf.json 中的 identifier 一致）
+pub const APP_IDENTIFIER: &str = "com.aitotype";
+
+pub trait AppPaths {
+    fn app_config_dir(&self) -> Result<PathBuf, String>;
+    fn app_data_dir(&self) -> Result<PathBuf, String>;
+    fn app_log_dir(&self) -> Result<PathBuf, String>;
+}
+
+impl AppPaths for tauri::AppHandle {
+    fn app_config_dir(&self) -> Result<PathBuf, String> {
+        use tauri::Manager;
+
+        self.path()
+            .app_config_dir()
+            .map_err(|e| format!("获取配置目录失败: {:?}", e))
+    }
+
+    fn app_data_dir(&self) -> Result<PathBuf, String> {
+        use tauri::Manager;
+
+        self.path()
+            .app_data_dir()
+            .map_err(|e| format!("获取数据目录失败: {:?}", e))
+    }
+
+    fn app_log_dir(&self) -> Result<PathBuf, String> {
+        use tauri::Manager;
+
+        self.path()
+            .app_log_dir()
+            .map_err(|e| format!("获取日志目录失败: {:?}", e))
+    }
+}
+
+/// 不依赖 Tauri 运行时的目录解析（规则与 Tauri 桌面端一致）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeadlessPaths;
+
+impl AppPaths for HeadlessPaths {
+    fn app_config_dir(&self) -> Result<PathBuf, String> {
+        dirs::config_dir()
+            .map(|dir| dir.join(APP_IDENTIFIER))
+            .ok_or_else(|| "获取配置目录失败".to_string())
+    }
+
+    fn app_data_dir(&self) -> Result<PathBuf, String> {
+        dirs::data_dir()
+            .map(|dir| dir.join(APP_IDENTIFIER))
+            .ok_or_else(|| "获取数据目录失败".to_string())
+    }
+
+    fn app_log_dir(&self) -> Result<PathBuf, String> {
+        let dir = if cfg!(target_os = "macos") {
+            dirs::home_dir().map(|dir| dir.join("Library/Logs").join(APP_IDENTIFIER))
+        } else {
+            dirs::data_local_dir().map(|dir| dir.join(APP_IDENTIFIER).join("logs"))
+        };
+        dir.ok_or_else(|| "获取日志目录失败".to_string())
+    }
+}
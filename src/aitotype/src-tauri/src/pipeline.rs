@@ -0,0 +1,743 @@
+//! 转写流水线模块
+//!
+//! 停止录音后的处理拆成依次执行的阶段（`Stage`）：
+//! 转写 -> 逆文本规范化 -> 易错词替换 -> LLM 润色 -> 再次替换 -> 后处理 -> 输出。
+//! 各阶段把文本、状态与耗时写入共享的 `PipelineContext`；
+//! 调用 STT、LLM、后处理以及写日志都通过 `Providers`，GUI、命令行和测试各自提供实现，
+//! 流水线本身不依赖 `AppHandle`。配置方案可通过 `pipeline_stages` 调整阶段顺序。
+
+use crate::corrections::{self, CorrectionHit, CorrectionStore};
+use crate::itn;
+use crate::logging::{self, TranscribeLogEntry};
+use crate::postprocess::{self, PostProcessConfig, PostProcessInput};
+use crate::redaction;
+use crate::stt::{self, SttConfig, TranscribeError};
+use crate::timing::{self, PipelineTimer, StageTimings};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// 阶段类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageKind {
+    Transcribe,
+    /// 逆文本规范化（口语数字改写为阿拉伯数字）
+    Itn,
+    Correct,
+    Enhance,
+    PostProcess,
+    Output,
+}
+
+/// 默认阶段顺序
+pub const DEFAULT_STAGES: &[StageKind] = &[
+    StageKind::Transcribe,
+    StageKind::Itn,
+    StageKind::Correct,
+    StageKind::Enhance,
+    StageKind::Correct,
+    StageKind::PostProcess,
+    StageKind::Output,
+];
+
+/// 阶段执行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Success,
+    /// 未启用或无需执行
+    Skipped,
+    /// 失败，沿用上一阶段的文本继续
+    Fallback,
+    /// 失败，流水线中止
+    Failed,
+    Cancelled,
+}
+
+/// 单个阶段的状态与耗时
+#[derive(Debug, Clone, Serialize)]
+pub struct StageReport {
+    pub stage: StageKind,
+    pub status: StageStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+pub struct StageOutcome {
+    status: StageStatus,
+    error: Option<String>,
+}
+
+impl StageOutcome {
+    fn success() -> Self {
+        Self::with(StageStatus::Success, None)
+    }
+
+    fn skipped() -> Self {
+        Self::with(StageStatus::Skipped, None)
+    }
+
+    fn fallback(error: String) -> Self {
+        Self::with(StageStatus::Fallback, Some(error))
+    }
+
+    fn failed(error: String) -> Self {
+        Self::with(StageStatus::Failed, Some(error))
+    }
+
+    fn cancelled() -> Self {
+        Self::with(StageStatus::Cancelled, None)
+    }
+
+    fn with(status: StageStatus, error: Option<String>) -> Self {
+        Self { status, error }
+    }
+}
+
+/// 流水线依赖的外部服务，默认调用真实的 STT / LLM / 后处理，测试中可替换
+#[async_trait]
+pub trait Providers: Send + Sync {
+    async fn transcribe(
+        &self,
+        audio_path: &str,
+        config: &SttConfig,
+        timings: &mut StageTimings,
+    ) -> Result<String, TranscribeError> {
+        stt::transcribe_timed(audio_path, config, timings).await
+    }
+
+    async fn enhance(&self, text: &str, config: &SttConfig) -> Result<String, String> {
+        stt::enhance_text(text, config).await
+    }
+
+    async fn post_process(
+        &self,
+        config: &PostProcessConfig,
+        input: &PostProcessInput<'_>,
+    ) -> Result<String, String> {
+        postprocess::run(config, input).await
+    }
+
+    /// 每个阶段结束时调用
+    fn report(&self, _report: &StageReport) {}
+
+    /// 输出阶段以及被取消时调用：写日志、保存易错词命中次数、交给粘贴等
+    fn output(&self, ctx: &mut PipelineContext);
+}
+
+/// 各阶段共享的状态
+pub struct PipelineContext {
+    pub config: SttConfig,
+    pub audio_path: String,
+    /// 当前文本（每个阶段在上一阶段的结果上处理）
+    pub text: String,
+    pub entry: TranscribeLogEntry,
+    pub timer: PipelineTimer,
+    pub corrections: CorrectionStore,
+    /// 易错词命中次数有变化，需要保存
+    pub corrections_dirty: bool,
+    pub correction_hits: Vec<CorrectionHit>,
+    pub reports: Vec<StageReport>,
+    pub cancel: CancellationToken,
+    /// STT 失败是暂时性的（网络、超时、429、5xx），录音可以进入离线队列自动重试
+    pub transcribe_error_transient: bool,
+    /// 上一次易错词替换的结果（之后文本没变时不再重复替换）
+    last_corrected: Option<String>,
+}
+
+impl PipelineContext {
+    pub fn new(
+        config: SttConfig,
+        audio_path: String,
+        corrections: CorrectionStore,
+        timer: PipelineTimer,
+        cancel: CancellationToken,
+    ) -> Self {
+        let entry = TranscribeLogEntry {
+            id: logging::new_entry_id(),
+            timestamp: chrono::Local::now().to_rfc3339(),
+            stt_provider: config.provider.clone(),
+            stt_model: config.model.clone(),
+            enhancement_enabled: config.enhancement_enabled,
+            status: Some(crate::LOG_STATUS_SUCCESS.to_string()),
+            ..Default::default()
+        };
+        Self {
+            config,
+            audio_path,
+            text: String::new(),
+            entry,
+            timer,
+            corrections,
+            corrections_dirty: false,
+            correction_hits: Vec::new(),
+            reports: Vec::new(),
+            cancel,
+            transcribe_error_transient: false,
+            last_corrected: None,
+        }
+    }
+
+    pub fn report(&self, stage: StageKind) -> Option<&StageReport> {
+        self.reports.iter().find(|report| report.stage == stage)
+    }
+
+    /// 已拿到 STT 结果
+    pub fn transcribed(&self) -> bool {
+        self.report(StageKind::Transcribe)
+            .is_some_and(|report| report.status == StageStatus::Success)
+    }
+
+    /// STT 请求失败（录音应进入离线队列）
+    pub fn transcribe_failed(&self) -> bool {
+        self.report(StageKind::Transcribe)
+            .is_some_and(|report| report.status == StageStatus::Failed)
+    }
+}
+
+#[async_trait]
+pub trait Stage: Send + Sync {
+    fn kind(&self) -> StageKind;
+
+    async fn run(&self, ctx: &mut PipelineContext, providers: &dyn Providers) -> StageOutcome;
+}
+
+struct TranscribeStage;
+
+#[async_trait]
+impl Stage for TranscribeStage {
+    fn kind(&self) -> StageKind {
+        StageKind::Transcribe
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext, providers: &dyn Providers) -> StageOutcome {
+        let result = ctx
+            .cancel
+            .run_until_cancelled(providers.transcribe(
+                &ctx.audio_path,
+                &ctx.config,
+                &mut ctx.timer.timings,
+            ))
+            .await;
+        match result {
+            Some(Ok(text)) => {
+                ctx.entry.stt_text = text.clone();
+                ctx.entry.final_text = text.clone();
+                ctx.text = text;
+                StageOutcome::success()
+            }
+            Some(Err(err)) => {
+                ctx.transcribe_error_transient = err.transient;
+                StageOutcome::failed(err.message)
+            }
+            None => StageOutcome::cancelled(),
+        }
+    }
+}
+
+struct ItnStage;
+
+#[async_trait]
+impl Stage for ItnStage {
+    fn kind(&self) -> StageKind {
+        StageKind::Itn
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext, _providers: &dyn Providers) -> StageOutcome {
+        if !ctx.config.itn.enabled {
+            return StageOutcome::skipped();
+        }
+        ctx.text = itn::apply(&ctx.text, &ctx.config.itn);
+        StageOutcome::success()
+    }
+}
+
+struct CorrectStage;
+
+#[async_trait]
+impl Stage for CorrectStage {
+    fn kind(&self) -> StageKind {
+        StageKind::Correct
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext, _providers: &dyn Providers) -> StageOutcome {
+        if ctx.last_corrected.as_deref() == Some(ctx.text.as_str()) {
+            return StageOutcome::skipped();
+        }
+
+        // 第一次替换前的文本已经过 ITN，与 stt_text 可能不同
+        if ctx.entry.pre_correction_text.is_none() {
+            ctx.entry.pre_correction_text = Some(ctx.text.clone());
+        }
+        let started = Instant::now();
+        let result = corrections::apply_corrections(&ctx.text, &ctx.corrections);
+        ctx.corrections_dirty |=
+            corrections::increment_hit_counts(&mut ctx.corrections, &result.hits);
+        ctx.correction_hits.extend(result.hits);
+        ctx.entry.post_correction_text = Some(result.text.clone());
+        ctx.entry.correction_hits =
+            (!ctx.correction_hits.is_empty()).then(|| ctx.correction_hits.clone());
+        ctx.last_corrected = Some(result.text.clone());
+        ctx.text = result.text;
+        timing::add_ms(
+            &mut ctx.timer.timings.corrections_ms,
+            timing::elapsed_ms(started),
+        );
+        StageOutcome::success()
+    }
+}
+
+struct EnhanceStage;
+
+#[async_trait]
+impl Stage for EnhanceStage {
+    fn kind(&self) -> StageKind {
+        StageKind::Enhance
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext, providers: &dyn Providers) -> StageOutcome {
+        if !ctx.config.enhancement_enabled {
+            return StageOutcome::skipped();
+        }
+        ctx.entry.enhancement_provider = Some(ctx.config.enhancement_provider.clone());
+        ctx.entry.enhancement_model = Some(ctx.config.enhancement_model.clone());
+
+        let started = Instant::now();
+        let redacted = redaction::redact_for_enhancement(&ctx.text, &ctx.config.redaction);
+        let result = ctx
+            .cancel
+            .run_until_cancelled(tokio::time::timeout(
+                Duration::from_secs(stt::ENHANCEMENT_REQUEST_TIMEOUT_SECS),
+                enhance_redacted_text(providers, &redacted, &ctx.config),
+            ))
+            .await;
+        let duration_ms = timing::elapsed_ms(started);
+        ctx.timer.timings.enhancement_ms = Some(duration_ms);
+        ctx.entry.enhancement_duration_ms = Some(duration_ms);
+
+        match result {
+            Some(Ok(Ok(enhanced_text))) => {
+                ctx.entry.enhancement_text = Some(enhanced_text.clone());
+                ctx.entry.enhancement_status = Some("success".to_string());
+                ctx.text = enhanced_text;
+                StageOutcome::success()
+            }
+            Some(Ok(Err(err))) => {
+                warn!("LLM enhancement 失败，回退原始文本: {}", err);
+                ctx.entry.enhancement_status = Some("failed".to_string());
+                ctx.entry.enhancement_error = Some(err.clone());
+                StageOutcome::fallback(err)
+            }
+            Some(Err(_)) => {
+                let reason = format!(
+                    "LLM 润色超时（{} 秒）",
+                    stt::ENHANCEMENT_REQUEST_TIMEOUT_SECS
+                );
+                warn!("LLM enhancement 超时，回退原始文本: {}", reason);
+                ctx.entry.enhancement_status = Some("timeout".to_string());
+                ctx.entry.enhancement_error = Some(reason.clone());
+                StageOutcome::fallback(reason)
+            }
+            None => {
+                ctx.entry.enhancement_status = Some(crate::LOG_STATUS_CANCELLED.to_string());
+                StageOutcome::cancelled()
+            }
+        }
+    }
+}
+
+/// 润色脱敏后的文本，并在结果中还原占位符
+///
+/// 润色结果丢失占位符时视为失败，由调用方回退到未润色的文本。
+async fn enhance_redacted_text(
+    providers: &dyn Providers,
+    redacted: &redaction::RedactedText,
+    config: &SttConfig,
+) -> Result<String, String> {
+    if redacted.placeholders.is_empty() {
+        return providers.enhance(&redacted.text, config).await;
+    }
+
+    let mut config = config.clone();
+    config.enhancement_prompt = redaction::with_placeholder_hint(&config.enhancement_prompt);
+    let enhanced = providers.enhance(&redacted.text, &config).await?;
+    redaction::restore(&enhanced, &redacted.placeholders)
+        .ok_or_else(|| "润色结果丢失了脱敏占位符".to_string())
+}
+
+struct PostProcessStage;
+
+#[async_trait]
+impl Stage for PostProcessStage {
+    fn kind(&self) -> StageKind {
+        StageKind::PostProcess
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext, providers: &dyn Providers) -> StageOutcome {
+        if !ctx.config.post_process.is_enabled() {
+            return StageOutcome::skipped();
+        }
+
+        let started = Instant::now();
+        let input = PostProcessInput {
+            text: &ctx.text,
+            raw_text: &ctx.entry.stt_text,
+            timestamp: &ctx.entry.timestamp,
+        };
+        let result = ctx
+            .cancel
+            .run_until_cancelled(providers.post_process(&ctx.config.post_process, &input))
+            .await;
+        ctx.timer.timings.post_process_ms = Some(timing::elapsed_ms(started));
+
+        match result {
+            Some(Ok(text)) => {
+                ctx.text = text;
+                StageOutcome::success()
+            }
+            Some(Err(err)) => {
+                warn!("后处理失败，保留原文本: {}", err);
+                StageOutcome::fallback(err)
+            }
+            None => StageOutcome::cancelled(),
+        }
+    }
+}
+
+struct OutputStage;
+
+#[async_trait]
+impl Stage for OutputStage {
+    fn kind(&self) -> StageKind {
+        StageKind::Output
+    }
+
+    async fn run(&self, ctx: &mut PipelineContext, providers: &dyn Providers) -> StageOutcome {
+        ctx.entry.final_text = ctx.text.clone();
+        providers.output(ctx);
+        StageOutcome::success()
+    }
+}
+
+fn stage_for(kind: StageKind) -> Box<dyn Stage> {
+    match kind {
+        StageKind::Transcribe => Box::new(TranscribeStage),
+        StageKind::Itn => Box::new(ItnStage),
+        StageKind::Correct => Box::new(CorrectStage),
+        StageKind::Enhance => Box::new(EnhanceStage),
+        StageKind::PostProcess => Box::new(PostProcessStage),
+        StageKind::Output => Box::new(OutputStage),
+    }
+}
+
+/// 校验配置中的阶段顺序：空表示默认顺序；否则必须以转写开始、以输出结束，润色与后处理最多一次
+pub fn validate_stages(stages: &[StageKind]) -> Result<(), String> {
+    if stages.is_empty() {
+        return Ok(());
+    }
+    if stages.first() != Some(&StageKind::Transcribe) || stages.last() != Some(&StageKind::Output) {
+        return Err("流水线必须以 transcribe 开始、以 output 结束".to_string());
+    }
+    for kind in [
+        StageKind::Transcribe,
+        StageKind::Enhance,
+        StageKind::PostProcess,
+        StageKind::Output,
+    ] {
+        if stages.iter().filter(|stage| **stage == kind).count() > 1 {
+            return Err(format!("流水线阶段 {:?} 只能出现一次", kind));
+        }
+    }
+    Ok(())
+}
+
+pub struct Pipeline {
+    stages: Vec<Box<dyn Stage>>,
+}
+
+impl Pipeline {
+    pub fn new(kinds: &[StageKind]) -> Self {
+        Self {
+            stages: kinds.iter().copied().map(stage_for).collect(),
+        }
+    }
+
+    /// 按配置方案中的阶段顺序构建（未设置或无效时使用默认顺序）
+    pub fn for_config(config: &SttConfig) -> Self {
+        match validate_stages(&config.pipeline_stages) {
+            Ok(()) if !config.pipeline_stages.is_empty() => Self::new(&config.pipeline_stages),
+            Ok(()) => Self::new(DEFAULT_STAGES),
+            Err(e) => {
+                warn!("流水线配置无效，使用默认顺序: {}", e);
+                Self::new(DEFAULT_STAGES)
+            }
+        }
+    }
+
+    /// 依次执行各阶段，返回最终文本
+    ///
+    /// 转写失败时直接返回错误（不输出）；被取消时以取消状态输出日志后返回取消错误。
+    pub async fn run(
+        &self,
+        providers: &dyn Providers,
+        ctx: &mut PipelineContext,
+    ) -> Result<String, String> {
+        for stage in &self.stages {
+            let started = Instant::now();
+            let outcome = stage.run(ctx, providers).await;
+            let report = StageReport {
+                stage: stage.kind(),
+                status: outcome.status,
+                error: outcome.error,
+                duration_ms: timing::elapsed_ms(started),
+            };
+            debug!("流水线阶段 {:?}: {:?}", report.stage, report.status);
+            providers.report(&report);
+            let error = report.error.clone();
+            ctx.reports.push(report);
+
+            match outcome.status {
+                StageStatus::Failed => return Err(error.unwrap_or_default()),
+                StageStatus::Cancelled => {
+                    ctx.entry.final_text = String::new();
+                    ctx.entry.status = Some(crate::LOG_STATUS_CANCELLED.to_string());
+                    providers.output(ctx);
+                    return Err(crate::TRANSCRIPTION_CANCELLED_ERROR.to_string());
+                }
+                _ => {}
+            }
+        }
+        Ok(ctx.text.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct FakeProviders {
+        stt_text: String,
+        enhanced: Option<Result<String, String>>,
+        post_processed: Option<Result<String, String>>,
+        outputs: Mutex<Vec<TranscribeLogEntry>>,
+    }
+
+    #[async_trait]
+    impl Providers for FakeProviders {
+        async fn transcribe(
+            &self,
+            _audio_path: &str,
+            _config: &SttConfig,
+            _timings: &mut StageTimings,
+        ) -> Result<String, TranscribeError> {
+            Ok(self.stt_text.clone())
+        }
+
+        async fn enhance(&self, _text: &str, _config: &SttConfig) -> Result<String, String> {
+            self.enhanced
+                .clone()
+                .unwrap_or_else(|| Err("no enhancer".to_string()))
+        }
+
+        async fn post_process(
+            &self,
+            _config: &PostProcessConfig,
+            input: &PostProcessInput<'_>,
+        ) -> Result<String, String> {
+            self.post_processed
+                .clone()
+                .unwrap_or_else(|| Ok(input.text.to_string()))
+        }
+
+        fn output(&self, ctx: &mut PipelineContext) {
+            self.outputs.lock().unwrap().push(ctx.entry.clone());
+        }
+    }
+
+    fn context(config: SttConfig) -> PipelineContext {
+        let mut store = CorrectionStore::default();
+        corrections::add_correction(&mut store, "gemeni", "Gemini").unwrap();
+        PipelineContext::new(
+            config,
+            "fake.wav".to_string(),
+            store,
+            PipelineTimer::start(),
+            CancellationToken::new(),
+        )
+    }
+
+    fn statuses(ctx: &PipelineContext) -> Vec<(StageKind, StageStatus)> {
+        ctx.reports
+            .iter()
+            .map(|report| (report.stage, report.status))
+            .collect()
+    }
+
+    fn post_process_config() -> PostProcessConfig {
+        PostProcessConfig {
+            mode: postprocess::PostProcessMode::Command,
+            command: "fake".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_default_stages() {
+        let config = SttConfig {
+            enhancement_enabled: true,
+            itn: itn::ItnConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            post_process: post_process_config(),
+            ..Default::default()
+        };
+        let providers = FakeProviders {
+            stt_text: "我在用三百二十个 gemeni".to_string(),
+            enhanced: Some(Ok("我在使用 320 个 gemeni。".to_string())),
+            post_processed: Some(Ok("我在使用 320 个 Gemini。#note".to_string())),
+            ..Default::default()
+        };
+        let mut ctx = context(config);
+
+        let text = Pipeline::for_config(&ctx.config)
+            .run(&providers, &mut ctx)
+            .await
+            .unwrap();
+        assert_eq!(text, "我在使用 320 个 Gemini。#note");
+        assert_eq!(
+            statuses(&ctx),
+            DEFAULT_STAGES
+                .iter()
+                .map(|stage| (*stage, StageStatus::Success))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(ctx.correction_hits.len(), 2);
+        assert!(ctx.corrections_dirty);
+
+        let outputs = providers.outputs.lock().unwrap();
+        let entry = &outputs[0];
+        assert_eq!(entry.stt_text, "我在用三百二十个 gemeni");
+        assert_eq!(
+            entry.pre_correction_text.as_deref(),
+            Some("我在用320个 gemeni")
+        );
+        assert_eq!(
+            entry.post_correction_text.as_deref(),
+            Some("我在使用 320 个 Gemini。")
+        );
+        assert_eq!(entry.enhancement_status.as_deref(), Some("success"));
+        assert_eq!(entry.final_text, "我在使用 320 个 Gemini。#note");
+        assert!(ctx.timer.timings.enhancement_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn failures_fall_back_to_previous_text() {
+        let config = SttConfig {
+            enhancement_enabled: true,
+            post_process: post_process_config(),
+            ..Default::default()
+        };
+        let providers = FakeProviders {
+            stt_text: "gemeni".to_string(),
+            enhanced: Some(Err("HTTP 500".to_string())),
+            post_processed: Some(Err("exit 1".to_string())),
+            ..Default::default()
+        };
+        let mut ctx = context(config);
+
+        let text = Pipeline::for_config(&ctx.config)
+            .run(&providers, &mut ctx)
+            .await
+            .unwrap();
+        assert_eq!(text, "Gemini");
+        assert_eq!(
+            statuses(&ctx),
+            vec![
+                (StageKind::Transcribe, StageStatus::Success),
+                (StageKind::Itn, StageStatus::Skipped),
+                (StageKind::Correct, StageStatus::Success),
+                (StageKind::Enhance, StageStatus::Fallback),
+                (StageKind::Correct, StageStatus::Skipped),
+                (StageKind::PostProcess, StageStatus::Fallback),
+                (StageKind::Output, StageStatus::Success),
+            ]
+        );
+        let entry = &providers.outputs.lock().unwrap()[0];
+        assert_eq!(entry.enhancement_status.as_deref(), Some("failed"));
+        assert_eq!(entry.enhancement_error.as_deref(), Some("HTTP 500"));
+        assert_eq!(entry.correction_hits.as_ref().map(Vec::len), Some(1));
+    }
+
+    #[tokio::test]
+    async fn cancellation_outputs_cancelled_entry() {
+        let providers = FakeProviders {
+            stt_text: "gemeni".to_string(),
+            ..Default::default()
+        };
+        let mut ctx = context(SttConfig::default());
+        ctx.cancel.cancel();
+
+        let result = Pipeline::for_config(&ctx.config)
+            .run(&providers, &mut ctx)
+            .await;
+        assert_eq!(
+            result,
+            Err(crate::TRANSCRIPTION_CANCELLED_ERROR.to_string())
+        );
+        assert!(!ctx.transcribed());
+        let entry = &providers.outputs.lock().unwrap()[0];
+        assert_eq!(entry.status.as_deref(), Some(crate::LOG_STATUS_CANCELLED));
+        assert!(entry.final_text.is_empty());
+    }
+
+    #[tokio::test]
+    async fn honours_custom_stage_order() {
+        let stages = vec![
+            StageKind::Transcribe,
+            StageKind::PostProcess,
+            StageKind::Correct,
+            StageKind::Output,
+        ];
+        assert!(validate_stages(&stages).is_ok());
+        assert!(validate_stages(&[StageKind::Correct, StageKind::Output]).is_err());
+        assert!(validate_stages(&[
+            StageKind::Transcribe,
+            StageKind::Enhance,
+            StageKind::Enhance,
+            StageKind::Output
+        ])
+        .is_err());
+
+        let config = SttConfig {
+            post_process: post_process_config(),
+            pipeline_stages: stages.clone(),
+            ..Default::default()
+        };
+        let providers = FakeProviders {
+            stt_text: "ticket 42".to_string(),
+            post_processed: Some(Ok("ticket 42 gemeni".to_string())),
+            ..Default::default()
+        };
+        let mut ctx = context(config);
+
+        let text = Pipeline::for_config(&ctx.config)
+            .run(&providers, &mut ctx)
+            .await
+            .unwrap();
+        assert_eq!(text, "ticket 42 Gemini");
+        assert_eq!(
+            ctx.reports
+                .iter()
+                .map(|report| report.stage)
+                .collect::<Vec<_>>(),
+            stages
+        );
+    }
+}
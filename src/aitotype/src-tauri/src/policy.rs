@@ -0,0 +1,272 @@
+//! 管理员策略模块
+//!
+//! 批量部署时可在系统级路径放置策略文件（或用 `AITOTYPE_POLICY_FILE` 指定路径）：
+//! `defaults` 作为用户配置之下的默认值，`locked` 列出的字段始终使用策略值，
+//! 用户和导入都无法修改。例如强制内网 base URL、关闭第三方润色、强制日志保留策略。
+//!
+//! ```json
+//! {
+//!   "defaults": { "base_url": "https://stt.example.internal/v1", "enhancement_enabled": false },
+//!   "locked": ["base_url", "enhancement_enabled"]
+//! }
+//! ```
+
+use crate::stt::SttConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+/// 指定策略文件路径的环境变量
+pub const POLICY_ENV_VAR: &str = "AITOTYPE_POLICY_FILE";
+
+/// 不允许通过策略文件下发的字段（策略文件通常对所有用户可读）
+const UNMANAGED_FIELDS: &[&str] = &["version", "api_key", "enhancement_api_key"];
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct PolicyFile {
+    #[serde(default)]
+    defaults: Map<String, Value>,
+    #[serde(default)]
+    locked: Vec<String>,
+}
+
+/// 已加载的策略
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    path: Option<PathBuf>,
+    defaults: Map<String, Value>,
+    locked: BTreeSet<String>,
+}
+
+/// 返回给设置界面的锁定信息
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyInfo {
+    /// 生效的策略文件路径（没有策略时为空）
+    pub path: Option<String>,
+    pub locked: Vec<String>,
+    /// 锁定字段的取值
+    pub values: Map<String, Value>,
+}
+
+/// 系统级默认路径
+fn system_policy_path() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/AItoType/defaults.json")
+    } else if cfg!(target_os = "windows") {
+        let program_data =
+            std::env::var("ProgramData").unwrap_or_else(|_| "C:\\ProgramData".to_string());
+        PathBuf::from(program_data)
+            .join("AItoType")
+            .join("defaults.json")
+    } else {
+        PathBuf::from("/etc/aitotype/defaults.json")
+    }
+}
+
+fn policy_path() -> PathBuf {
+    match std::env::var(POLICY_ENV_VAR) {
+        Ok(path) if !path.trim().is_empty() => PathBuf::from(path.trim()),
+        _ => system_policy_path(),
+    }
+}
+
+impl Policy {
+    /// 解析策略内容；无效的字段会被忽略并记录警告
+    fn parse(content: &str, path: Option<PathBuf>) -> Result<Self, String> {
+        let file: PolicyFile =
+            serde_json::from_str(content).map_err(|e| format!("策略文件格式错误: {}", e))?;
+        let Ok(Value::Object(known)) = serde_json::to_value(SttConfig::default()) else {
+            return Err("读取配置字段失败".to_string());
+        };
+        let is_managed = |field: &str| {
+            if UNMANAGED_FIELDS.contains(&field) {
+                warn!("策略文件不支持字段 {}，已忽略", field);
+                false
+            } else if !known.contains_key(field) {
+                warn!("策略文件包含未知字段 {}，已忽略", field);
+                false
+            } else {
+                true
+            }
+        };
+
+        let defaults: Map<String, Value> = file
+            .defaults
+            .into_iter()
+            .filter(|(field, _)| is_managed(field))
+            .collect();
+        let locked = file
+            .locked
+            .into_iter()
+            .filter(|field| is_managed(field))
+            .filter(|field| {
+                let has_value = defaults.contains_key(field);
+                if !has_value {
+                    warn!(
+                        "策略文件锁定了 {}，但 defaults 中没有对应的值，已忽略",
+                        field
+                    );
+                }
+                has_value
+            })
+            .collect();
+
+        let policy = Self {
+            path,
+            defaults,
+            locked,
+        };
+        policy.apply_defaults(SttConfig::default())?;
+        Ok(policy)
+    }
+
+    fn load() -> Self {
+        let path = policy_path();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("读取策略文件失败 {:?}: {:?}", path, e);
+                return Self::default();
+            }
+        };
+        match Self::parse(&content, Some(path.clone())) {
+            Ok(policy) => {
+                info!("已加载策略文件 {:?}，锁定字段: {:?}", path, policy.locked);
+                policy
+            }
+            Err(e) => {
+                warn!("策略文件无效，已忽略 {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// 把策略默认值补到配置 JSON 中缺失的字段（用户已设置的字段保持不变）
+    pub fn merge_defaults(&self, config: &mut Map<String, Value>) {
+        for (field, value) in &self.defaults {
+            config.entry(field.clone()).or_insert_with(|| value.clone());
+        }
+    }
+
+    /// 以策略默认值覆盖内置默认配置（没有用户配置时使用）
+    pub fn apply_defaults(&self, config: SttConfig) -> Result<SttConfig, String> {
+        self.overwrite(config, |_| true)
+    }
+
+    /// 锁定字段强制使用策略值
+    pub fn enforce(&self, config: SttConfig) -> SttConfig {
+        if self.locked.is_empty() {
+            return config;
+        }
+        let fallback = config.clone();
+        self.overwrite(config, |field| self.locked.contains(field))
+            .unwrap_or_else(|e| {
+                warn!("应用策略锁定失败: {}", e);
+                fallback
+            })
+    }
+
+    fn overwrite(
+        &self,
+        config: SttConfig,
+        include: impl Fn(&str) -> bool,
+    ) -> Result<SttConfig, String> {
+        let Value::Object(mut object) =
+            serde_json::to_value(config).map_err(|e| format!("序列化配置失败: {:?}", e))?
+        else {
+            return Err("配置格式错误".to_string());
+        };
+        for (field, value) in &self.defaults {
+            if include(field) {
+                object.insert(field.clone(), value.clone());
+            }
+        }
+        serde_json::from_value(Value::Object(object))
+            .map_err(|e| format!("策略字段取值无效: {}", e))
+    }
+
+    pub fn info(&self) -> PolicyInfo {
+        PolicyInfo {
+            path: self
+                .path
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string()),
+            locked: self.locked.iter().cloned().collect(),
+            values: self
+                .defaults
+                .iter()
+                .filter(|(field, _)| self.locked.contains(*field))
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// 当前策略（首次调用时加载，运行期间不变）
+pub fn current() -> &'static Policy {
+    POLICY.get_or_init(Policy::load)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locked_fields_override_user_config() {
+        let content = serde_json::json!({
+            "defaults": {
+                "base_url": "https://stt.example.internal/v1",
+                "enhancement_enabled": false,
+                "log_max_age_days": 30,
+                "model": "team-model",
+                "api_key": "sk-should-not-ship"
+            },
+            "locked": ["base_url", "enhancement_enabled", "log_max_age_days", "api_key", "unknown"]
+        })
+        .to_string();
+        let policy = Policy::parse(&content, None).unwrap();
+        assert_eq!(
+            policy.info().locked,
+            vec!["base_url", "enhancement_enabled", "log_max_age_days"]
+        );
+
+        let user = SttConfig {
+            base_url: "https://openrouter.ai/api/v1".to_string(),
+            enhancement_enabled: true,
+            log_max_age_days: 0,
+            model: "my-model".to_string(),
+            ..Default::default()
+        };
+        let enforced = policy.enforce(user);
+        assert_eq!(enforced.base_url, "https://stt.example.internal/v1");
+        assert!(!enforced.enhancement_enabled);
+        assert_eq!(enforced.log_max_age_days, 30);
+        assert_eq!(enforced.model, "my-model");
+
+        let defaults = policy.apply_defaults(SttConfig::default()).unwrap();
+        assert_eq!(defaults.model, "team-model");
+        assert!(defaults.api_key.is_empty());
+
+        let mut file = Map::new();
+        file.insert("model".to_string(), Value::from("file-model"));
+        policy.merge_defaults(&mut file);
+        assert_eq!(file["model"], "file-model");
+        assert_eq!(file["log_max_age_days"], 30);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let content = serde_json::json!({
+            "defaults": { "enhancement_enabled": "no" },
+            "locked": ["enhancement_enabled"]
+        })
+        .to_string();
+        assert!(Policy::parse(&content, None).is_err());
+    }
+}
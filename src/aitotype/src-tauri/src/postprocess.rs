@@ -0,0 +1,320 @@
+//! 后处理模块
+//!
+//! 在易错词第二轮替换之后、输出之前，把最终文本交给用户脚本或本地 Webhook，
+//! 用于团队格式约定、工单号展开、同步到知识库等：
+//! - 命令：通过系统 shell 执行，文本写入 stdin，stdout 作为替换结果
+//! - Webhook：POST JSON `{"text", "raw_text", "timestamp"}`，响应 `{"text": ...}` 或纯文本作为替换结果，
+//!   空响应表示保持原文（只做记录的 Webhook）
+//!
+//! 失败或超时由调用方回退到未处理的文本。
+
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const MAX_TIMEOUT_SECS: u64 = 120;
+/// 错误信息中保留的 stderr 长度
+const MAX_STDERR_CHARS: usize = 200;
+
+/// 后处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostProcessMode {
+    #[default]
+    Off,
+    /// 本地命令（stdin → stdout）
+    Command,
+    /// HTTP POST 到 Webhook
+    Webhook,
+}
+
+/// 后处理配置（默认关闭）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostProcessConfig {
+    #[serde(default)]
+    pub mode: PostProcessMode,
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub url: String,
+    /// 超时秒数（0 表示使用默认值）
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+impl Default for PostProcessConfig {
+    fn default() -> Self {
+        Self {
+            mode: PostProcessMode::Off,
+            command: String::new(),
+            url: String::new(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl PostProcessConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.mode != PostProcessMode::Off
+    }
+
+    fn timeout(&self) -> Duration {
+        let secs = match self.timeout_secs {
+            0 => DEFAULT_TIMEOUT_SECS,
+            secs => secs.min(MAX_TIMEOUT_SECS),
+        };
+        Duration::from_secs(secs)
+    }
+
+    /// 去掉首尾空白，超时秒数限制在合理范围
+    pub fn normalized(&self) -> Self {
+        Self {
+            mode: self.mode,
+            command: self.command.trim().to_string(),
+            url: self.url.trim().to_string(),
+            timeout_secs: self.timeout().as_secs(),
+        }
+    }
+}
+
+/// 解析 Webhook 地址：只接受本机回环地址的 http(s) 地址
+///
+/// 后处理会发送未脱敏的原始文本，不能绕过脱敏与润色锁定发往第三方。
+fn webhook_url(url: &str) -> Result<reqwest::Url, String> {
+    let url =
+        reqwest::Url::parse(url.trim()).map_err(|e| format!("后处理 Webhook 地址无效: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("后处理 Webhook 地址必须以 http:// 或 https:// 开头".to_string());
+    }
+    let host = url.host_str().unwrap_or_default();
+    let loopback = host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback());
+    if !loopback {
+        return Err("后处理 Webhook 只支持本机地址（127.0.0.1、::1 或 localhost）".to_string());
+    }
+    Ok(url)
+}
+
+/// 保存前校验：启用的方式必须填写命令或本机的 http(s) 地址
+pub fn validate(config: &PostProcessConfig) -> Result<(), String> {
+    match config.mode {
+        PostProcessMode::Off => Ok(()),
+        PostProcessMode::Command if config.command.trim().is_empty() => {
+            Err("后处理命令不能为空".to_string())
+        }
+        PostProcessMode::Command => Ok(()),
+        PostProcessMode::Webhook => webhook_url(&config.url).map(|_| ()),
+    }
+}
+
+/// 交给后处理的文本
+#[derive(Debug, Clone, Serialize)]
+pub struct PostProcessInput<'a> {
+    /// 润色与易错词替换后的最终文本
+    pub text: &'a str,
+    /// STT 原始文本
+    pub raw_text: &'a str,
+    pub timestamp: &'a str,
+}
+
+/// 执行后处理，返回替换后的文本
+pub async fn run(
+    config: &PostProcessConfig,
+    input: &PostProcessInput<'_>,
+) -> Result<String, String> {
+    let timeout = config.timeout();
+    let task = async {
+        match config.mode {
+            PostProcessMode::Off => Ok(input.text.to_string()),
+            PostProcessMode::Command => run_command(config.command.trim(), input.text).await,
+            PostProcessMode::Webhook => run_webhook(config.url.trim(), input, timeout).await,
+        }
+    };
+    tokio::time::timeout(timeout, task)
+        .await
+        .map_err(|_| format!("后处理超时（{} 秒）", timeout.as_secs()))?
+}
+
+fn shell_command(command: &str) -> tokio::process::Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+async fn run_command(command: &str, text: &str) -> Result<String, String> {
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // 超时后 future 被丢弃时结束子进程
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("启动后处理命令失败: {:?}", e))?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| "打开后处理命令 stdin 失败".to_string())?;
+    let write = async move {
+        // 脚本可能不读 stdin，写入失败不影响结果
+        let _ = stdin.write_all(text.as_bytes()).await;
+    };
+    let ((), output) = tokio::join!(write, child.wait_with_output());
+    let output = output.map_err(|e| format!("等待后处理命令失败: {:?}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr: String = stderr.trim().chars().take(MAX_STDERR_CHARS).collect();
+        return Err(format!("后处理命令失败（{}）: {}", output.status, stderr));
+    }
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|_| "后处理命令输出不是有效的 UTF-8".to_string())?;
+    let replaced = trim_trailing_newlines(&stdout);
+    if replaced.trim().is_empty() {
+        return Err("后处理命令没有输出".to_string());
+    }
+    Ok(replaced.to_string())
+}
+
+async fn run_webhook(
+    url: &str,
+    input: &PostProcessInput<'_>,
+    timeout: Duration,
+) -> Result<String, String> {
+    let url = webhook_url(url)?;
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {:?}", e))?;
+    let response = client
+        .post(url)
+        .json(input)
+        .send()
+        .await
+        .map_err(|e| format!("请求后处理 Webhook 失败: {}", e.without_url()))?;
+
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("json"));
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("读取后处理 Webhook 响应失败: {}", e.without_url()))?;
+    if !status.is_success() {
+        return Err(format!("后处理 Webhook 返回 {}", status));
+    }
+    webhook_result(&body, is_json, input.text)
+}
+
+/// 解析 Webhook 响应：JSON 取 `text` 字段，否则把响应体当作文本；为空时保持原文
+fn webhook_result(body: &str, is_json: bool, original: &str) -> Result<String, String> {
+    if is_json {
+        let value: serde_json::Value = serde_json::from_str(body)
+            .map_err(|e| format!("后处理 Webhook 响应不是有效的 JSON: {}", e))?;
+        return Ok(match value.get("text").and_then(|text| text.as_str()) {
+            Some(text) if !text.trim().is_empty() => text.to_string(),
+            _ => original.to_string(),
+        });
+    }
+    let text = trim_trailing_newlines(body);
+    if text.trim().is_empty() {
+        Ok(original.to_string())
+    } else {
+        Ok(text.to_string())
+    }
+}
+
+fn trim_trailing_newlines(text: &str) -> &str {
+    text.trim_end_matches(['\r', '\n'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_enabled_modes() {
+        assert!(validate(&PostProcessConfig::default()).is_ok());
+
+        let command = PostProcessConfig {
+            mode: PostProcessMode::Command,
+            command: "  ".to_string(),
+            ..Default::default()
+        };
+        assert!(validate(&command).is_err());
+
+        let webhook = PostProcessConfig {
+            mode: PostProcessMode::Webhook,
+            url: "http://127.0.0.1:8080/hook".to_string(),
+            ..Default::default()
+        };
+        assert!(validate(&webhook).is_ok());
+        let webhook = PostProcessConfig {
+            url: "file:///etc/passwd".to_string(),
+            ..webhook
+        };
+        assert!(validate(&webhook).is_err());
+        for url in ["http://localhost:9000/hook", "https://[::1]/hook"] {
+            let local = PostProcessConfig {
+                url: url.to_string(),
+                ..webhook.clone()
+            };
+            assert!(validate(&local).is_ok(), "{}", url);
+        }
+        for url in ["https://example.com/hook", "http://192.168.1.10/hook"] {
+            let remote = PostProcessConfig {
+                url: url.to_string(),
+                ..webhook.clone()
+            };
+            assert!(validate(&remote).is_err(), "{}", url);
+        }
+
+        let normalized = PostProcessConfig {
+            timeout_secs: 0,
+            ..Default::default()
+        }
+        .normalized();
+        assert_eq!(normalized.timeout_secs, DEFAULT_TIMEOUT_SECS);
+    }
+
+    #[test]
+    fn parses_webhook_responses() {
+        let original = "修复 123 号工单";
+        assert_eq!(
+            webhook_result(r#"{"text": "修复 PROJ-123"}"#, true, original).unwrap(),
+            "修复 PROJ-123"
+        );
+        assert_eq!(
+            webhook_result(r#"{"ok": true}"#, true, original).unwrap(),
+            original
+        );
+        assert_eq!(
+            webhook_result("替换后\n", false, original).unwrap(),
+            "替换后"
+        );
+        assert_eq!(webhook_result("", false, original).unwrap(), original);
+        assert!(webhook_result("not json", true, original).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! 配置方案模块
+//!
+//! 一个配置方案是一份完整的 `SttConfig` 快照（服务商、模型、润色等），
+//! 保存在 app config 目录下的 `profiles.json`，API Key 按方案存入密钥存储。
+//! `config.json` 始终是当前方案的配置，切换方案时整体替换。
+
+use crate::config;
+use crate::paths::AppPaths;
+use crate::secrets;
+use crate::stt::SttConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::warn;
+
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_FILENAME: &str = "profiles.json";
+const PROFILES_VERSION: u32 = 1;
+const MAX_PROFILE_NAME_CHARS: usize = 40;
+
+/// 配置方案切换后发给前端的事件
+pub const PROFILE_CHANGED_EVENT: &str = "profile-changed-event";
+
+/// 配置方案存储（不含 API Key）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileStore {
+    #[serde(default)]
+    pub version: u32,
+    /// 当前使用的方案
+    #[serde(default)]
+    pub active: String,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, SttConfig>,
+}
+
+/// 返回给前端的方案列表
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileList {
+    pub active: String,
+    pub profiles: Vec<String>,
+}
+
+impl ProfileStore {
+    pub fn list(&self) -> ProfileList {
+        ProfileList {
+            active: self.active.clone(),
+            profiles: self.profiles.keys().cloned().collect(),
+        }
+    }
+
+    pub fn create(&mut self, name: &str, config: &SttConfig) -> Result<String, String> {
+        let name = validate_name(name)?;
+        if self.profiles.contains_key(&name) {
+            return Err(format!("配置方案已存在: {}", name));
+        }
+        self.profiles
+            .insert(name.clone(), secrets::stripped(config));
+        Ok(name)
+    }
+
+    pub fn rename(&mut self, old_name: &str, new_name: &str) -> Result<String, String> {
+        let new_name = validate_name(new_name)?;
+        if !self.profiles.contains_key(old_name) {
+            return Err(format!("未找到配置方案: {}", old_name));
+        }
+        if old_name == new_name {
+            return Ok(new_name);
+        }
+        if self.profiles.contains_key(&new_name) {
+            return Err(format!("配置方案已存在: {}", new_name));
+        }
+        if let Some(config) = self.profiles.remove(old_name) {
+            self.profiles.insert(new_name.clone(), config);
+        }
+        if self.active == old_name {
+            self.active = new_name.clone();
+        }
+        Ok(new_name)
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        if self.active == name {
+            return Err("不能删除当前使用的配置方案".to_string());
+        }
+        self.profiles
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| format!("未找到配置方案: {}", name))
+    }
+}
+
+/// 校验方案名称：去掉首尾空白，不能为空、过长或包含控制字符
+pub fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("配置方案名称不能为空".to_string());
+    }
+    if name.chars().count() > MAX_PROFILE_NAME_CHARS {
+        return Err(format!(
+            "配置方案名称不能超过 {} 个字符",
+            MAX_PROFILE_NAME_CHARS
+        ));
+    }
+    if name.chars().any(char::is_control) {
+        return Err("配置方案名称不能包含控制字符".to_string());
+    }
+    Ok(name.to_string())
+}
+
+fn profiles_path(app: &impl AppPaths) -> Result<PathBuf, String> {
+    Ok(app.app_config_dir()?.join(PROFILES_FILENAME))
+}
+
+/// 读取方案存储（文件不存在或无法解析时返回空存储）
+pub fn load_store(app: &impl AppPaths) -> ProfileStore {
+    let path = match profiles_path(app) {
+        Ok(path) => path,
+        Err(_) => return ProfileStore::default(),
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return ProfileStore::default(),
+    };
+    match serde_json::from_str::<ProfileStore>(&content) {
+        Ok(store) => store,
+        Err(e) => {
+            warn!("读取配置方案失败 {:?}: {:?}", path, e);
+            ProfileStore::default()
+        }
+    }
+}
+
+/// 保存方案存储（原子写入，不含 API Key）
+pub fn save_store(app: &impl AppPaths, store: &ProfileStore) -> Result<(), String> {
+    let mut store = store.clone();
+    store.version = PROFILES_VERSION;
+    for config in store.profiles.values_mut() {
+        *config = secrets::stripped(config);
+        config.version = config::CONFIG_VERSION;
+    }
+    let json =
+        serde_json::to_string_pretty(&store).map_err(|e| format!("序列化配置方案失败: {:?}", e))?;
+    config::write_atomic(&profiles_path(app)?, json.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(model: &str) -> SttConfig {
+        SttConfig {
+            model: model.to_string(),
+            api_key: "sk-secret".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn create_rename_and_delete() {
+        let mut store = ProfileStore::default();
+        store.create(DEFAULT_PROFILE, &config("a")).unwrap();
+        store.active = DEFAULT_PROFILE.to_string();
+
+        assert_eq!(store.create(" work ", &config("w")).unwrap(), "work");
+        assert!(store.profiles["work"].api_key.is_empty());
+        assert!(store.create("work", &config("w")).is_err());
+        assert!(store.create("  ", &config("w")).is_err());
+
+        store.active = "work".to_string();
+        assert_eq!(store.rename("work", "office").unwrap(), "office");
+        assert_eq!(store.active, "office");
+        assert_eq!(store.profiles["office"].model, "w");
+
+        assert!(store.delete("office").is_err());
+        store.delete(DEFAULT_PROFILE).unwrap();
+        assert_eq!(store.list().profiles, vec!["office".to_string()]);
+    }
+}
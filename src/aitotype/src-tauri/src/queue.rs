@@ -0,0 +1,288 @@
+//! 离线转写队列模块
+//!
+//! 转写失败的录音连同元数据移入 app data 目录下的队列，
+//! 网络恢复后自动重试，也可在历史页面手动重试。
+//! 只有网络、超时、429、5xx 这类暂时性失败会进入队列；自动重试
+//! [`MAX_AUTO_ATTEMPTS`] 次或遇到非暂时性错误后，只能手动重试。
+
+use crate::stt::SttConfig;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+pub const QUEUE_DIRNAME: &str = "queue";
+/// 自动重试的基础间隔（秒），按失败次数指数退避
+pub const RETRY_BASE_DELAY_SECS: i64 = 30;
+/// 自动重试的最大间隔（秒）
+pub const RETRY_MAX_DELAY_SECS: i64 = 30 * 60;
+/// 自动重试次数上限，超过后任务留在队列中等待手动重试
+pub const MAX_AUTO_ATTEMPTS: u32 = 8;
+
+/// 队列中的一条待重试录音
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedJob {
+    pub id: String,
+    pub created_at: String,
+    pub audio_file: String,
+    pub stt_provider: String,
+    pub stt_model: String,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub last_attempt_at: Option<String>,
+    /// 最近一次失败是否为暂时性的，决定是否继续自动重试
+    #[serde(default = "default_auto_retry")]
+    pub auto_retry: bool,
+}
+
+fn default_auto_retry() -> bool {
+    true
+}
+
+/// 获取队列目录
+pub fn queue_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(QUEUE_DIRNAME))
+        .map_err(|e| format!("获取数据目录失败: {:?}", e))
+}
+
+fn validate_job_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("无效的队列任务 ID: {}", id))
+    }
+}
+
+fn job_meta_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn save_job(dir: &Path, job: &QueuedJob) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(job).map_err(|e| format!("序列化失败: {:?}", e))?;
+    std::fs::write(job_meta_path(dir, &job.id), json).map_err(|e| format!("保存失败: {:?}", e))
+}
+
+/// 移动文件；跨文件系统时回退为复制后删除
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to).map_err(|e| format!("复制录音文件失败: {:?}", e))?;
+    if let Err(e) = std::fs::remove_file(from) {
+        warn!("queue: 清理原录音文件失败 {:?}: {:?}", from, e);
+    }
+    Ok(())
+}
+
+/// 把转写失败的录音移入队列
+pub fn enqueue(
+    app: &tauri::AppHandle,
+    file_path: &str,
+    config: &SttConfig,
+    error: &str,
+) -> Result<QueuedJob, String> {
+    let dir = queue_dir(app)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建队列目录失败: {:?}", e))?;
+
+    let now = Local::now();
+    let id = now.format("%Y%m%d%H%M%S%3f").to_string();
+    let audio_file = format!("{}.wav", id);
+    move_file(Path::new(file_path), &dir.join(&audio_file))?;
+
+    let job = QueuedJob {
+        id,
+        created_at: now.to_rfc3339(),
+        audio_file,
+        stt_provider: config.provider.clone(),
+        stt_model: config.model.clone(),
+        attempts: 0,
+        last_error: Some(error.to_string()),
+        last_attempt_at: Some(now.to_rfc3339()),
+        auto_retry: true,
+    };
+    save_job(&dir, &job)?;
+    Ok(job)
+}
+
+/// 列出队列中的任务（按创建时间升序）
+pub fn list_jobs(app: &tauri::AppHandle) -> Vec<QueuedJob> {
+    let dir = match queue_dir(app) {
+        Ok(dir) => dir,
+        Err(_) => return Vec::new(),
+    };
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut jobs: Vec<QueuedJob> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            match serde_json::from_str::<QueuedJob>(&content) {
+                Ok(job) => Some(job),
+                Err(err) => {
+                    warn!("queue: parse failed {:?}: {:?}", path, err);
+                    None
+                }
+            }
+        })
+        .collect();
+    jobs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    jobs
+}
+
+pub fn load_job(app: &tauri::AppHandle, id: &str) -> Result<QueuedJob, String> {
+    validate_job_id(id)?;
+    let dir = queue_dir(app)?;
+    let content = std::fs::read_to_string(job_meta_path(&dir, id))
+        .map_err(|e| format!("读取队列任务失败: {:?}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析队列任务失败: {:?}", e))
+}
+
+/// 获取任务录音文件的绝对路径
+pub fn job_audio_path(app: &tauri::AppHandle, job: &QueuedJob) -> Result<PathBuf, String> {
+    validate_job_id(&job.id)?;
+    let dir = queue_dir(app)?;
+    let path = dir.join(format!("{}.wav", job.id));
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(format!("队列录音文件不存在: {:?}", path))
+    }
+}
+
+/// 记录一次重试失败；`transient` 为 false 时不再自动重试
+pub fn record_failure(app: &tauri::AppHandle, job: &mut QueuedJob, error: &str, transient: bool) {
+    job.attempts = job.attempts.saturating_add(1);
+    job.last_error = Some(error.to_string());
+    job.auto_retry = transient;
+    job.last_attempt_at = Some(Local::now().to_rfc3339());
+
+    let result = queue_dir(app).and_then(|dir| save_job(&dir, job));
+    if let Err(e) = result {
+        warn!("queue: record_failure {}: {}", job.id, e);
+    }
+}
+
+/// 删除任务及其录音文件
+pub fn remove_job(app: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    validate_job_id(id)?;
+    let dir = queue_dir(app)?;
+
+    let audio_path = dir.join(format!("{}.wav", id));
+    if audio_path.exists() {
+        std::fs::remove_file(&audio_path).map_err(|e| format!("删除录音失败: {:?}", e))?;
+    }
+    let meta_path = job_meta_path(&dir, id);
+    if meta_path.exists() {
+        std::fs::remove_file(&meta_path).map_err(|e| format!("删除队列任务失败: {:?}", e))?;
+    }
+    Ok(())
+}
+
+/// 第 n 次失败后的自动重试间隔（秒）
+fn retry_delay_secs(attempts: u32) -> i64 {
+    let factor = 1_i64 << attempts.min(10);
+    (RETRY_BASE_DELAY_SECS * factor).min(RETRY_MAX_DELAY_SECS)
+}
+
+/// 是否还会自动重试：次数未用完，且最近一次失败是暂时性的
+pub fn allows_auto_retry(job: &QueuedJob) -> bool {
+    job.auto_retry && job.attempts < MAX_AUTO_ATTEMPTS
+}
+
+/// 是否到了自动重试时间
+pub fn is_due(job: &QueuedJob, now: DateTime<Local>) -> bool {
+    if !allows_auto_retry(job) {
+        return false;
+    }
+    let last_attempt = match job
+        .last_attempt_at
+        .as_deref()
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+    {
+        Some(value) => value,
+        None => return true,
+    };
+    let elapsed = now.signed_duration_since(last_attempt).num_seconds();
+    elapsed >= retry_delay_secs(job.attempts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_with(attempts: u32, last_attempt_at: Option<String>) -> QueuedJob {
+        QueuedJob {
+            id: "20260101120000000".to_string(),
+            created_at: Local::now().to_rfc3339(),
+            audio_file: "20260101120000000.wav".to_string(),
+            stt_provider: "openrouter".to_string(),
+            stt_model: "model".to_string(),
+            attempts,
+            last_error: None,
+            last_attempt_at,
+            auto_retry: true,
+        }
+    }
+
+    #[test]
+    fn retry_delay_backs_off_and_caps() {
+        assert_eq!(retry_delay_secs(0), 30);
+        assert_eq!(retry_delay_secs(1), 60);
+        assert_eq!(retry_delay_secs(3), 240);
+        assert_eq!(retry_delay_secs(50), RETRY_MAX_DELAY_SECS);
+    }
+
+    #[test]
+    fn job_is_due_after_backoff() {
+        let now = Local::now();
+        let recent = job_with(1, Some((now - chrono::Duration::seconds(10)).to_rfc3339()));
+        assert!(!is_due(&recent, now));
+
+        let old = job_with(1, Some((now - chrono::Duration::seconds(61)).to_rfc3339()));
+        assert!(is_due(&old, now));
+
+        assert!(is_due(&job_with(0, None), now));
+    }
+
+    #[test]
+    fn stops_auto_retry_after_limit_or_permanent_error() {
+        let long_ago = Some((Local::now() - chrono::Duration::days(1)).to_rfc3339());
+        let now = Local::now();
+
+        let mut job = job_with(MAX_AUTO_ATTEMPTS - 1, long_ago.clone());
+        job.last_error = Some("API 返回错误 503 Service Unavailable: busy".to_string());
+        assert!(is_due(&job, now));
+
+        job.attempts = MAX_AUTO_ATTEMPTS;
+        assert!(!allows_auto_retry(&job));
+        assert!(!is_due(&job, now));
+
+        let mut job = job_with(1, long_ago);
+        job.last_error = Some("API 返回错误 401 Unauthorized: bad key".to_string());
+        job.auto_retry = false;
+        assert!(!is_due(&job, now));
+    }
+
+    #[test]
+    fn rejects_path_like_job_ids() {
+        assert!(validate_job_id("20260101120000000").is_ok());
+        assert!(validate_job_id("../config").is_err());
+        assert!(validate_job_id("").is_err());
+    }
+}
@@ -0,0 +1,541 @@
+//! 隐私脱敏模块
+//!
+//! 在写入日志和发送给第三方 LLM 润色之前识别敏感信息：邮箱、电话、银行卡号（Luhn 校验）、
+//! IBAN（mod-97 校验）、疑似 API Key，以及用户自定义正则。
+//! 每类可选择仅在日志中打码，或同时在润色前替换为占位符、润色后还原。
+
+use crate::logging::TranscribeLogEntry;
+use crate::stt;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use tracing::warn;
+
+/// 润色前提示 LLM 保留占位符
+const PLACEHOLDER_HINT: &str =
+    "注意：文本中形如 [EMAIL_1] 的方括号占位符代表已隐藏的内容，必须原样保留在输出中。";
+
+lazy_static::lazy_static! {
+    static ref EMAIL_RE: Regex =
+        Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}").unwrap();
+    static ref PHONE_RE: Regex = Regex::new(concat!(
+        // 中国大陆手机号
+        r"(?:\+?86[ -]?)?1[3-9]\d[ -]?\d{4}[ -]?\d{4}",
+        // 固定电话
+        r"|0\d{2,3}-\d{7,8}",
+        // 国际格式
+        r"|\+\d{1,3}[ -]?\(?\d{1,4}\)?(?:[ -]?\d{2,4}){2,4}",
+        // 北美格式
+        r"|\(?\d{3}\)?[ .-]\d{3}[ .-]\d{4}",
+    ))
+    .unwrap();
+    static ref CARD_RE: Regex = Regex::new(r"\d(?:[ -]?\d){12,18}").unwrap();
+    static ref IBAN_RE: Regex = Regex::new(r"[A-Z]{2}\d{2}(?: ?[A-Z0-9]){11,30}").unwrap();
+    static ref API_KEY_RE: Regex = Regex::new(concat!(
+        r"sk-[A-Za-z0-9_-]{16,}",
+        r"|(?:ghp|gho|ghu|ghs|ghr|github_pat)_[A-Za-z0-9_]{20,}",
+        r"|AKIA[0-9A-Z]{16}",
+        r"|AIza[0-9A-Za-z_-]{35}",
+        r"|xox[abprs]-[A-Za-z0-9-]{10,}",
+        r"|[A-Za-z0-9_-]{32,}",
+    ))
+    .unwrap();
+    /// 诊断日志中必须抹去的凭据（带明确前缀的密钥与 Bearer 头）
+    static ref SECRET_RE: Regex = Regex::new(concat!(
+        r"sk-[A-Za-z0-9_-]{16,}",
+        r"|(?:ghp|gho|ghu|ghs|ghr|github_pat)_[A-Za-z0-9_]{20,}",
+        r"|AKIA[0-9A-Z]{16}",
+        r"|AIza[0-9A-Za-z_-]{35}",
+        r"|xox[abprs]-[A-Za-z0-9-]{10,}",
+        r"|(?i:bearer)\s+[A-Za-z0-9._~+/=-]{8,}",
+    ))
+    .unwrap();
+}
+
+/// 抹去文本中的密钥（诊断日志兜底，不受脱敏配置影响）
+pub fn scrub_secrets(text: &str) -> Cow<'_, str> {
+    SECRET_RE.replace_all(text, "[API_KEY]")
+}
+
+/// 脱敏方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionMode {
+    #[default]
+    Off,
+    /// 仅在日志中打码
+    Log,
+    /// 日志中打码，并在润色前替换为占位符、润色后还原
+    Enhancement,
+}
+
+/// 用户自定义脱敏规则
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomRedactionRule {
+    #[serde(default)]
+    pub name: String,
+    pub pattern: String,
+    #[serde(default)]
+    pub mode: RedactionMode,
+}
+
+/// 脱敏配置（默认全部关闭）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    #[serde(default)]
+    pub email: RedactionMode,
+    #[serde(default)]
+    pub phone: RedactionMode,
+    #[serde(default)]
+    pub credit_card: RedactionMode,
+    #[serde(default)]
+    pub iban: RedactionMode,
+    #[serde(default)]
+    pub api_key: RedactionMode,
+    #[serde(default)]
+    pub custom: Vec<CustomRedactionRule>,
+}
+
+impl RedactionConfig {
+    fn is_active(&self) -> bool {
+        [
+            self.email,
+            self.phone,
+            self.credit_card,
+            self.iban,
+            self.api_key,
+        ]
+        .iter()
+        .chain(self.custom.iter().map(|rule| &rule.mode))
+        .any(|mode| *mode != RedactionMode::Off)
+    }
+}
+
+/// 润色前替换后的文本，以及占位符与原文的对应关系
+#[derive(Debug, Clone, Default)]
+pub struct RedactedText {
+    pub text: String,
+    pub placeholders: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+struct Span {
+    start: usize,
+    end: usize,
+    label: String,
+    mode: RedactionMode,
+}
+
+/// 校验自定义规则的正则
+pub fn validate(config: &RedactionConfig) -> Result<(), String> {
+    for rule in &config.custom {
+        if rule.pattern.trim().is_empty() {
+            return Err(format!("自定义脱敏规则 {} 缺少正则", rule.name));
+        }
+        Regex::new(&rule.pattern)
+            .map_err(|e| format!("自定义脱敏规则 {} 的正则无效: {}", rule.name, e))?;
+    }
+    Ok(())
+}
+
+/// 日志打码：所有启用的规则命中处替换为 `[类别]`
+pub fn mask_text(text: &str, config: &RedactionConfig) -> String {
+    if !config.is_active() {
+        return text.to_string();
+    }
+    replace_spans(text, &find_spans(text, config), |span| {
+        format!("[{}]", span.label)
+    })
+}
+
+/// 对日志条目中的各个文本字段打码
+pub fn mask_log_entry(entry: &mut TranscribeLogEntry, config: &RedactionConfig) {
+    if !config.is_active() {
+        return;
+    }
+    entry.stt_text = mask_text(&entry.stt_text, config);
+    entry.final_text = mask_text(&entry.final_text, config);
+    for field in [
+        &mut entry.pre_correction_text,
+        &mut entry.post_correction_text,
+        &mut entry.enhancement_text,
+    ] {
+        if let Some(text) = field.as_mut() {
+            *text = mask_text(text, config);
+        }
+    }
+}
+
+/// 润色前：把“润色前替换”规则的命中替换为编号占位符（相同原文复用同一占位符）
+pub fn redact_for_enhancement(text: &str, config: &RedactionConfig) -> RedactedText {
+    let spans: Vec<Span> = find_spans(text, config)
+        .into_iter()
+        .filter(|span| span.mode == RedactionMode::Enhancement)
+        .collect();
+    if spans.is_empty() {
+        return RedactedText {
+            text: text.to_string(),
+            placeholders: Vec::new(),
+        };
+    }
+
+    let mut placeholders: Vec<(String, String)> = Vec::new();
+    let redacted = replace_spans(text, &spans, |span| {
+        let original = &text[span.start..span.end];
+        if let Some((placeholder, _)) = placeholders.iter().find(|(_, value)| value == original) {
+            return placeholder.clone();
+        }
+        let index = placeholders
+            .iter()
+            .filter(|(placeholder, _)| placeholder.starts_with(&format!("[{}_", span.label)))
+            .count()
+            + 1;
+        let placeholder = format!("[{}_{}]", span.label, index);
+        placeholders.push((placeholder.clone(), original.to_string()));
+        placeholder
+    });
+
+    RedactedText {
+        text: redacted,
+        placeholders,
+    }
+}
+
+/// 润色后还原占位符；有占位符丢失时返回 None
+pub fn restore(text: &str, placeholders: &[(String, String)]) -> Option<String> {
+    if placeholders
+        .iter()
+        .any(|(placeholder, _)| !text.contains(placeholder.as_str()))
+    {
+        return None;
+    }
+    Some(
+        placeholders
+            .iter()
+            .fold(text.to_string(), |acc, (placeholder, original)| {
+                acc.replace(placeholder.as_str(), original)
+            }),
+    )
+}
+
+/// 在润色 Prompt 末尾追加“保留占位符”的要求
+pub fn with_placeholder_hint(template: &str) -> String {
+    let template = if template.contains("{text}") {
+        template.trim().to_string()
+    } else {
+        stt::default_enhancement_prompt()
+    };
+    format!("{}\n\n{}", template, PLACEHOLDER_HINT)
+}
+
+fn replace_spans(
+    text: &str,
+    spans: &[Span],
+    mut replacement: impl FnMut(&Span) -> String,
+) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for span in spans {
+        output.push_str(&text[cursor..span.start]);
+        output.push_str(&replacement(span));
+        cursor = span.end;
+    }
+    output.push_str(&text[cursor..]);
+    output
+}
+
+/// 按优先级收集命中：自定义 > 邮箱 > API Key > IBAN > 银行卡 > 电话，重叠时保留先命中者
+fn find_spans(text: &str, config: &RedactionConfig) -> Vec<Span> {
+    let mut spans = Vec::new();
+
+    for rule in &config.custom {
+        if rule.mode == RedactionMode::Off {
+            continue;
+        }
+        let re = match Regex::new(&rule.pattern) {
+            Ok(re) => re,
+            Err(e) => {
+                warn!("跳过无效的自定义脱敏规则 {}: {}", rule.name, e);
+                continue;
+            }
+        };
+        let label = custom_label(&rule.name);
+        for m in re.find_iter(text).filter(|m| !m.is_empty()) {
+            add_span(&mut spans, m.start(), m.end(), &label, rule.mode);
+        }
+    }
+
+    collect(&mut spans, text, &EMAIL_RE, config.email, "EMAIL", |_| true);
+    collect(
+        &mut spans,
+        text,
+        &API_KEY_RE,
+        config.api_key,
+        "API_KEY",
+        looks_like_api_key,
+    );
+    collect(
+        &mut spans,
+        text,
+        &IBAN_RE,
+        config.iban,
+        "IBAN",
+        is_valid_iban,
+    );
+
+    if config.credit_card != RedactionMode::Off {
+        for m in CARD_RE.find_iter(text) {
+            if let Some(len) = luhn_prefix_len(m.as_str()) {
+                let end = m.start() + len;
+                if is_bounded(text, m.start(), end) {
+                    add_span(&mut spans, m.start(), end, "CARD", config.credit_card);
+                }
+            }
+        }
+    }
+
+    collect(&mut spans, text, &PHONE_RE, config.phone, "PHONE", |_| true);
+
+    spans.sort_by_key(|span| span.start);
+    spans
+}
+
+fn collect(
+    spans: &mut Vec<Span>,
+    text: &str,
+    re: &Regex,
+    mode: RedactionMode,
+    label: &str,
+    accept: impl Fn(&str) -> bool,
+) {
+    if mode == RedactionMode::Off {
+        return;
+    }
+    for m in re.find_iter(text) {
+        if is_bounded(text, m.start(), m.end()) && accept(m.as_str()) {
+            add_span(spans, m.start(), m.end(), label, mode);
+        }
+    }
+}
+
+fn add_span(spans: &mut Vec<Span>, start: usize, end: usize, label: &str, mode: RedactionMode) {
+    if spans
+        .iter()
+        .any(|span| span.start < end && start < span.end)
+    {
+        return;
+    }
+    spans.push(Span {
+        start,
+        end,
+        label: label.to_string(),
+        mode,
+    });
+}
+
+fn custom_label(name: &str) -> String {
+    let label: String = name
+        .trim()
+        .chars()
+        .map(|ch| {
+            if ch.is_alphanumeric() {
+                ch.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if label.is_empty() {
+        "REDACTED".to_string()
+    } else {
+        label
+    }
+}
+
+/// 命中前后不能紧贴 ASCII 字母数字，避免截取更长编号的一部分（中文字符不算边界）
+fn is_bounded(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(|ch| ch.is_ascii_alphanumeric())
+        && !after.is_some_and(|ch| ch.is_ascii_alphanumeric())
+}
+
+/// 疑似密钥需同时包含字母和数字
+fn looks_like_api_key(token: &str) -> bool {
+    token.chars().any(|ch| ch.is_ascii_digit()) && token.chars().any(|ch| ch.is_ascii_alphabetic())
+}
+
+fn luhn_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, &digit)| {
+            if index % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// 候选串中能通过 Luhn 校验的最长前缀（按数字分组截断，13-19 位），返回字节长度
+///
+/// 用于处理卡号后紧跟其他数字的情况，如 “4111 1111 1111 1111 2026”。
+fn luhn_prefix_len(candidate: &str) -> Option<usize> {
+    let mut group_ends = Vec::new();
+    let mut digits = Vec::new();
+    let mut chars = candidate.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        let Some(digit) = ch.to_digit(10) else {
+            continue;
+        };
+        digits.push(digit);
+        let group_ends_here = chars.peek().is_none_or(|(_, next)| !next.is_ascii_digit());
+        if group_ends_here {
+            group_ends.push((index + 1, digits.len()));
+        }
+    }
+
+    group_ends
+        .into_iter()
+        .rev()
+        .find(|&(_, count)| (13..=19).contains(&count) && luhn_valid(&digits[..count]))
+        .map(|(end, _)| end)
+}
+
+/// IBAN mod-97 校验
+fn is_valid_iban(candidate: &str) -> bool {
+    let compact: String = candidate.chars().filter(|ch| !ch.is_whitespace()).collect();
+    if !(15..=34).contains(&compact.len()) {
+        return false;
+    }
+    let rearranged = format!("{}{}", &compact[4..], &compact[..4]);
+    let mut remainder: u32 = 0;
+    for ch in rearranged.chars() {
+        let value = match ch.to_digit(36) {
+            Some(value) => value,
+            None => return false,
+        };
+        remainder = if value >= 10 {
+            (remainder * 100 + value) % 97
+        } else {
+            (remainder * 10 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all(mode: RedactionMode) -> RedactionConfig {
+        RedactionConfig {
+            email: mode,
+            phone: mode,
+            credit_card: mode,
+            iban: mode,
+            api_key: mode,
+            custom: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn masks_builtin_detectors() {
+        let config = all(RedactionMode::Log);
+        assert_eq!(
+            mask_text("发邮件给 alice@example.com，电话13812345678", &config),
+            "发邮件给 [EMAIL]，电话[PHONE]"
+        );
+        assert_eq!(
+            mask_text("卡号 4111 1111 1111 1111 2026 年到期", &config),
+            "卡号 [CARD] 2026 年到期"
+        );
+        assert_eq!(
+            mask_text("IBAN GB82 WEST 1234 5698 7654 32", &config),
+            "IBAN [IBAN]"
+        );
+        assert_eq!(
+            mask_text("key is sk-abcdEFGH1234ijklMNOP5678", &config),
+            "key is [API_KEY]"
+        );
+        assert_eq!(
+            mask_text("call +1 415 555 2671 now", &config),
+            "call [PHONE] now"
+        );
+    }
+
+    #[test]
+    fn ignores_numbers_that_fail_checksums() {
+        let config = all(RedactionMode::Log);
+        assert_eq!(
+            mask_text("订单 4111 1111 1111 1112", &config),
+            "订单 4111 1111 1111 1112"
+        );
+        assert_eq!(
+            mask_text("GB00 WEST 1234 5698 7654 32", &config),
+            "GB00 WEST 1234 5698 7654 32"
+        );
+        assert_eq!(mask_text("2026年3月1日", &config), "2026年3月1日");
+        assert_eq!(
+            mask_text("anything", &RedactionConfig::default()),
+            "anything"
+        );
+    }
+
+    #[test]
+    fn placeholders_round_trip_through_enhancement() {
+        let mut config = all(RedactionMode::Log);
+        config.email = RedactionMode::Enhancement;
+        config.custom.push(CustomRedactionRule {
+            name: "project".to_string(),
+            pattern: r"代号\w{2}".to_string(),
+            mode: RedactionMode::Enhancement,
+        });
+
+        let redacted = redact_for_enhancement(
+            "嗯 把代号北极的文档发给 a@b.io 和 a@b.io，电话13812345678",
+            &config,
+        );
+        assert_eq!(
+            redacted.text,
+            "嗯 把[PROJECT_1]的文档发给 [EMAIL_1] 和 [EMAIL_1]，电话13812345678"
+        );
+
+        let enhanced = "把[PROJECT_1]的文档发给 [EMAIL_1]。";
+        assert_eq!(
+            restore(enhanced, &redacted.placeholders).unwrap(),
+            "把代号北极的文档发给 a@b.io。"
+        );
+        assert!(restore("把文档发出去。", &redacted.placeholders).is_none());
+    }
+
+    #[test]
+    fn scrubs_secrets_regardless_of_config() {
+        assert_eq!(
+            scrub_secrets("auth Bearer sk-or-v1-0123456789abcdef ok"),
+            "auth [API_KEY] ok"
+        );
+        assert_eq!(scrub_secrets("no secrets here"), "no secrets here");
+    }
+
+    #[test]
+    fn rejects_invalid_custom_patterns() {
+        let mut config = RedactionConfig::default();
+        config.custom.push(CustomRedactionRule {
+            name: "bad".to_string(),
+            pattern: "(".to_string(),
+            mode: RedactionMode::Log,
+        });
+        assert!(validate(&config).is_err());
+        assert_eq!(mask_text("(", &config), "(");
+    }
+}
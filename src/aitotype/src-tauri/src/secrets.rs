@@ -0,0 +1,311 @@
+//! 密钥存储模块
+//!
+//! API Key 不再明文写入 config.json：优先存入系统密钥库（macOS 钥匙串、
+//! Windows 凭据管理器、Linux Secret Service）；密钥库不可用时，
+//! 用本机绑定的密钥加密后写入 app config 目录下的 `secrets.json`。
+
+use crate::config;
+use crate::paths::{self, AppPaths};
+use crate::profiles;
+use crate::stt::SttConfig;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+const KEYRING_SERVICE: &str = paths::APP_IDENTIFIER;
+const FALLBACK_FILE: &str = "secrets.json";
+const KEY_DERIVATION_CONTEXT: &[u8] = b"aitotype-secrets-v1";
+const NONCE_LEN: usize = 12;
+
+pub const API_KEY: &str = "api_key";
+pub const ENHANCEMENT_API_KEY: &str = "enhancement_api_key";
+
+/// 返回给前端的掩码前缀；前端原样提交时表示“未修改”
+pub const MASK_PREFIX: &str = "••••";
+
+/// 掩码显示：只保留末 4 位，短密钥完全隐藏
+pub fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.is_empty() {
+        String::new()
+    } else if chars.len() <= 8 {
+        format!("{}{}", MASK_PREFIX, MASK_PREFIX)
+    } else {
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("{}{}", MASK_PREFIX, tail)
+    }
+}
+
+/// 返回 API Key 已掩码的配置副本（供前端展示）
+pub fn masked(config: &SttConfig) -> SttConfig {
+    let mut config = config.clone();
+    config.api_key = mask(&config.api_key);
+    config.enhancement_api_key = mask(&config.enhancement_api_key);
+    config
+}
+
+/// 前端提交的掩码值还原为当前密钥，其余值视为新密钥
+///
+/// 掩码与当前密钥对不上（例如期间切换过服务商）时无法还原，要求重新输入。
+pub fn unmask(submitted: &mut SttConfig, current: &SttConfig) -> Result<(), String> {
+    fn restore(submitted: &mut String, current: &str) -> Result<(), String> {
+        if !submitted.starts_with(MASK_PREFIX) {
+            return Ok(());
+        }
+        if *submitted != mask(current) {
+            return Err("API Key 已变更，请重新输入".to_string());
+        }
+        *submitted = current.to_string();
+        Ok(())
+    }
+
+    restore(&mut submitted.api_key, &current.api_key)?;
+    restore(
+        &mut submitted.enhancement_api_key,
+        &current.enhancement_api_key,
+    )
+}
+
+/// 去掉 API Key 的配置副本（写入 config.json）
+pub fn stripped(config: &SttConfig) -> SttConfig {
+    let mut config = config.clone();
+    config.api_key.clear();
+    config.enhancement_api_key.clear();
+    config
+}
+
+fn keyring_entry(name: &str) -> Result<keyring::Entry, keyring::Error> {
+    keyring::Entry::new(KEYRING_SERVICE, name)
+}
+
+fn fallback_path(app: &impl AppPaths) -> Result<PathBuf, String> {
+    Ok(app.app_config_dir()?.join(FALLBACK_FILE))
+}
+
+/// 由本机机器码派生加密密钥，拷贝到其他机器后无法解密
+fn machine_key() -> Result<[u8; 32], String> {
+    let machine_id = machine_uid::get().map_err(|e| format!("读取机器码失败: {}", e))?;
+    Ok(derive_key(machine_id.trim()))
+}
+
+fn derive_key(machine_id: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_DERIVATION_CONTEXT);
+    hasher.update(machine_id.as_bytes());
+    hasher.finalize().into()
+}
+
+/// 加密为 base64(nonce || ciphertext)
+fn encrypt(key: &[u8; 32], plaintext: &str) -> Result<String, String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|e| format!("加密密钥失败: {:?}", e))?;
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(STANDARD.encode(payload))
+}
+
+fn decrypt(key: &[u8; 32], encoded: &str) -> Result<String, String> {
+    let payload = STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("解码密钥失败: {:?}", e))?;
+    if payload.len() <= NONCE_LEN {
+        return Err("密钥数据已损坏".to_string());
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "解密密钥失败（可能来自其他机器）".to_string())?;
+    String::from_utf8(plaintext).map_err(|e| format!("解密密钥失败: {:?}", e))
+}
+
+fn read_fallback(app: &impl AppPaths) -> BTreeMap<String, String> {
+    let Ok(path) = fallback_path(app) else {
+        return BTreeMap::new();
+    };
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_fallback(app: &impl AppPaths, entries: &BTreeMap<String, String>) -> Result<(), String> {
+    let path = fallback_path(app)?;
+    if entries.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| format!("删除密钥文件失败: {:?}", e))?;
+        }
+        return Ok(());
+    }
+    let json = serde_json::to_string_pretty(entries)
+        .map_err(|e| format!("序列化密钥文件失败: {:?}", e))?;
+    config::write_atomic(&path, json.as_bytes())?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+fn load_fallback(app: &impl AppPaths, name: &str) -> Option<String> {
+    let encoded = read_fallback(app).remove(name)?;
+    let key = machine_key().map_err(|e| warn!("{}", e)).ok()?;
+    decrypt(&key, &encoded)
+        .map_err(|e| warn!("读取密钥 {} 失败: {}", name, e))
+        .ok()
+}
+
+fn store_fallback(app: &impl AppPaths, name: &str, value: Option<&str>) -> Result<(), String> {
+    let mut entries = read_fallback(app);
+    match value {
+        Some(value) => {
+            entries.insert(name.to_string(), encrypt(&machine_key()?, value)?);
+        }
+        None => {
+            if entries.remove(name).is_none() {
+                return Ok(());
+            }
+        }
+    }
+    write_fallback(app, &entries)
+}
+
+/// 读取密钥：先查系统密钥库，再查加密文件
+pub fn load(app: &impl AppPaths, name: &str) -> Option<String> {
+    match keyring_entry(name).and_then(|entry| entry.get_password()) {
+        Ok(value) => return Some(value),
+        Err(keyring::Error::NoEntry) => {}
+        Err(e) => warn!("系统密钥库不可用，改用加密文件: {}", e),
+    }
+    load_fallback(app, name)
+}
+
+/// 保存密钥（空值表示删除）：优先系统密钥库，失败时写入加密文件
+pub fn store(app: &impl AppPaths, name: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        match keyring_entry(name).and_then(|entry| entry.delete_credential()) {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => warn!("删除系统密钥库中的 {} 失败: {}", name, e),
+        }
+        return store_fallback(app, name, None);
+    }
+
+    match keyring_entry(name).and_then(|entry| entry.set_password(value)) {
+        Ok(()) => store_fallback(app, name, None),
+        Err(e) => {
+            warn!("写入系统密钥库失败，改用加密文件: {}", e);
+            store_fallback(app, name, Some(value))
+        }
+    }
+}
+
+/// 配置方案对应的密钥名；默认方案沿用旧名称，兼容升级前保存的密钥
+fn slot(profile: &str, name: &str) -> String {
+    if profile == profiles::DEFAULT_PROFILE {
+        name.to_string()
+    } else {
+        format!("{}@{}", name, profile)
+    }
+}
+
+/// 把配置中的 API Key 写入指定配置方案的密钥存储
+pub fn save_config_keys(
+    app: &impl AppPaths,
+    profile: &str,
+    config: &SttConfig,
+) -> Result<(), String> {
+    store(app, &slot(profile, API_KEY), &config.api_key)?;
+    store(
+        app,
+        &slot(profile, ENHANCEMENT_API_KEY),
+        &config.enhancement_api_key,
+    )
+}
+
+/// 从指定配置方案的密钥存储补全配置中的 API Key
+pub fn load_config_keys(app: &impl AppPaths, profile: &str, config: &mut SttConfig) {
+    if config.api_key.is_empty() {
+        config.api_key = load(app, &slot(profile, API_KEY)).unwrap_or_default();
+    }
+    if config.enhancement_api_key.is_empty() {
+        config.enhancement_api_key =
+            load(app, &slot(profile, ENHANCEMENT_API_KEY)).unwrap_or_default();
+    }
+}
+
+/// 删除配置方案的密钥
+pub fn delete_profile_keys(app: &impl AppPaths, profile: &str) -> Result<(), String> {
+    save_config_keys(app, profile, &stripped(&SttConfig::default()))
+}
+
+/// 配置方案改名时迁移密钥
+pub fn rename_profile_keys(app: &impl AppPaths, from: &str, to: &str) -> Result<(), String> {
+    let mut config = stripped(&SttConfig::default());
+    load_config_keys(app, from, &mut config);
+    save_config_keys(app, to, &config)?;
+    delete_profile_keys(app, from)
+}
+
+/// 旧版 config.json 中的明文密钥迁移到密钥存储
+///
+/// 返回 true 表示已迁移，调用方应重写去掉密钥的 config.json。
+pub fn migrate_plaintext_keys(app: &impl AppPaths, profile: &str, config: &SttConfig) -> bool {
+    if config.api_key.is_empty() && config.enhancement_api_key.is_empty() {
+        return false;
+    }
+    match save_config_keys(app, profile, config) {
+        Ok(()) => {
+            info!("已将 config.json 中的明文 API Key 迁移到安全存储");
+            true
+        }
+        Err(e) => {
+            warn!("迁移明文 API Key 失败，保留原配置: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_and_restores_unchanged_keys() {
+        assert_eq!(mask(""), "");
+        assert_eq!(mask("short"), "••••••••");
+        assert_eq!(mask("sk-or-v1-abcdef1234"), "••••1234");
+
+        let current = SttConfig {
+            api_key: "sk-or-v1-abcdef1234".to_string(),
+            enhancement_api_key: "sk-old-enhancement".to_string(),
+            ..Default::default()
+        };
+        let mut submitted = masked(&current);
+        submitted.enhancement_api_key = "sk-new-enhancement".to_string();
+        unmask(&mut submitted, &current).unwrap();
+        assert_eq!(submitted.api_key, current.api_key);
+        assert_eq!(submitted.enhancement_api_key, "sk-new-enhancement");
+
+        let mut stale = masked(&current);
+        stale.api_key = "••••9999".to_string();
+        assert!(unmask(&mut stale, &current).is_err());
+    }
+
+    #[test]
+    fn encrypts_with_machine_bound_key() {
+        let key = derive_key("machine-a");
+        let encoded = encrypt(&key, "sk-secret").unwrap();
+        assert!(!encoded.contains("sk-secret"));
+        assert_eq!(decrypt(&key, &encoded).unwrap(), "sk-secret");
+        assert!(decrypt(&derive_key("machine-b"), &encoded).is_err());
+    }
+}
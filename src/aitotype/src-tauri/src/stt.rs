@@ -2,11 +2,18 @@
 //!
 //! 支持 OpenRouter 与 SiliconFlow 两个 Provider
 
+use crate::itn::ItnConfig;
+use crate::pipeline::StageKind;
+use crate::postprocess::PostProcessConfig;
+use crate::redaction::RedactionConfig;
+use crate::timing::{self, StageTimings};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 pub const PROVIDER_OPENROUTER: &str = "openrouter";
 pub const PROVIDER_SILICONFLOW: &str = "siliconflow";
@@ -37,6 +44,26 @@ fn default_record_mode() -> String {
     "toggle".to_string()
 }
 
+fn default_audio_archive_max_age_days() -> u32 {
+    30
+}
+
+fn default_audio_archive_max_total_mb() -> u32 {
+    1024
+}
+
+fn default_log_enabled() -> bool {
+    true
+}
+
+fn default_diagnostic_log_level() -> String {
+    crate::diagnostics::DEFAULT_LOG_LEVEL.to_string()
+}
+
+fn default_control_api_port() -> u16 {
+    crate::control::DEFAULT_PORT
+}
+
 fn default_enhancement_provider() -> String {
     PROVIDER_OPENROUTER.to_string()
 }
@@ -45,7 +72,7 @@ fn default_enhancement_model() -> String {
     DEFAULT_ENHANCEMENT_OPENROUTER_MODEL.to_string()
 }
 
-fn default_enhancement_prompt() -> String {
+pub fn default_enhancement_prompt() -> String {
     "你是语音转文字的润色助手。请按规则处理文本：\n1) 去除口头禅、重复词和无意义停顿词；\n2) 修正明显错别字、术语和专有名词错误；\n3) 保留原意，不扩写、不总结、不补充新信息；\n4) 仅做必要标点与断句优化；\n5) 只输出润色后的最终文本，不要任何解释。\n\n原文：\n{text}".to_string()
 }
 
@@ -91,8 +118,13 @@ pub fn env_key_for_provider(provider: &str) -> &'static str {
 }
 
 /// STT 配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Debug 输出经过脱敏（见 `redacted`），避免 API Key 进入诊断日志。
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SttConfig {
+    /// 配置版本（见 `config::CONFIG_VERSION`），缺失视为 0
+    #[serde(default)]
+    pub version: u32,
     /// Provider: openrouter / siliconflow
     #[serde(default = "default_provider")]
     pub provider: String,
@@ -126,11 +158,53 @@ pub struct SttConfig {
     /// 润色 Prompt 模板（支持 {text} 占位）
     #[serde(default = "default_enhancement_prompt")]
     pub enhancement_prompt: String,
+    /// 是否归档录音（默认关闭）
+    #[serde(default)]
+    pub audio_archive_enabled: bool,
+    /// 归档录音最长保留天数（0 表示不限制）
+    #[serde(default = "default_audio_archive_max_age_days")]
+    pub audio_archive_max_age_days: u32,
+    /// 归档录音总大小上限，单位 MB（0 表示不限制）
+    #[serde(default = "default_audio_archive_max_total_mb")]
+    pub audio_archive_max_total_mb: u32,
+    /// 是否记录转写日志（关闭后不再保存任何转写内容）
+    #[serde(default = "default_log_enabled")]
+    pub log_enabled: bool,
+    /// 日志最长保留天数（0 表示不限制）
+    #[serde(default)]
+    pub log_max_age_days: u32,
+    /// 日志总大小上限，单位 MB（0 表示不限制）
+    #[serde(default)]
+    pub log_max_total_mb: u32,
+    /// 是否 gzip 压缩非当天的日志文件
+    #[serde(default)]
+    pub log_compress_old_files: bool,
+    /// 敏感信息脱敏规则（日志打码 / 润色前替换）
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    /// 最终文本后处理（用户脚本或 Webhook）
+    #[serde(default)]
+    pub post_process: PostProcessConfig,
+    /// 逆文本规范化（口语数字改写为阿拉伯数字）
+    #[serde(default)]
+    pub itn: ItnConfig,
+    /// 转写流水线阶段顺序（空表示默认顺序）
+    #[serde(default)]
+    pub pipeline_stages: Vec<StageKind>,
+    /// 诊断日志级别: error / warn / info / debug / trace
+    #[serde(default = "default_diagnostic_log_level")]
+    pub diagnostic_log_level: String,
+    /// 本地控制接口（仅监听 127.0.0.1 / Unix socket，需 Bearer Token）
+    #[serde(default)]
+    pub control_api_enabled: bool,
+    #[serde(default = "default_control_api_port")]
+    pub control_api_port: u16,
 }
 
 impl Default for SttConfig {
     fn default() -> Self {
         Self {
+            version: crate::config::CONFIG_VERSION,
             provider: default_provider(),
             base_url: DEFAULT_OPENROUTER_BASE_URL.to_string(),
             api_key: String::new(),
@@ -143,10 +217,82 @@ impl Default for SttConfig {
             enhancement_api_key: String::new(),
             enhancement_model: default_enhancement_model(),
             enhancement_prompt: default_enhancement_prompt(),
+            audio_archive_enabled: false,
+            audio_archive_max_age_days: default_audio_archive_max_age_days(),
+            audio_archive_max_total_mb: default_audio_archive_max_total_mb(),
+            log_enabled: default_log_enabled(),
+            log_max_age_days: 0,
+            log_max_total_mb: 0,
+            log_compress_old_files: false,
+            redaction: RedactionConfig::default(),
+            post_process: PostProcessConfig::default(),
+            itn: ItnConfig::default(),
+            pipeline_stages: Vec::new(),
+            diagnostic_log_level: default_diagnostic_log_level(),
+            control_api_enabled: false,
+            control_api_port: default_control_api_port(),
         }
     }
 }
 
+impl SttConfig {
+    /// 抹去 API Key 与 Base URL 中凭据后的副本，用于日志与诊断
+    pub fn redacted(&self) -> SttConfig {
+        let mask = |value: &str| {
+            if value.trim().is_empty() {
+                String::new()
+            } else {
+                "***".to_string()
+            }
+        };
+        let mut config = self.clone();
+        config.api_key = mask(&config.api_key);
+        config.enhancement_api_key = mask(&config.enhancement_api_key);
+        config.base_url = strip_url_credentials(&config.base_url);
+        config.enhancement_base_url = strip_url_credentials(&config.enhancement_base_url);
+        config.post_process.url = strip_url_credentials(&config.post_process.url);
+        config
+    }
+}
+
+impl std::fmt::Debug for SttConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let json = serde_json::to_string(&self.redacted()).unwrap_or_default();
+        write!(f, "SttConfig {}", json)
+    }
+}
+
+/// 去掉 URL 中的用户名/密码，以及名称像凭据的查询参数的值
+pub fn strip_url_credentials(url: &str) -> String {
+    let mut parsed = match reqwest::Url::parse(url.trim()) {
+        Ok(parsed) => parsed,
+        Err(_) => return url.to_string(),
+    };
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        let _ = parsed.set_username("***");
+        let _ = parsed.set_password(None);
+    }
+    if parsed.query().is_some() {
+        let pairs: Vec<(String, String)> = parsed
+            .query_pairs()
+            .map(|(name, value)| {
+                let lower = name.to_lowercase();
+                let secret = ["key", "token", "secret", "auth", "password"]
+                    .iter()
+                    .any(|word| lower.contains(word));
+                let value = if secret {
+                    "***".to_string()
+                } else {
+                    value.into_owned()
+                };
+                (name.into_owned(), value)
+            })
+            .collect();
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    parsed.to_string()
+}
+
 /// OpenRouter/LLM 响应格式
 #[derive(Debug, Deserialize)]
 struct ChatCompletionResponse {
@@ -279,6 +425,11 @@ fn classify_http_error(status: StatusCode, body: &str) -> String {
     }
 }
 
+/// 限流与服务端错误属于暂时性失败，稍后重试可能成功
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
 fn classify_network_error(err: &reqwest::Error) -> String {
     if err.is_timeout() {
         return format!(
@@ -514,17 +665,146 @@ async fn test_siliconflow_connection(config: &SttConfig) -> Result<(), String> {
     Ok(())
 }
 
+/// 检测 STT 服务地址能否建立 TCP 连接（用于判断网络是否恢复）
+pub async fn is_reachable(config: &SttConfig) -> bool {
+    let provider = normalize_provider(&config.provider);
+    let url = match reqwest::Url::parse(&normalize_base_url(&config.base_url, &provider)) {
+        Ok(url) => url,
+        Err(_) => return false,
+    };
+    let (host, port) = match (url.host_str(), url.port_or_known_default()) {
+        (Some(host), Some(port)) => (host.to_string(), port),
+        _ => return false,
+    };
+
+    matches!(
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            tokio::net::TcpStream::connect((host.as_str(), port)),
+        )
+        .await,
+        Ok(Ok(_))
+    )
+}
+
+/// 转写失败
+///
+/// `transient` 在拿到 `reqwest::Error` / `StatusCode` 的地方判定，决定录音能否进入
+/// 离线队列自动重试：网络不通、超时、429、5xx 为暂时性失败；鉴权失败、模型不存在、
+/// 地区限制等重试也不会成功，需要用户修改配置后手动重试。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscribeError {
+    pub message: String,
+    pub transient: bool,
+}
+
+impl TranscribeError {
+    fn http(status: StatusCode, message: String) -> Self {
+        Self {
+            message,
+            transient: is_transient_status(status),
+        }
+    }
+
+    /// 请求发送或读取响应失败：连接失败、超时、响应体中断属于暂时性失败，
+    /// 构建请求或解析响应失败不是
+    fn request(context: &str, err: reqwest::Error) -> Self {
+        Self {
+            transient: err.is_timeout() || err.is_connect() || err.is_request() || err.is_body(),
+            message: format!("{}: {:?}", context, err),
+        }
+    }
+}
+
+impl From<String> for TranscribeError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            transient: false,
+        }
+    }
+}
+
+impl std::fmt::Display for TranscribeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 /// 转录音频文件
 pub async fn transcribe(file_path: &str, config: &SttConfig) -> Result<String, String> {
+    transcribe_timed(file_path, config, &mut StageTimings::default())
+        .await
+        .map_err(|e| e.message)
+}
+
+/// 转写并记录读文件、编码、上传、首字节与响应各阶段耗时
+pub async fn transcribe_timed(
+    file_path: &str,
+    config: &SttConfig,
+    timings: &mut StageTimings,
+) -> Result<String, TranscribeError> {
     let provider = normalize_provider(&config.provider);
     if provider == PROVIDER_SILICONFLOW {
-        transcribe_with_siliconflow(file_path, config).await
+        transcribe_with_siliconflow(file_path, config, timings).await
     } else {
-        transcribe_with_openrouter(file_path, config).await
+        transcribe_with_openrouter(file_path, config, timings).await
     }
 }
 
-async fn transcribe_with_openrouter(file_path: &str, config: &SttConfig) -> Result<String, String> {
+/// 请求体分块大小，最后一块被读走即视为上传完成
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// 请求体上传完成时刻
+type UploadMark = Arc<Mutex<Option<Instant>>>;
+
+/// 把请求体包装为分块流，在最后一块被读走时记录时刻
+fn timed_body(bytes: Vec<u8>, mark: UploadMark) -> reqwest::Body {
+    let chunks: Vec<Vec<u8>> = bytes
+        .chunks(UPLOAD_CHUNK_SIZE)
+        .map(<[u8]>::to_vec)
+        .collect();
+    let last = chunks.len().saturating_sub(1);
+    let stream =
+        futures_util::stream::iter(chunks.into_iter().enumerate().map(move |(index, chunk)| {
+            if index == last {
+                if let Ok(mut mark) = mark.lock() {
+                    *mark = Some(Instant::now());
+                }
+            }
+            Ok::<_, std::io::Error>(chunk)
+        }));
+    reqwest::Body::wrap_stream(stream)
+}
+
+/// 发送请求并拆分上传与等待首字节的耗时
+async fn send_timed(
+    request: reqwest::RequestBuilder,
+    mark: &UploadMark,
+    timings: &mut StageTimings,
+) -> Result<reqwest::Response, TranscribeError> {
+    let send_started = Instant::now();
+    let response = request
+        .send()
+        .await
+        .map_err(|e| TranscribeError::request("请求失败", e))?;
+    let headers_at = Instant::now();
+
+    match mark.lock().ok().and_then(|mark| *mark) {
+        Some(uploaded_at) if uploaded_at <= headers_at => {
+            timings.request_send_ms = Some((uploaded_at - send_started).as_millis() as u64);
+            timings.first_byte_ms = Some((headers_at - uploaded_at).as_millis() as u64);
+        }
+        _ => timings.first_byte_ms = Some((headers_at - send_started).as_millis() as u64),
+    }
+    Ok(response)
+}
+
+async fn transcribe_with_openrouter(
+    file_path: &str,
+    config: &SttConfig,
+    timings: &mut StageTimings,
+) -> Result<String, TranscribeError> {
     let provider = normalize_provider(&config.provider);
     let model = if config.model.trim().is_empty() {
         default_model_for_provider(&provider)
@@ -532,7 +812,11 @@ async fn transcribe_with_openrouter(file_path: &str, config: &SttConfig) -> Resu
         config.model.trim()
     };
     let api_key = resolve_api_key(config, &provider)?;
+    let read_started = Instant::now();
     let audio_bytes = read_audio_file(file_path)?;
+    timings.file_read_ms = Some(timing::elapsed_ms(read_started));
+
+    let encode_started = Instant::now();
     let audio_base64 = STANDARD.encode(&audio_bytes);
 
     let request_body = serde_json::json!({
@@ -560,6 +844,9 @@ async fn transcribe_with_openrouter(file_path: &str, config: &SttConfig) -> Resu
             }
         ]
     });
+    let body_bytes =
+        serde_json::to_vec(&request_body).map_err(|e| format!("构建请求体失败: {:?}", e))?;
+    timings.encode_ms = Some(timing::elapsed_ms(encode_started));
 
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(120))
@@ -570,16 +857,17 @@ async fn transcribe_with_openrouter(file_path: &str, config: &SttConfig) -> Resu
         "{}/chat/completions",
         normalize_base_url(&config.base_url, &provider)
     );
-    let response = client
+    let upload_mark = UploadMark::default();
+    let request = client
         .post(&url)
         .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
+        .header("Content-Length", body_bytes.len())
         .header("HTTP-Referer", "https://github.com/aitotype")
         .header("X-Title", "AItoType")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("请求失败: {:?}", e))?;
+        .body(timed_body(body_bytes, upload_mark.clone()));
+    let response = send_timed(request, &upload_mark, timings).await?;
+    let response_started = Instant::now();
 
     if !response.status().is_success() {
         let status = response.status();
@@ -588,31 +876,36 @@ async fn transcribe_with_openrouter(file_path: &str, config: &SttConfig) -> Resu
         if lower.contains("user location is not supported")
             || lower.contains("location is not supported")
         {
-            return Err("当前账号路由到 Google AI Studio 且受地区限制。已尝试绕开该 Provider 但仍失败，请在 OpenRouter 控制台为该模型切换可用 Provider（如 Vertex 路由）或使用可用地区网络。".to_string());
+            return Err("当前账号路由到 Google AI Studio 且受地区限制。已尝试绕开该 Provider 但仍失败，请在 OpenRouter 控制台为该模型切换可用 Provider（如 Vertex 路由）或使用可用地区网络。".to_string().into());
         }
-        return Err(format!("API 返回错误 {}: {}", status, error_text));
+        return Err(TranscribeError::http(
+            status,
+            format!("API 返回错误 {}: {}", status, error_text),
+        ));
     }
 
     let result: ChatCompletionResponse = response
         .json()
         .await
-        .map_err(|e| format!("解析响应失败: {:?}", e))?;
+        .map_err(|e| TranscribeError::request("解析响应失败", e))?;
+    timings.response_ms = Some(timing::elapsed_ms(response_started));
 
     if let Some(error) = result.error {
-        return Err(format!("API 错误: {}", error.message));
+        return Err(format!("API 错误: {}", error.message).into());
     }
 
     result
         .choices
         .and_then(|c| c.first().cloned())
         .and_then(|c| extract_text_from_chat_message(&c.message))
-        .ok_or_else(|| "未获取到转录结果".to_string())
+        .ok_or_else(|| "未获取到转录结果".to_string().into())
 }
 
 async fn transcribe_with_siliconflow(
     file_path: &str,
     config: &SttConfig,
-) -> Result<String, String> {
+    timings: &mut StageTimings,
+) -> Result<String, TranscribeError> {
     let provider = PROVIDER_SILICONFLOW.to_string();
     let model = if config.model.trim().is_empty() {
         DEFAULT_SILICONFLOW_MODEL
@@ -620,52 +913,559 @@ async fn transcribe_with_siliconflow(
         config.model.trim()
     };
     let api_key = resolve_api_key(config, &provider)?;
+    let read_started = Instant::now();
     let audio_bytes = read_audio_file(file_path)?;
+    timings.file_read_ms = Some(timing::elapsed_ms(read_started));
     let url = format!(
         "{}/audio/transcriptions",
         normalize_base_url(&config.base_url, &provider)
     );
 
-    let file_part = reqwest::multipart::Part::bytes(audio_bytes)
-        .file_name("audio.wav")
-        .mime_str("audio/wav")
-        .map_err(|e| format!("构建音频请求体失败: {:?}", e))?;
+    let encode_started = Instant::now();
+    let upload_mark = UploadMark::default();
+    let audio_len = audio_bytes.len() as u64;
+    let file_part = reqwest::multipart::Part::stream_with_length(
+        timed_body(audio_bytes, upload_mark.clone()),
+        audio_len,
+    )
+    .file_name("audio.wav")
+    .mime_str("audio/wav")
+    .map_err(|e| format!("构建音频请求体失败: {:?}", e))?;
 
     let form = reqwest::multipart::Form::new()
         .text("model", model.to_string())
         .part("file", file_part);
+    timings.encode_ms = Some(timing::elapsed_ms(encode_started));
 
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(120))
         .build()
         .map_err(|e| format!("创建客户端失败: {:?}", e))?;
 
-    let response = client
+    let request = client
         .post(&url)
         .header("Authorization", format!("Bearer {}", api_key))
-        .multipart(form)
-        .send()
-        .await
-        .map_err(|e| format!("请求失败: {:?}", e))?;
+        .multipart(form);
+    let response = send_timed(request, &upload_mark, timings).await?;
+    let response_started = Instant::now();
 
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "SiliconFlow API 返回错误 {}: {}",
-            status, error_text
+        return Err(TranscribeError::http(
+            status,
+            format!("SiliconFlow API 返回错误 {}: {}", status, error_text),
         ));
     }
 
     let value: serde_json::Value = response
         .json()
         .await
-        .map_err(|e| format!("解析响应失败: {:?}", e))?;
+        .map_err(|e| TranscribeError::request("解析响应失败", e))?;
+    timings.response_ms = Some(timing::elapsed_ms(response_started));
 
     value
         .get("text")
         .and_then(|v| v.as_str())
         .map(|v| v.trim().to_string())
         .filter(|v| !v.is_empty())
-        .ok_or_else(|| format!("未获取到转录结果: {}", value))
+        .ok_or_else(|| format!("未获取到转录结果: {}", value).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_provider::{MockResponse, MockServer};
+    use serde_json::json;
+
+    const AUDIO: &[u8] = b"RIFF\x24\x00\x00\x00WAVEfmt mock-audio";
+
+    fn write_audio(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "aitotype-stt-test-{}-{}.wav",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, AUDIO).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn openrouter_config(server: &MockServer) -> SttConfig {
+        SttConfig {
+            base_url: format!("{}/", server.base_url()),
+            api_key: " sk-or-test ".to_string(),
+            model: "google/gemini-test".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn siliconflow_config(server: &MockServer) -> SttConfig {
+        SttConfig {
+            provider: PROVIDER_SILICONFLOW.to_string(),
+            base_url: server.base_url(),
+            api_key: "sk-sf-test".to_string(),
+            model: "FunAudioLLM/SenseVoiceSmall".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn error_type(message: &str) -> &str {
+        message.split_once('|').map(|(kind, _)| kind).unwrap_or("")
+    }
+
+    #[test]
+    fn classifies_http_errors() {
+        let cases = [
+            (401, "", CONNECTION_ERROR_AUTH_FAILED),
+            (403, "forbidden", CONNECTION_ERROR_AUTH_FAILED),
+            (402, "", CONNECTION_ERROR_QUOTA_EXCEEDED),
+            (429, "", CONNECTION_ERROR_QUOTA_EXCEEDED),
+            (400, "Insufficient credits", CONNECTION_ERROR_QUOTA_EXCEEDED),
+            (400, "账户余额不足", CONNECTION_ERROR_QUOTA_EXCEEDED),
+            (404, "", CONNECTION_ERROR_MODEL_NOT_FOUND),
+            (
+                400,
+                "Model Not Found: foo",
+                CONNECTION_ERROR_MODEL_NOT_FOUND,
+            ),
+            (400, "模型不存在", CONNECTION_ERROR_MODEL_NOT_FOUND),
+            (500, "boom", CONNECTION_ERROR_UNKNOWN),
+        ];
+        for (status, body, expected) in cases {
+            let status = StatusCode::from_u16(status).unwrap();
+            let message = classify_http_error(status, body);
+            assert_eq!(error_type(&message), expected, "{} {}", status, body);
+        }
+        assert_eq!(
+            classify_http_error(StatusCode::INTERNAL_SERVER_ERROR, "boom"),
+            "unknown|API 返回错误 500: boom"
+        );
+        assert_eq!(
+            strip_typed_error_message(classify_http_error(StatusCode::UNAUTHORIZED, "")),
+            "鉴权失败，请检查 API Key 是否正确"
+        );
+    }
+
+    #[test]
+    fn classifies_transient_statuses() {
+        for code in [429, 500, 502, 503] {
+            let status = StatusCode::from_u16(code).unwrap();
+            assert!(
+                TranscribeError::http(status, String::new()).transient,
+                "{}",
+                code
+            );
+        }
+        for code in [400, 401, 403, 404] {
+            let status = StatusCode::from_u16(code).unwrap();
+            assert!(
+                !TranscribeError::http(status, String::new()).transient,
+                "{}",
+                code
+            );
+        }
+        assert!(!TranscribeError::from("打开文件失败: NotFound".to_string()).transient);
+    }
+
+    #[test]
+    fn extracts_text_from_chat_messages() {
+        let message = |content: serde_json::Value| ChatMessage {
+            content: Some(content),
+        };
+        assert_eq!(
+            extract_text_from_chat_message(&message(json!("  你好  "))),
+            Some("你好".to_string())
+        );
+        assert_eq!(
+            extract_text_from_chat_message(&message(json!([
+                { "type": "text", "text": " 第一段 " },
+                { "type": "image_url", "image_url": {} },
+                { "type": "text", "text": "" },
+                { "type": "text", "text": "第二段" }
+            ]))),
+            Some("第一段\n第二段".to_string())
+        );
+        assert_eq!(extract_text_from_chat_message(&message(json!("   "))), None);
+        assert_eq!(extract_text_from_chat_message(&message(json!(42))), None);
+        assert_eq!(
+            extract_text_from_chat_message(&ChatMessage { content: None }),
+            None
+        );
+    }
+
+    #[test]
+    fn renders_enhancement_prompt() {
+        assert_eq!(
+            render_enhancement_prompt("  润色：{text}  ", "原文"),
+            "润色：原文"
+        );
+        let fallback = render_enhancement_prompt("没有占位符", "原文");
+        assert_eq!(
+            fallback,
+            default_enhancement_prompt().replace("{text}", "原文")
+        );
+        assert_eq!(render_enhancement_prompt("", "原文"), fallback);
+    }
+
+    /// 环境变量是进程级状态，相关断言集中在一个测试里
+    #[test]
+    fn resolves_api_keys() {
+        let config = SttConfig {
+            api_key: " stt-key ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_api_key(&config, PROVIDER_OPENROUTER).unwrap(),
+            "stt-key"
+        );
+        // 同一 Provider 的润色复用 STT Key，不同 Provider 不复用
+        assert_eq!(
+            resolve_enhancement_api_key(&config, PROVIDER_OPENROUTER).unwrap(),
+            "stt-key"
+        );
+        let with_enhancement_key = SttConfig {
+            enhancement_api_key: "enhance-key".to_string(),
+            ..config.clone()
+        };
+        assert_eq!(
+            resolve_enhancement_api_key(&with_enhancement_key, PROVIDER_SILICONFLOW).unwrap(),
+            "enhance-key"
+        );
+
+        std::env::remove_var("SILICONFLOW_API_KEY");
+        let error = resolve_enhancement_api_key(&config, PROVIDER_SILICONFLOW).unwrap_err();
+        assert!(error.contains("SILICONFLOW_API_KEY"), "{}", error);
+
+        std::env::set_var("SILICONFLOW_API_KEY", " env-key ");
+        assert_eq!(
+            resolve_enhancement_api_key(&config, PROVIDER_SILICONFLOW).unwrap(),
+            "env-key"
+        );
+        let empty = SttConfig {
+            provider: PROVIDER_SILICONFLOW.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_api_key(&empty, PROVIDER_SILICONFLOW).unwrap(),
+            "env-key"
+        );
+        std::env::remove_var("SILICONFLOW_API_KEY");
+        assert!(resolve_api_key(&empty, PROVIDER_SILICONFLOW).is_err());
+    }
+
+    #[tokio::test]
+    async fn openrouter_transcription_sends_input_audio() {
+        let server = MockServer::builder()
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::chat(json!([{ "type": "text", "text": " 转写结果 " }])),
+            )
+            .start()
+            .await;
+        let audio = write_audio("openrouter");
+        let mut timings = StageTimings::default();
+
+        let text = transcribe_timed(&audio, &openrouter_config(&server), &mut timings).await;
+        let _ = std::fs::remove_file(&audio);
+        assert_eq!(text.unwrap(), "转写结果");
+        assert!(timings.file_read_ms.is_some() && timings.response_ms.is_some());
+
+        let request = server.single_request();
+        assert_eq!(request.header("authorization"), Some("Bearer sk-or-test"));
+        assert_eq!(request.header("x-title"), Some("AItoType"));
+        assert_eq!(
+            request.json(),
+            json!({
+                "model": "google/gemini-test",
+                "provider": {
+                    "allow_fallbacks": true,
+                    "ignore": ["Google AI Studio"]
+                },
+                "messages": [{
+                    "role": "user",
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": "请精准转录这段音频内容。保持原义，不要翻译，如果是中文就直接输出中文。只输出转录文字，不要输出任何解释。"
+                        },
+                        {
+                            "type": "input_audio",
+                            "input_audio": { "data": STANDARD.encode(AUDIO), "format": "wav" }
+                        }
+                    ]
+                }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn openrouter_transcription_reports_errors() {
+        let server = MockServer::builder()
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::json(
+                    400,
+                    json!({ "error": { "message": "User location is not supported" } }),
+                ),
+            )
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::text(500, "upstream down"),
+            )
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::json(200, json!({ "error": { "message": "model overloaded" } })),
+            )
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::chat(json!("   ")),
+            )
+            .start()
+            .await;
+        let audio = write_audio("openrouter-errors");
+        let config = openrouter_config(&server);
+
+        let mut errors = Vec::new();
+        for _ in 0..4 {
+            let mut timings = StageTimings::default();
+            errors.push(
+                transcribe_timed(&audio, &config, &mut timings)
+                    .await
+                    .unwrap_err(),
+            );
+        }
+        let _ = std::fs::remove_file(&audio);
+        let results: Vec<&str> = errors.iter().map(|err| err.message.as_str()).collect();
+
+        assert!(results[0].contains("Google AI Studio"), "{}", results[0]);
+        assert_eq!(
+            results[1],
+            "API 返回错误 500 Internal Server Error: upstream down"
+        );
+        assert_eq!(results[2], "API 错误: model overloaded");
+        assert_eq!(results[3], "未获取到转录结果");
+        assert_eq!(server.requests().len(), 4);
+        let transient: Vec<bool> = errors.iter().map(|err| err.transient).collect();
+        assert_eq!(transient, vec![false, true, false, false]);
+    }
+
+    #[tokio::test]
+    async fn siliconflow_transcription_uploads_multipart() {
+        let server = MockServer::builder()
+            .on(
+                "POST",
+                "/v1/audio/transcriptions",
+                MockResponse::json(200, json!({ "text": " 硅基转写 " })),
+            )
+            .on(
+                "POST",
+                "/v1/audio/transcriptions",
+                MockResponse::json(200, json!({ "text": "" })),
+            )
+            .on(
+                "POST",
+                "/v1/audio/transcriptions",
+                MockResponse::text(401, "invalid token"),
+            )
+            .start()
+            .await;
+        let audio = write_audio("siliconflow");
+        let config = siliconflow_config(&server);
+
+        let first = transcribe(&audio, &config).await;
+        let second = transcribe(&audio, &config).await;
+        let third = transcribe_timed(&audio, &config, &mut StageTimings::default()).await;
+        let _ = std::fs::remove_file(&audio);
+
+        assert_eq!(first.unwrap(), "硅基转写");
+        assert!(second.unwrap_err().starts_with("未获取到转录结果"));
+        let third = third.unwrap_err();
+        assert_eq!(
+            third.message,
+            "SiliconFlow API 返回错误 401 Unauthorized: invalid token"
+        );
+        assert!(!third.transient);
+
+        let request = server.requests().remove(0);
+        assert_eq!(request.header("authorization"), Some("Bearer sk-sf-test"));
+        assert!(request
+            .header("content-type")
+            .unwrap()
+            .starts_with("multipart/form-data"));
+        assert_eq!(
+            request.multipart_field("model").unwrap(),
+            b"FunAudioLLM/SenseVoiceSmall"
+        );
+        assert_eq!(request.multipart_field("file").unwrap(), AUDIO);
+    }
+
+    #[tokio::test]
+    async fn siliconflow_connection_test_checks_models() {
+        let models = json!({
+            "object": "list",
+            "data": [{ "id": "funaudiollm/sensevoicesmall" }, { "id": "TeleAI/TeleSpeechASR" }]
+        });
+        let server = MockServer::builder()
+            .on("GET", "/v1/models", MockResponse::json(200, models))
+            .on(
+                "GET",
+                "/v1/models",
+                MockResponse::json(200, json!({ "data": [{ "id": "other" }] })),
+            )
+            .on("GET", "/v1/models", MockResponse::text(401, "unauthorized"))
+            .on("GET", "/v1/models", MockResponse::text(429, "slow down"))
+            .start()
+            .await;
+        let config = siliconflow_config(&server);
+
+        assert!(test_connection(&config).await.is_ok());
+        let mut errors = Vec::new();
+        for _ in 0..3 {
+            errors.push(test_connection(&config).await.unwrap_err());
+        }
+        assert_eq!(error_type(&errors[0]), CONNECTION_ERROR_MODEL_NOT_FOUND);
+        assert_eq!(error_type(&errors[1]), CONNECTION_ERROR_AUTH_FAILED);
+        assert_eq!(error_type(&errors[2]), CONNECTION_ERROR_QUOTA_EXCEEDED);
+
+        let request = server.requests().remove(0);
+        assert_eq!(request.header("authorization"), Some("Bearer sk-sf-test"));
+        assert!(request.body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn openrouter_connection_test_classifies_errors() {
+        let server = MockServer::builder()
+            .on("POST", "/v1/chat/completions", MockResponse::chat(json!("h")))
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::json(
+                    402,
+                    json!({ "error": { "message": "Insufficient credits" } }),
+                ),
+            )
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::json(
+                    400,
+                    json!({ "error": { "message": "google/gemini-test is not a valid model ID, model not found" } }),
+                ),
+            )
+            .start()
+            .await;
+        let config = openrouter_config(&server);
+
+        assert!(test_connection(&config).await.is_ok());
+        let quota = test_connection(&config).await.unwrap_err();
+        let model = test_connection(&config).await.unwrap_err();
+        assert_eq!(error_type(&quota), CONNECTION_ERROR_QUOTA_EXCEEDED);
+        assert_eq!(error_type(&model), CONNECTION_ERROR_MODEL_NOT_FOUND);
+
+        assert_eq!(
+            server.requests()[0].json(),
+            json!({
+                "model": "google/gemini-test",
+                "messages": [{ "role": "user", "content": "hi" }],
+                "max_tokens": 1,
+                "temperature": 0
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn enhancement_sends_rendered_prompt() {
+        let server = MockServer::builder()
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::chat(json!("润色后的文本")),
+            )
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::json(200, json!({ "error": { "message": "context too long" } })),
+            )
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                MockResponse::text(401, "bad key"),
+            )
+            .start()
+            .await;
+        let config = SttConfig {
+            api_key: "sk-stt".to_string(),
+            enhancement_enabled: true,
+            enhancement_provider: PROVIDER_SILICONFLOW.to_string(),
+            enhancement_base_url: server.base_url(),
+            enhancement_api_key: "sk-enhance".to_string(),
+            enhancement_model: "Qwen/Qwen-test".to_string(),
+            enhancement_prompt: "润色：{text}".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            enhance_text("原始文本", &config).await.unwrap(),
+            "润色后的文本"
+        );
+        assert_eq!(
+            enhance_text("原始文本", &config).await.unwrap_err(),
+            "Enhancement API 错误: context too long"
+        );
+        assert_eq!(
+            enhance_text("原始文本", &config).await.unwrap_err(),
+            "鉴权失败，请检查 API Key 是否正确"
+        );
+
+        let request = server.requests().remove(0);
+        assert_eq!(request.header("authorization"), Some("Bearer sk-enhance"));
+        assert_eq!(
+            request.json(),
+            json!({
+                "model": "Qwen/Qwen-test",
+                "messages": [{ "role": "user", "content": "润色：原始文本" }],
+                "temperature": 0.2,
+                "max_tokens": 1024
+            })
+        );
+
+        // 未启用或空文本时不发请求
+        let disabled = SttConfig {
+            enhancement_enabled: false,
+            ..config
+        };
+        assert_eq!(
+            enhance_text("原始文本", &disabled).await.unwrap(),
+            "原始文本"
+        );
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn connection_refused_is_network_failure() {
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let config = SttConfig {
+            base_url: format!("http://127.0.0.1:{}/v1", port),
+            api_key: "sk-test".to_string(),
+            ..Default::default()
+        };
+        let error = test_connection(&config).await.unwrap_err();
+        assert_eq!(error_type(&error), CONNECTION_ERROR_NETWORK_FAILED);
+        assert!(!is_reachable(&config).await);
+
+        let audio = write_audio("refused");
+        let error = transcribe_timed(&audio, &config, &mut StageTimings::default())
+            .await
+            .unwrap_err();
+        let _ = std::fs::remove_file(&audio);
+        assert!(error.transient, "{}", error);
+    }
 }
@@ -0,0 +1,90 @@
+//! 转写流程计时模块
+//!
+//! 记录一次听写从停止录音到输出文字的各阶段耗时，写入日志并以调试事件发出，
+//! 用于定位“为什么这次很慢”。
+
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// 各阶段耗时（毫秒），未经过的阶段为空
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageTimings {
+    /// 停止录音到录音线程写完 WAV
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_ms: Option<u64>,
+    /// 其中 WAV finalize 耗时
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finalize_ms: Option<u64>,
+    /// 读取录音文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_read_ms: Option<u64>,
+    /// 构建请求体（Base64 / JSON / multipart）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encode_ms: Option<u64>,
+    /// 开始发送到请求体上传完成
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_send_ms: Option<u64>,
+    /// 上传完成到收到响应头（服务商处理时间）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_byte_ms: Option<u64>,
+    /// 收到响应头到读完并解析响应体
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_ms: Option<u64>,
+    /// 两轮易错词替换（含保存命中次数）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrections_ms: Option<u64>,
+    /// LLM 润色
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enhancement_ms: Option<u64>,
+    /// 后处理脚本 / Webhook
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_process_ms: Option<u64>,
+    /// 粘贴输出
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_ms: Option<u64>,
+    /// 停止录音到得到最终文本（不含输出）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_ms: Option<u64>,
+}
+
+impl StageTimings {
+    /// STT 请求耗时：上传、等待首字节与读取响应之和，未发出请求时为空
+    pub fn stt_request_ms(&self) -> Option<u64> {
+        [self.request_send_ms, self.first_byte_ms, self.response_ms]
+            .into_iter()
+            .flatten()
+            .reduce(|total, ms| total + ms)
+    }
+}
+
+/// 自 `since` 起经过的毫秒数
+pub fn elapsed_ms(since: Instant) -> u64 {
+    since.elapsed().as_millis() as u64
+}
+
+/// 累加到某个阶段（同一阶段可能分多次执行）
+pub fn add_ms(slot: &mut Option<u64>, ms: u64) {
+    *slot = Some(slot.unwrap_or(0) + ms);
+}
+
+/// 一次转写流程的计时器
+pub struct PipelineTimer {
+    started: Instant,
+    pub timings: StageTimings,
+}
+
+impl PipelineTimer {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            timings: StageTimings::default(),
+        }
+    }
+
+    /// 结束计时，返回填入总耗时的各阶段耗时
+    pub fn finish(&self) -> StageTimings {
+        let mut timings = self.timings.clone();
+        timings.total_ms = Some(elapsed_ms(self.started));
+        timings
+    }
+}
@@ -22,6 +22,7 @@ const ENHANCEMENT_API_KEY_STORAGE_KEYS = {
   [PROVIDER_OPENROUTER]: 'aitotype_api_key_enhancement_openrouter',
   [PROVIDER_SILICONFLOW]: 'aitotype_api_key_enhancement_siliconflow'
 };
+const MASKED_API_KEY_PREFIX = '••••';
 const DEFAULT_SHORTCUT = /windows/i.test(navigator.userAgent || navigator.platform || '')
   ? 'Ctrl+Shift+Space'
   : 'Alt+Space';
@@ -33,7 +34,15 @@ const SHORTCUT_MODIFIER_SET = new Set(SHORTCUT_MODIFIER_ORDER);
 const state = {
   status: 'idle',
   lastResult: '',
+  configResetNoticeShown: false,
+  activeProfile: '',
+  configPolicy: null,
+  controlApiInfo: null,
   history: [],
+  historyTotal: 0,
+  historyHasMore: false,
+  historySearchTimer: null,
+  queuedJobs: [],
   audioLevelTimer: null,
   shortcutUnlisten: null,
   shortcutCaptureActive: false,
@@ -129,8 +138,50 @@ const el = {
   correctionAddCorrect: document.getElementById('correction-add-correct'),
   correctionAddBtn: document.getElementById('correction-add-btn'),
   logDirPath: document.getElementById('log-dir-path'),
+  audioArchiveSwitch: document.getElementById('audio-archive-switch'),
+  audioArchiveMaxAgeInput: document.getElementById('audio-archive-max-age-input'),
+  audioArchiveMaxSizeInput: document.getElementById('audio-archive-max-size-input'),
+  logEnabledSwitch: document.getElementById('log-enabled-switch'),
+  logMaxAgeInput: document.getElementById('log-max-age-input'),
+  logMaxSizeInput: document.getElementById('log-max-size-input'),
+  logCompressSwitch: document.getElementById('log-compress-switch'),
+  redactEmailSelect: document.getElementById('redact-email-select'),
+  redactPhoneSelect: document.getElementById('redact-phone-select'),
+  redactCreditCardSelect: document.getElementById('redact-credit-card-select'),
+  redactIbanSelect: document.getElementById('redact-iban-select'),
+  redactApiKeySelect: document.getElementById('redact-api-key-select'),
+  redactCustomSelect: document.getElementById('redact-custom-select'),
+  redactCustomInput: document.getElementById('redact-custom-input'),
+  itnSwitch: document.getElementById('itn-switch'),
+  itnRules: document.getElementById('itn-rules'),
+  postProcessModeSelect: document.getElementById('post-process-mode-select'),
+  postProcessTargetInput: document.getElementById('post-process-target-input'),
+  postProcessTimeoutInput: document.getElementById('post-process-timeout-input'),
+  wipeLogsBtn: document.getElementById('wipe-logs-btn'),
+  diagnosticLogLevelSelect: document.getElementById('diagnostic-log-level-select'),
+  viewDiagnosticLogsBtn: document.getElementById('view-diagnostic-logs-btn'),
+  copyDiagnosticLogsBtn: document.getElementById('copy-diagnostic-logs-btn'),
+  diagnosticLogsViewer: document.getElementById('diagnostic-logs-viewer'),
+  createDiagnosticsBundleBtn: document.getElementById('create-diagnostics-bundle-btn'),
+  diagnosticsBundleStatus: document.getElementById('diagnostics-bundle-status'),
+  settingsIncludeKeysSwitch: document.getElementById('settings-include-keys-switch'),
+  exportSettingsBtn: document.getElementById('export-settings-btn'),
+  importSettingsBtn: document.getElementById('import-settings-btn'),
+  importSettingsFile: document.getElementById('import-settings-file'),
+  settingsTransferStatus: document.getElementById('settings-transfer-status'),
+  controlApiSwitch: document.getElementById('control-api-switch'),
+  controlApiPortInput: document.getElementById('control-api-port-input'),
+  controlApiStatus: document.getElementById('control-api-status'),
+  copyControlTokenBtn: document.getElementById('copy-control-token-btn'),
+  regenerateControlTokenBtn: document.getElementById('regenerate-control-token-btn'),
   openLogDirBtn: document.getElementById('open-log-dir-btn'),
   settingsForm: document.getElementById('settings-form'),
+  profileSelect: document.getElementById('profile-select'),
+  profileActivateBtn: document.getElementById('profile-activate-btn'),
+  profileNewBtn: document.getElementById('profile-new-btn'),
+  profileRenameBtn: document.getElementById('profile-rename-btn'),
+  profileDeleteBtn: document.getElementById('profile-delete-btn'),
+  policyHint: document.getElementById('policy-hint'),
   settingsStatus: document.getElementById('settings-status'),
   settingsSaveBtn: document.querySelector('#settings-form button[type="submit"]'),
 
@@ -138,12 +189,20 @@ const el = {
   shortcutRecorder: document.getElementById('shortcut-recorder'),
   shortcutLabel: document.getElementById('shortcut-label'),
   shortcutHint: document.getElementById('shortcut-hint'),
+  cancelShortcutInput: document.getElementById('cancel-shortcut-input'),
 
   // Recording Mode
   recordModeSwitch: document.getElementById('record-mode-switch'),
 
   // History
-  historyContainer: document.getElementById('history-container')
+  historyContainer: document.getElementById('history-container'),
+  historySearchInput: document.getElementById('history-search-input'),
+  historyStartDate: document.getElementById('history-start-date'),
+  historyEndDate: document.getElementById('history-end-date'),
+  historyExportFormat: document.getElementById('history-export-format'),
+  historyExportBtn: document.getElementById('history-export-btn'),
+  historyStats: document.getElementById('history-stats'),
+  queueContainer: document.getElementById('queue-container')
 };
 
 // ============ Spotlight Effect ============
@@ -288,6 +347,18 @@ function safeHideOverlay() {
   });
 }
 
+function isCancelledError(e) {
+  return String(e || '').startsWith('cancelled|');
+}
+
+function handleTranscriptionCancelled() {
+  updateStatus('idle', 'Cancelled');
+  if (state.backgroundSession) {
+    safeHideOverlay();
+  }
+  state.backgroundSession = false;
+}
+
 // ============ Recorder Logic ============
 async function toggleRecording() {
   if (state.status === 'transcribing') return;
@@ -302,7 +373,7 @@ async function toggleRecording() {
       const result = await invoke('stop_and_transcribe');
 
       state.lastResult = result;
-      addToHistory(result);
+      addToHistory();
 
       // Auto-Copy
       if (el.autoCopySwitch && el.autoCopySwitch.checked) {
@@ -331,6 +402,10 @@ async function toggleRecording() {
       }
       state.backgroundSession = false;
     } catch (e) {
+      if (isCancelledError(e)) {
+        handleTranscriptionCancelled();
+        return;
+      }
       console.error(e);
       updateStatus('error', e.toString());
       if (state.backgroundSession) {
@@ -394,7 +469,8 @@ async function startRecordingOnly(background) {
   }
 }
 
-async function stopAndTranscribeOnly() {
+// paste 为 false 时只转写不粘贴（本地控制接口要求直接返回文本）
+async function stopAndTranscribeOnly(paste = true) {
   if (state.status !== 'recording') return;
 
   const holdDuration = Date.now() - (state.holdStartedAt || 0);
@@ -416,14 +492,14 @@ async function stopAndTranscribeOnly() {
     const result = await invoke('stop_and_transcribe');
 
     state.lastResult = result;
-    addToHistory(result);
+    addToHistory();
 
     if (el.autoCopySwitch && el.autoCopySwitch.checked) {
       await copyResultToClipboard(result);
     }
 
     const isAutoWriteEnabled = state.sttConfig?.auto_write || (el.autoWriteSwitch && el.autoWriteSwitch.checked);
-    if (state.backgroundSession || isAutoWriteEnabled) {
+    if (paste && (state.backgroundSession || isAutoWriteEnabled)) {
       if (state.backgroundSession) {
         safeHideOverlay();
       }
@@ -442,6 +518,10 @@ async function stopAndTranscribeOnly() {
     }
     state.backgroundSession = false;
   } catch (e) {
+    if (isCancelledError(e)) {
+      handleTranscriptionCancelled();
+      return;
+    }
     console.error(e);
     updateStatus('error', e.toString());
     if (state.backgroundSession) {
@@ -985,16 +1065,192 @@ async function initUpdateListener() {
 }
 
 // ============ History ============
-function addToHistory(text) {
-  const time = new Date().toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' });
-  state.history.unshift({ time, text });
-  if (state.history.length > 20) state.history.pop();
+const HISTORY_PAGE_SIZE = 20;
+
+function buildHistoryQuery(offset) {
+  return {
+    query: el.historySearchInput?.value.trim() || null,
+    start_date: el.historyStartDate?.value || null,
+    end_date: el.historyEndDate?.value || null,
+    offset,
+    limit: HISTORY_PAGE_SIZE
+  };
+}
+
+async function loadHistory({ append = false } = {}) {
+  const offset = append ? state.history.length : 0;
+  try {
+    const page = await invoke('list_history', { query: buildHistoryQuery(offset) });
+    const items = Array.isArray(page?.items) ? page.items : [];
+    state.history = append ? state.history.concat(items) : items;
+    state.historyTotal = page?.total || 0;
+    state.historyHasMore = Boolean(page?.has_more);
+  } catch (e) {
+    console.error('Load history failed', e);
+    if (!append) state.history = [];
+    state.historyHasMore = false;
+  }
   renderHistory();
+  if (!append) loadStats();
+}
+
+async function loadStats() {
+  if (!el.historyStats) return;
+  const range = {
+    start_date: el.historyStartDate?.value || null,
+    end_date: el.historyEndDate?.value || null
+  };
+  try {
+    const stats = await invoke('get_stats', { range });
+    renderStats(stats);
+  } catch (e) {
+    console.error('Load stats failed', e);
+    el.historyStats.classList.add('hidden');
+  }
+}
+
+function renderStats(stats) {
+  if (!stats || !stats.dictations) {
+    el.historyStats.classList.add('hidden');
+    return;
+  }
+  const parts = [
+    `${stats.dictations} 次听写`,
+    `${stats.words} 字词`,
+    `录音 ${stats.audio_minutes.toFixed(1)} 分钟`,
+    `约节省 ${stats.time_saved_minutes.toFixed(1)} 分钟打字`
+  ];
+  if (stats.enhancement?.attempts) {
+    parts.push(`润色成功率 ${Math.round(stats.enhancement.success_rate * 100)}%`);
+  }
+  const latency = (stats.provider_latency || [])
+    .map((item) => `${item.stage === 'stt' ? 'STT' : '润色'} ${item.provider} ${item.average_ms}ms`)
+    .join(' / ');
+  if (latency) parts.push(`平均耗时 ${latency}`);
+  const top = (stats.top_corrections || [])
+    .slice(0, 3)
+    .map((item) => `${item.variant}→${item.correct}×${item.hits}`)
+    .join('，');
+  if (top) parts.push(`常见纠错 ${top}`);
+
+  el.historyStats.textContent = parts.join(' · ');
+  el.historyStats.classList.remove('hidden');
+}
+
+function scheduleHistorySearch() {
+  if (state.historySearchTimer) clearTimeout(state.historySearchTimer);
+  state.historySearchTimer = setTimeout(() => {
+    state.historySearchTimer = null;
+    loadHistory();
+  }, 250);
+}
+
+async function exportHistory() {
+  const options = {
+    format: el.historyExportFormat?.value || 'markdown',
+    start_date: el.historyStartDate?.value || null,
+    end_date: el.historyEndDate?.value || null
+  };
+  try {
+    const result = await invoke('export_history', { options });
+    showCorrectionToast(`已导出 ${result.count} 条记录到 ${result.path}`, 'success');
+  } catch (e) {
+    showCorrectionToast(String(e), 'error');
+  }
+}
+
+function addToHistory() {
+  // 日志已由后端写入，重新加载第一页即可
+  loadHistory();
+}
+
+function formatHistoryTime(timestamp) {
+  const date = new Date(timestamp);
+  if (Number.isNaN(date.getTime())) return timestamp || '';
+  const sameDay = date.toDateString() === new Date().toDateString();
+  return sameDay
+    ? date.toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })
+    : date.toLocaleString([], { month: '2-digit', day: '2-digit', hour: '2-digit', minute: '2-digit' });
+}
+
+async function loadQueuedJobs() {
+  try {
+    state.queuedJobs = await invoke('list_queued_transcriptions');
+  } catch (e) {
+    console.error('Load queued transcriptions failed', e);
+    state.queuedJobs = [];
+  }
+  renderQueuedJobs();
+}
+
+function renderQueuedJobs() {
+  if (!el.queueContainer) return;
+  const jobs = Array.isArray(state.queuedJobs) ? state.queuedJobs : [];
+  el.queueContainer.classList.toggle('hidden', jobs.length === 0);
+  el.queueContainer.innerHTML = '';
+  if (jobs.length === 0) return;
+
+  const title = document.createElement('div');
+  title.className = 'queue-title';
+  title.textContent = `${jobs.length} 条录音转写失败，等待重试`;
+  el.queueContainer.appendChild(title);
+
+  jobs.forEach((job) => {
+    const card = document.createElement('div');
+    card.className = 'queue-card';
+
+    const time = document.createElement('span');
+    time.className = 'history-time';
+    time.textContent = new Date(job.created_at).toLocaleString([], {
+      month: '2-digit', day: '2-digit', hour: '2-digit', minute: '2-digit'
+    });
+
+    const error = document.createElement('span');
+    error.className = 'queue-error';
+    error.textContent = job.last_error || '';
+    error.title = job.last_error || '';
+
+    const retryBtn = document.createElement('button');
+    retryBtn.type = 'button';
+    retryBtn.className = 'text-link';
+    retryBtn.textContent = 'Retry';
+    retryBtn.addEventListener('click', async () => {
+      retryBtn.disabled = true;
+      try {
+        await invoke('retry_queued_transcription', { id: job.id });
+      } catch (e) {
+        console.error('Retry queued transcription failed', e);
+      }
+      await loadQueuedJobs();
+    });
+
+    const deleteBtn = document.createElement('button');
+    deleteBtn.type = 'button';
+    deleteBtn.className = 'text-link';
+    deleteBtn.textContent = 'Delete';
+    deleteBtn.addEventListener('click', async () => {
+      try {
+        await invoke('delete_queued_transcription', { id: job.id });
+      } catch (e) {
+        console.error('Delete queued transcription failed', e);
+      }
+      await loadQueuedJobs();
+    });
+
+    card.appendChild(time);
+    card.appendChild(error);
+    card.appendChild(retryBtn);
+    card.appendChild(deleteBtn);
+    el.queueContainer.appendChild(card);
+  });
 }
 
 function renderHistory() {
   if (state.history.length === 0) {
-    el.historyContainer.innerHTML = '<div class="empty-state">No recordings yet</div>';
+    const hasFilter = Boolean(
+      el.historySearchInput?.value.trim() || el.historyStartDate?.value || el.historyEndDate?.value
+    );
+    el.historyContainer.innerHTML = `<div class="empty-state">${hasFilter ? 'No matching recordings' : 'No recordings yet'}</div>`;
     return;
   }
 
@@ -1002,34 +1258,87 @@ function renderHistory() {
   const fragment = document.createDocumentFragment();
 
   state.history.forEach((item) => {
-    const card = document.createElement('button');
-    card.type = 'button';
+    const card = document.createElement('div');
     card.className = 'history-card';
+    card.classList.toggle('pinned', Boolean(item.pinned));
+    card.tabIndex = 0;
+    card.setAttribute('role', 'button');
     card.title = 'Click to copy';
 
-    const time = document.createElement('div');
+    const meta = document.createElement('div');
+    meta.className = 'history-meta';
+
+    const time = document.createElement('span');
     time.className = 'history-time';
-    time.textContent = item.time;
+    time.textContent = formatHistoryTime(item.timestamp);
+
+    const pinBtn = document.createElement('button');
+    pinBtn.type = 'button';
+    pinBtn.className = 'text-link';
+    pinBtn.textContent = item.pinned ? 'Unpin' : 'Pin';
+    pinBtn.addEventListener('click', async (event) => {
+      event.stopPropagation();
+      try {
+        await invoke('set_history_pinned', { logId: item.id, pinned: !item.pinned });
+      } catch (e) {
+        console.error('Pin history entry failed', e);
+      }
+      await loadHistory();
+    });
+
+    const deleteBtn = document.createElement('button');
+    deleteBtn.type = 'button';
+    deleteBtn.className = 'text-link';
+    deleteBtn.textContent = 'Delete';
+    deleteBtn.addEventListener('click', async (event) => {
+      event.stopPropagation();
+      try {
+        await invoke('delete_history_entry', { logId: item.id });
+      } catch (e) {
+        console.error('Delete history entry failed', e);
+      }
+      await loadHistory();
+    });
+
+    meta.appendChild(time);
+    meta.appendChild(pinBtn);
+    meta.appendChild(deleteBtn);
 
     const text = document.createElement('div');
     text.className = 'history-text';
-    text.textContent = item.text;
+    text.textContent = item.final_text;
 
-    card.appendChild(time);
+    card.appendChild(meta);
     card.appendChild(text);
 
-    card.addEventListener('click', async () => {
-      const copied = await copyResultToClipboard(item.text);
+    const copyItem = async () => {
+      const copied = await copyResultToClipboard(item.final_text);
       if (!copied) return;
       card.classList.add('copied');
       setTimeout(() => card.classList.remove('copied'), 450);
+    };
+    card.addEventListener('click', copyItem);
+    card.addEventListener('keydown', (event) => {
+      if (event.target !== card) return;
+      if (event.key === 'Enter' || event.key === ' ') {
+        event.preventDefault();
+        copyItem();
+      }
     });
 
     fragment.appendChild(card);
   });
 
-  el.historyContainer.appendChild(fragment);
+  if (state.historyHasMore) {
+    const moreBtn = document.createElement('button');
+    moreBtn.type = 'button';
+    moreBtn.className = 'text-link history-load-more';
+    moreBtn.textContent = `Load more (${state.history.length}/${state.historyTotal})`;
+    moreBtn.addEventListener('click', () => loadHistory({ append: true }));
+    fragment.appendChild(moreBtn);
+  }
 
+  el.historyContainer.appendChild(fragment);
 }
 
 // ============ Settings ============
@@ -1055,6 +1364,10 @@ function enhancementKeyStorageKey(provider) {
   return ENHANCEMENT_API_KEY_STORAGE_KEYS[normalizeProvider(provider)];
 }
 
+function isMaskedApiKey(value) {
+  return typeof value === 'string' && value.startsWith(MASKED_API_KEY_PREFIX);
+}
+
 function getStoredApiKey(provider) {
   const storageKey = keyStorageKey(provider);
   if (!storageKey) return '';
@@ -1064,7 +1377,7 @@ function getStoredApiKey(provider) {
 function setStoredApiKey(provider, value) {
   const storageKey = keyStorageKey(provider);
   if (!storageKey) return;
-  if (!value) {
+  if (!value || isMaskedApiKey(value)) {
     localStorage.removeItem(storageKey);
     return;
   }
@@ -1080,7 +1393,7 @@ function getStoredEnhancementApiKey(provider) {
 function setStoredEnhancementApiKey(provider, value) {
   const storageKey = enhancementKeyStorageKey(provider);
   if (!storageKey) return;
-  if (!value) {
+  if (!value || isMaskedApiKey(value)) {
     localStorage.removeItem(storageKey);
     return;
   }
@@ -1181,6 +1494,8 @@ function buildSttConfigFromUi() {
   const enhancementPrompt = (el.enhancementPromptInput?.value || '').trim() || DEFAULT_ENHANCEMENT_PROMPT;
 
   return {
+    // Keep fields that have no UI control so saving does not reset them.
+    ...(state.sttConfig || {}),
     provider,
     api_key: apiKey,
     model,
@@ -1192,10 +1507,143 @@ function buildSttConfigFromUi() {
     enhancement_base_url: '',
     enhancement_api_key: enhancementApiKey,
     enhancement_model: enhancementModel,
-    enhancement_prompt: enhancementPrompt
+    enhancement_prompt: enhancementPrompt,
+    audio_archive_enabled: el.audioArchiveSwitch ? el.audioArchiveSwitch.checked : false,
+    audio_archive_max_age_days: parseNonNegativeInt(el.audioArchiveMaxAgeInput?.value, 30),
+    audio_archive_max_total_mb: parseNonNegativeInt(el.audioArchiveMaxSizeInput?.value, 1024),
+    log_enabled: el.logEnabledSwitch ? el.logEnabledSwitch.checked : true,
+    log_max_age_days: parseNonNegativeInt(el.logMaxAgeInput?.value, 0),
+    log_max_total_mb: parseNonNegativeInt(el.logMaxSizeInput?.value, 0),
+    log_compress_old_files: el.logCompressSwitch ? el.logCompressSwitch.checked : false,
+    redaction: buildRedactionConfigFromUi(),
+    post_process: buildPostProcessConfigFromUi(),
+    itn: buildItnConfigFromUi(),
+    // Not editable in the UI; keep the profile's stage order when saving.
+    pipeline_stages: state.sttConfig?.pipeline_stages || [],
+    diagnostic_log_level: el.diagnosticLogLevelSelect?.value || 'info',
+    control_api_enabled: el.controlApiSwitch ? el.controlApiSwitch.checked : false,
+    control_api_port: parseNonNegativeInt(el.controlApiPortInput?.value, 47631) || 47631
   };
 }
 
+// The single target input holds the command or the webhook URL depending on the mode.
+function buildPostProcessConfigFromUi() {
+  const mode = el.postProcessModeSelect?.value || 'off';
+  const target = (el.postProcessTargetInput?.value || '').trim();
+  const saved = state.sttConfig?.post_process || {};
+  return {
+    mode,
+    command: mode === 'command' ? target : (saved.command || ''),
+    url: mode === 'webhook' ? target : (saved.url || ''),
+    timeout_secs: parseNonNegativeInt(el.postProcessTimeoutInput?.value, 10) || 10
+  };
+}
+
+function applyPostProcessConfigToUi(config) {
+  const postProcess = config || {};
+  const mode = postProcess.mode || 'off';
+  if (el.postProcessModeSelect) el.postProcessModeSelect.value = mode;
+  if (el.postProcessTargetInput) {
+    el.postProcessTargetInput.value = mode === 'webhook' ? (postProcess.url || '') : (postProcess.command || '');
+  }
+  if (el.postProcessTimeoutInput) el.postProcessTimeoutInput.value = postProcess.timeout_secs ?? 10;
+  updatePostProcessPlaceholder();
+}
+
+function updatePostProcessPlaceholder() {
+  if (!el.postProcessTargetInput) return;
+  const mode = el.postProcessModeSelect?.value || 'off';
+  el.postProcessTargetInput.placeholder = mode === 'webhook'
+    ? 'Webhook URL，例如 http://127.0.0.1:8080/hook'
+    : '命令，例如 python3 ~/format.py';
+  el.postProcessTargetInput.disabled = mode === 'off';
+}
+
+const ITN_RULES = ['numbers', 'ordinals', 'dates', 'times', 'percentages', 'currencies', 'units'];
+
+function buildItnConfigFromUi() {
+  const config = { enabled: el.itnSwitch ? el.itnSwitch.checked : false };
+  ITN_RULES.forEach((rule) => {
+    const input = document.getElementById(`itn-${rule}-switch`);
+    config[rule] = input ? input.checked : true;
+  });
+  return config;
+}
+
+function applyItnConfigToUi(config) {
+  const itn = config || {};
+  if (el.itnSwitch) el.itnSwitch.checked = !!itn.enabled;
+  ITN_RULES.forEach((rule) => {
+    const input = document.getElementById(`itn-${rule}-switch`);
+    if (input) input.checked = itn[rule] !== false;
+  });
+  updateItnRulesVisibility();
+}
+
+function updateItnRulesVisibility() {
+  if (el.itnRules) el.itnRules.classList.toggle('hidden', !el.itnSwitch?.checked);
+}
+
+function buildRedactionConfigFromUi() {
+  const customMode = el.redactCustomSelect?.value || 'log';
+  const custom = (el.redactCustomInput?.value || '')
+    .split('\n')
+    .map((line) => line.trim())
+    .filter(Boolean)
+    .map((line) => {
+      const index = line.indexOf('=');
+      if (index < 0) return { name: '', pattern: line, mode: customMode };
+      return {
+        name: line.slice(0, index).trim(),
+        pattern: line.slice(index + 1).trim(),
+        mode: customMode
+      };
+    });
+
+  return {
+    email: el.redactEmailSelect?.value || 'off',
+    phone: el.redactPhoneSelect?.value || 'off',
+    credit_card: el.redactCreditCardSelect?.value || 'off',
+    iban: el.redactIbanSelect?.value || 'off',
+    api_key: el.redactApiKeySelect?.value || 'off',
+    custom
+  };
+}
+
+function applyRedactionConfigToUi(redaction) {
+  const config = redaction || {};
+  if (el.redactEmailSelect) el.redactEmailSelect.value = config.email || 'off';
+  if (el.redactPhoneSelect) el.redactPhoneSelect.value = config.phone || 'off';
+  if (el.redactCreditCardSelect) el.redactCreditCardSelect.value = config.credit_card || 'off';
+  if (el.redactIbanSelect) el.redactIbanSelect.value = config.iban || 'off';
+  if (el.redactApiKeySelect) el.redactApiKeySelect.value = config.api_key || 'off';
+
+  const custom = Array.isArray(config.custom) ? config.custom : [];
+  if (el.redactCustomSelect) el.redactCustomSelect.value = custom[0]?.mode || 'log';
+  if (el.redactCustomInput) {
+    el.redactCustomInput.value = custom
+      .map((rule) => (rule.name ? `${rule.name} = ${rule.pattern}` : rule.pattern))
+      .join('\n');
+  }
+}
+
+async function loadDiagnosticLogs() {
+  if (!el.diagnosticLogsViewer) return;
+  try {
+    const lines = await invoke('get_diagnostic_logs', { lines: 500 });
+    el.diagnosticLogsViewer.textContent = lines.length ? lines.join('\n') : 'No diagnostic logs yet.';
+  } catch (e) {
+    el.diagnosticLogsViewer.textContent = `Failed to load logs: ${e}`;
+  }
+  el.diagnosticLogsViewer.hidden = false;
+  el.diagnosticLogsViewer.scrollTop = el.diagnosticLogsViewer.scrollHeight;
+}
+
+function parseNonNegativeInt(value, fallback) {
+  const parsed = Number.parseInt(value, 10);
+  return Number.isFinite(parsed) && parsed >= 0 ? parsed : fallback;
+}
+
 function formatProviderName(provider) {
   return normalizeProvider(provider) === PROVIDER_SILICONFLOW ? 'SiliconFlow' : 'OpenRouter';
 }
@@ -1257,6 +1705,285 @@ async function loadLogDirPath() {
   }
 }
 
+function renderProfiles(list) {
+  if (!el.profileSelect || !list) return;
+  const profiles = Array.isArray(list.profiles) ? list.profiles : [];
+  el.profileSelect.innerHTML = '';
+  for (const name of profiles) {
+    const option = document.createElement('option');
+    option.value = name;
+    option.textContent = name;
+    el.profileSelect.appendChild(option);
+  }
+  el.profileSelect.value = list.active || '';
+  state.activeProfile = list.active || '';
+  updateProfileButtons();
+}
+
+function updateProfileButtons() {
+  const isActive = el.profileSelect?.value === state.activeProfile;
+  if (el.profileActivateBtn) el.profileActivateBtn.disabled = isActive;
+  if (el.profileDeleteBtn) el.profileDeleteBtn.disabled = isActive;
+}
+
+async function loadProfiles() {
+  try {
+    renderProfiles(await invoke('list_profiles'));
+  } catch (e) {
+    console.error('Load profiles failed', e);
+  }
+}
+
+async function runProfileCommand(command, args) {
+  try {
+    renderProfiles(await invoke(command, args));
+  } catch (e) {
+    window.alert(`${e}`);
+    await loadProfiles();
+  }
+}
+
+function initProfileControls() {
+  if (el.profileSelect) {
+    el.profileSelect.addEventListener('change', updateProfileButtons);
+  }
+
+  if (el.profileActivateBtn) {
+    el.profileActivateBtn.addEventListener('click', async () => {
+      const name = el.profileSelect?.value;
+      if (!name) return;
+      await runProfileCommand('activate_profile', { name });
+      await loadConfig();
+    });
+  }
+
+  if (el.profileNewBtn) {
+    el.profileNewBtn.addEventListener('click', async () => {
+      const name = window.prompt('New profile name (copies the current settings):');
+      if (!name) return;
+      await runProfileCommand('create_profile', { name });
+    });
+  }
+
+  if (el.profileRenameBtn) {
+    el.profileRenameBtn.addEventListener('click', async () => {
+      const oldName = el.profileSelect?.value;
+      if (!oldName) return;
+      const newName = window.prompt('Rename profile:', oldName);
+      if (!newName || newName === oldName) return;
+      await runProfileCommand('rename_profile', { oldName, newName });
+    });
+  }
+
+  if (el.profileDeleteBtn) {
+    el.profileDeleteBtn.addEventListener('click', async () => {
+      const name = el.profileSelect?.value;
+      if (!name) return;
+      if (!window.confirm(`Delete profile "${name}"?`)) return;
+      await runProfileCommand('delete_profile', { name });
+    });
+  }
+}
+
+function showSettingsTransferStatus(text) {
+  if (el.settingsTransferStatus) el.settingsTransferStatus.textContent = text;
+}
+
+function formatImportPreview(preview) {
+  const lines = [];
+  const source = [preview.app_version && `v${preview.app_version}`, preview.exported_at].filter(Boolean).join(', ');
+  if (source) lines.push(`Exported: ${source}`);
+
+  const changes = preview.config_changes || [];
+  lines.push(changes.length ? `Settings changes (${changes.length}):` : 'Settings: no changes');
+  changes.slice(0, 12).forEach((change) => {
+    lines.push(`  ${change.field}: ${JSON.stringify(change.current)} → ${JSON.stringify(change.incoming)}`);
+  });
+  if (changes.length > 12) lines.push(`  …and ${changes.length - 12} more`);
+  if (preview.includes_keys) lines.push('API keys: included and will replace the current keys');
+
+  const merge = preview.corrections || {};
+  lines.push(`Corrections: ${merge.added || 0} new, ${merge.moved || 0} re-mapped, ${merge.unchanged || 0} unchanged`);
+
+  if (preview.profiles_added?.length) lines.push(`New profiles: ${preview.profiles_added.join(', ')}`);
+  if (preview.profiles_replaced?.length) lines.push(`Replaced profiles: ${preview.profiles_replaced.join(', ')}`);
+
+  const shortcuts = preview.shortcuts || {};
+  if (shortcuts.shortcut) lines.push(`Shortcut: ${shortcuts.shortcut}`);
+  if (shortcuts.cancel_shortcut != null) lines.push(`Cancel shortcut: ${shortcuts.cancel_shortcut || '(disabled)'}`);
+  return lines.join('\n');
+}
+
+async function applyImportedShortcuts(shortcuts) {
+  if (!shortcuts) return;
+  if (shortcuts.shortcut) {
+    try {
+      const applied = await setShortcut(shortcuts.shortcut);
+      if (el.shortcutLabel) el.shortcutLabel.textContent = applied;
+    } catch (e) {
+      console.error('Apply imported shortcut failed', e);
+    }
+  }
+  if (shortcuts.cancel_shortcut != null) {
+    try {
+      const applied = await applyCancelShortcut(shortcuts.cancel_shortcut);
+      if (el.cancelShortcutInput) el.cancelShortcutInput.value = applied;
+    } catch (e) {
+      console.error('Apply imported cancel shortcut failed', e);
+    }
+  }
+}
+
+async function importSettingsFile(file) {
+  try {
+    const content = await file.text();
+    const preview = await invoke('import_settings', { content, apply: false });
+    if (!window.confirm(`Import settings from "${file.name}"?\n\n${formatImportPreview(preview)}`)) {
+      showSettingsTransferStatus('Import cancelled.');
+      return;
+    }
+    await invoke('import_settings', { content, apply: true });
+    await applyImportedShortcuts(preview.shortcuts);
+    await loadConfig();
+    await loadProfiles();
+    await loadCorrections();
+    showSettingsTransferStatus(`Imported ${file.name}`);
+  } catch (e) {
+    console.error('Import settings failed', e);
+    showSettingsTransferStatus(`Import failed: ${e}`);
+  }
+}
+
+function initSettingsTransfer() {
+  if (el.exportSettingsBtn) {
+    el.exportSettingsBtn.addEventListener('click', async () => {
+      el.exportSettingsBtn.disabled = true;
+      try {
+        const options = {
+          include_keys: !!el.settingsIncludeKeysSwitch?.checked,
+          shortcuts: {
+            shortcut: localStorage.getItem('aitotype_shortcut') || null,
+            cancel_shortcut: localStorage.getItem('aitotype_cancel_shortcut') || '',
+          },
+        };
+        const result = await invoke('export_settings', { options });
+        showSettingsTransferStatus(`Saved to ${result.path}`);
+      } catch (e) {
+        showSettingsTransferStatus(`Export failed: ${e}`);
+      } finally {
+        el.exportSettingsBtn.disabled = false;
+      }
+    });
+  }
+
+  if (el.importSettingsBtn && el.importSettingsFile) {
+    el.importSettingsBtn.addEventListener('click', () => el.importSettingsFile.click());
+    el.importSettingsFile.addEventListener('change', async () => {
+      const file = el.importSettingsFile.files?.[0];
+      el.importSettingsFile.value = '';
+      if (file) await importSettingsFile(file);
+    });
+  }
+}
+
+function showControlApiInfo(info) {
+  state.controlApiInfo = info;
+  if (!el.controlApiStatus) return;
+  const socket = info.socket_path ? ` or ${info.socket_path}` : '';
+  el.controlApiStatus.textContent = info.enabled
+    ? `${info.running ? 'Listening on' : 'Not running:'} ${info.url}${socket}`
+    : 'Lets foot pedals, Stream Deck or editor plugins start/stop dictation via 127.0.0.1 with a bearer token.';
+}
+
+async function loadControlApiInfo() {
+  try {
+    showControlApiInfo(await invoke('get_control_api_info'));
+  } catch (e) {
+    console.error('Load control API info failed', e);
+  }
+}
+
+function initControlApi() {
+  if (el.copyControlTokenBtn) {
+    el.copyControlTokenBtn.addEventListener('click', async () => {
+      if (!state.controlApiInfo) await loadControlApiInfo();
+      try {
+        await navigator.clipboard.writeText(state.controlApiInfo?.token || '');
+      } catch (e) {
+        console.error('Copy control token failed', e);
+      }
+    });
+  }
+
+  if (el.regenerateControlTokenBtn) {
+    el.regenerateControlTokenBtn.addEventListener('click', async () => {
+      if (!window.confirm('Regenerate the token? Scripts using the old token will stop working.')) return;
+      try {
+        showControlApiInfo(await invoke('regenerate_control_token'));
+      } catch (e) {
+        if (el.controlApiStatus) el.controlApiStatus.textContent = `Failed: ${e}`;
+      }
+    });
+  }
+}
+
+// Config fields that an administrator policy can lock, mapped to their controls.
+const POLICY_FIELD_CONTROLS = {
+  provider: 'providerSelect',
+  model: 'modelInput',
+  auto_write: 'autoWriteSwitch',
+  record_mode: 'recordModeSwitch',
+  enhancement_enabled: 'enhancementSwitch',
+  enhancement_provider: 'enhancementProviderSelect',
+  enhancement_model: 'enhancementModelInput',
+  enhancement_prompt: 'enhancementPromptInput',
+  audio_archive_enabled: 'audioArchiveSwitch',
+  audio_archive_max_age_days: 'audioArchiveMaxAgeInput',
+  audio_archive_max_total_mb: 'audioArchiveMaxSizeInput',
+  log_enabled: 'logEnabledSwitch',
+  log_max_age_days: 'logMaxAgeInput',
+  log_max_total_mb: 'logMaxSizeInput',
+  log_compress_old_files: 'logCompressSwitch',
+  post_process: 'postProcessModeSelect',
+  itn: 'itnSwitch',
+  diagnostic_log_level: 'diagnosticLogLevelSelect',
+  control_api_enabled: 'controlApiSwitch',
+  control_api_port: 'controlApiPortInput'
+};
+
+async function applyConfigPolicy() {
+  if (!state.configPolicy) {
+    try {
+      state.configPolicy = await invoke('get_config_policy');
+    } catch (e) {
+      console.error('Load config policy failed', e);
+      return;
+    }
+  }
+
+  const locked = state.configPolicy.locked || [];
+  locked.forEach((field) => {
+    const control = el[POLICY_FIELD_CONTROLS[field]];
+    if (!control) return;
+    control.disabled = true;
+    control.title = 'Managed by your organization';
+  });
+
+  if (el.policyHint) {
+    el.policyHint.hidden = locked.length === 0;
+    el.policyHint.textContent = locked.length
+      ? `Some settings are managed by your organization (${locked.join(', ')}).`
+      : '';
+  }
+}
+
+function showConfigResetNotice(notice) {
+  if (!notice || state.configResetNoticeShown) return;
+  state.configResetNoticeShown = true;
+  const backup = notice.backup_path ? `\n\nThe old file was backed up to:\n${notice.backup_path}` : '';
+  window.alert(`Your settings file could not be read and has been reset to defaults.\n\n${notice.error || ''}${backup}`);
+}
+
 async function loadConfig() {
   try {
     const config = await invoke('get_stt_config');
@@ -1267,13 +1994,14 @@ async function loadConfig() {
     state.enhancementProviderApiKeys[PROVIDER_OPENROUTER] = getStoredEnhancementApiKey(PROVIDER_OPENROUTER);
     state.enhancementProviderApiKeys[PROVIDER_SILICONFLOW] = getStoredEnhancementApiKey(PROVIDER_SILICONFLOW);
 
-    if (config.api_key && !state.providerApiKeys[provider]) {
+    // 后端只返回掩码后的 Key（真实值保存在系统密钥库），原样提交表示不修改
+    if (config.api_key) {
       state.providerApiKeys[provider] = config.api_key;
-      setStoredApiKey(provider, config.api_key);
+      setStoredApiKey(provider, '');
     }
-    if (config.enhancement_api_key && !state.enhancementProviderApiKeys[enhancementProvider]) {
+    if (config.enhancement_api_key) {
       state.enhancementProviderApiKeys[enhancementProvider] = config.enhancement_api_key;
-      setStoredEnhancementApiKey(enhancementProvider, config.enhancement_api_key);
+      setStoredEnhancementApiKey(enhancementProvider, '');
     }
 
     state.currentProvider = provider;
@@ -1313,6 +2041,20 @@ async function loadConfig() {
       el.enhancementPromptInput.value = (config.enhancement_prompt || '').trim() || DEFAULT_ENHANCEMENT_PROMPT;
     }
 
+    if (el.audioArchiveSwitch) el.audioArchiveSwitch.checked = !!config.audio_archive_enabled;
+    if (el.audioArchiveMaxAgeInput) el.audioArchiveMaxAgeInput.value = config.audio_archive_max_age_days ?? 30;
+    if (el.audioArchiveMaxSizeInput) el.audioArchiveMaxSizeInput.value = config.audio_archive_max_total_mb ?? 1024;
+    if (el.logEnabledSwitch) el.logEnabledSwitch.checked = config.log_enabled !== false;
+    if (el.logMaxAgeInput) el.logMaxAgeInput.value = config.log_max_age_days ?? 0;
+    if (el.logMaxSizeInput) el.logMaxSizeInput.value = config.log_max_total_mb ?? 0;
+    if (el.logCompressSwitch) el.logCompressSwitch.checked = !!config.log_compress_old_files;
+    applyRedactionConfigToUi(config.redaction);
+    applyPostProcessConfigToUi(config.post_process);
+    applyItnConfigToUi(config.itn);
+    if (el.diagnosticLogLevelSelect) el.diagnosticLogLevelSelect.value = config.diagnostic_log_level || 'info';
+    if (el.controlApiSwitch) el.controlApiSwitch.checked = !!config.control_api_enabled;
+    if (el.controlApiPortInput) el.controlApiPortInput.value = config.control_api_port ?? 47631;
+
     const recordMode = config.record_mode || localStorage.getItem('aitotype_record_mode') || 'toggle';
     state.recordMode = recordMode;
     localStorage.setItem('aitotype_record_mode', recordMode);
@@ -1322,7 +2064,9 @@ async function loadConfig() {
     updateInstructionText();
   } catch (e) { }
 
+  await applyConfigPolicy();
   await loadLogDirPath();
+  await loadControlApiInfo();
 }
 
 async function saveConfig(e) {
@@ -1693,6 +2437,48 @@ async function initShortcutRecorder() {
   });
 }
 
+async function applyCancelShortcut(shortcut) {
+  const normalized = shortcut ? normalizeShortcutString(shortcut) : '';
+  if (shortcut && !normalized) throw new Error('invalid shortcut');
+
+  const ready = await ensureShortcutPluginReady();
+  if (!ready) throw new Error('global shortcut plugin is not ready');
+
+  await invoke('update_cancel_shortcut', { shortcut: normalized || '' });
+  localStorage.setItem('aitotype_cancel_shortcut', normalized || '');
+  return normalized || '';
+}
+
+async function initCancelShortcut() {
+  if (!el.cancelShortcutInput) return;
+
+  const saved = localStorage.getItem('aitotype_cancel_shortcut') || '';
+  el.cancelShortcutInput.value = saved;
+  if (saved) {
+    try {
+      await applyCancelShortcut(saved);
+    } catch (e) {
+      console.error('Init cancel shortcut failed', e);
+    }
+  }
+
+  el.cancelShortcutInput.addEventListener('change', async () => {
+    const previous = localStorage.getItem('aitotype_cancel_shortcut') || '';
+    try {
+      const applied = await applyCancelShortcut(el.cancelShortcutInput.value.trim());
+      el.cancelShortcutInput.value = applied;
+      showShortcutHint(applied ? `取消快捷键已更新为 ${applied}。` : '已禁用取消快捷键。', 'success');
+    } catch (e) {
+      console.error('Apply cancel shortcut failed', e);
+      el.cancelShortcutInput.value = previous;
+      try {
+        await applyCancelShortcut(previous);
+      } catch (_) { }
+      showShortcutHint('取消快捷键设置失败，已恢复原设置。', 'error');
+    }
+  });
+}
+
 // ============ Init ============
 function updateInstructionText() {
   if (state.status !== 'idle') return;
@@ -1726,7 +2512,7 @@ async function init() {
       } else if (action === 'start') {
         startRecordingOnly(background);
       } else if (action === 'stop') {
-        stopAndTranscribeOnly();
+        stopAndTranscribeOnly(payload.paste !== false);
       }
     });
 
@@ -1734,10 +2520,41 @@ async function init() {
       const payload = event?.payload || {};
       showEnhancementFallbackHint(payload.reason);
     });
+
+    await listen('transcription-queued-event', () => {
+      loadQueuedJobs();
+    });
+
+    await listen('queued-transcription-done-event', (event) => {
+      const payload = event?.payload || {};
+      if (payload.text) addToHistory();
+      loadQueuedJobs();
+    });
+
+    await listen('pipeline-timings-event', (event) => {
+      const payload = event?.payload || {};
+      console.debug('Pipeline timings', payload.log_id, payload.timings);
+    });
+
+    await listen('profile-changed-event', async () => {
+      await loadProfiles();
+      await loadConfig();
+    });
+
+    await listen('config-reset-event', (event) => {
+      showConfigResetNotice(event?.payload);
+    });
+
+    await listen('transcription-cancelled-event', () => {
+      if (state.status === 'recording' || state.status === 'transcribing') {
+        handleTranscriptionCancelled();
+      }
+    });
   }
 
   // Shortcut
   await initShortcutRecorder();
+  await initCancelShortcut();
 
   // Navigation
   el.tabs.forEach(tab => {
@@ -1749,6 +2566,12 @@ async function init() {
     el.recordTrigger.addEventListener('click', toggleRecording);
   }
 
+  // History
+  if (el.historySearchInput) el.historySearchInput.addEventListener('input', scheduleHistorySearch);
+  if (el.historyStartDate) el.historyStartDate.addEventListener('change', () => loadHistory());
+  if (el.historyEndDate) el.historyEndDate.addEventListener('change', () => loadHistory());
+  if (el.historyExportBtn) el.historyExportBtn.addEventListener('click', exportHistory);
+
   // Result
   if (el.closeResultBtn) el.closeResultBtn.addEventListener('click', hideResult);
   if (el.copyBtn) {
@@ -1792,6 +2615,47 @@ async function init() {
     });
   }
 
+  if (el.wipeLogsBtn) {
+    el.wipeLogsBtn.addEventListener('click', async () => {
+      if (!window.confirm('Delete all transcription logs and history? This cannot be undone.')) return;
+      try {
+        await invoke('wipe_all_logs');
+      } catch (e) {
+        console.error('Wipe logs failed', e);
+      }
+      await loadHistory();
+    });
+  }
+
+  if (el.viewDiagnosticLogsBtn) {
+    el.viewDiagnosticLogsBtn.addEventListener('click', loadDiagnosticLogs);
+  }
+
+  if (el.copyDiagnosticLogsBtn) {
+    el.copyDiagnosticLogsBtn.addEventListener('click', async () => {
+      if (el.diagnosticLogsViewer?.hidden) await loadDiagnosticLogs();
+      try {
+        await navigator.clipboard.writeText(el.diagnosticLogsViewer?.textContent || '');
+      } catch (e) {
+        console.error('Copy diagnostic logs failed', e);
+      }
+    });
+  }
+
+  if (el.createDiagnosticsBundleBtn) {
+    el.createDiagnosticsBundleBtn.addEventListener('click', async () => {
+      el.createDiagnosticsBundleBtn.disabled = true;
+      try {
+        const result = await invoke('create_diagnostics_bundle', { options: {} });
+        if (el.diagnosticsBundleStatus) el.diagnosticsBundleStatus.textContent = `Saved to ${result.path}`;
+      } catch (e) {
+        if (el.diagnosticsBundleStatus) el.diagnosticsBundleStatus.textContent = `Failed: ${e}`;
+      } finally {
+        el.createDiagnosticsBundleBtn.disabled = false;
+      }
+    });
+  }
+
   if (el.recordModeSwitch) {
     el.recordModeSwitch.addEventListener('change', async () => {
       state.recordMode = el.recordModeSwitch.checked ? 'hold' : 'toggle';
@@ -1806,8 +2670,32 @@ async function init() {
   }
 
   // Load Config
+  initProfileControls();
+  initSettingsTransfer();
+  initControlApi();
+  if (el.itnSwitch) {
+    el.itnSwitch.addEventListener('change', updateItnRulesVisibility);
+  }
+
+  if (el.postProcessModeSelect) {
+    el.postProcessModeSelect.addEventListener('change', () => {
+      const saved = state.sttConfig?.post_process || {};
+      if (el.postProcessTargetInput) {
+        el.postProcessTargetInput.value = el.postProcessModeSelect.value === 'webhook' ? (saved.url || '') : (saved.command || '');
+      }
+      updatePostProcessPlaceholder();
+    });
+  }
+  await loadProfiles();
   await loadConfig();
+  try {
+    showConfigResetNotice(await invoke('take_config_reset_notice'));
+  } catch (e) {
+    console.error('Load config reset notice failed', e);
+  }
   await loadCorrections();
+  await loadQueuedJobs();
+  await loadHistory();
 
   // Load Auto Copy
   const savedAutoCopy = localStorage.getItem('aitotype_autocopy');